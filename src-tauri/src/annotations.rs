@@ -0,0 +1,179 @@
+//! Exporting the bookmarks and highlights of a book.
+
+use crate::library::{Book, Bookmark, Highlight};
+use chrono::{DateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+struct Annotations<'a> {
+    title: &'a str,
+    bookmarks: &'a [Bookmark],
+    highlights: &'a [Highlight],
+}
+
+pub fn export<Tz: TimeZone>(book: &Book, format: ExportFormat, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    match format {
+        ExportFormat::Markdown => to_markdown(book, tz),
+        ExportFormat::Json => to_json(book),
+    }
+}
+
+pub fn to_json(book: &Book) -> String {
+    let annotations = Annotations {
+        title: &book.title,
+        bookmarks: &book.bookmarks,
+        highlights: &book.highlights,
+    };
+    serde_json::to_string_pretty(&annotations).unwrap()
+}
+
+/// Bookmarks as a list and highlights as quotes, each with its note and where
+/// it is in the book.
+pub fn to_markdown<Tz: TimeZone>(book: &Book, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let position = |offset: usize| {
+        let total = book.total_character_count.max(1);
+        format!("{:.1}%", (offset.min(total) as f64) * 100.0 / total as f64)
+    };
+    let time = |time: u64| {
+        DateTime::from_timestamp(time as i64, 0)
+            .unwrap_or_default()
+            .with_timezone(tz)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+
+    let mut markdown = format!("# {}\n", book.title);
+
+    if !book.bookmarks.is_empty() {
+        markdown.push_str("\n## Bookmarks\n\n");
+        for bookmark in &book.bookmarks {
+            markdown.push_str(&format!(
+                "- {} · {} · {}\n",
+                position(bookmark.offset),
+                time(bookmark.created_time),
+                bookmark.snippet
+            ));
+            if let Some(note) = &bookmark.note {
+                markdown.push_str(&indent(note, "  "));
+            }
+        }
+    }
+
+    if !book.highlights.is_empty() {
+        markdown.push_str("\n## Highlights\n");
+        for highlight in &book.highlights {
+            markdown.push_str(&format!(
+                "\n{}\n*{} · {}*\n",
+                indent(&highlight.text, "> "),
+                position(highlight.start),
+                time(highlight.created_time)
+            ));
+            if let Some(note) = &highlight.note {
+                markdown.push_str(&format!("\n{note}\n"));
+            }
+        }
+    }
+
+    markdown
+}
+
+fn indent(text: &str, prefix: &str) -> String {
+    text.lines()
+        .map(|line| format!("{prefix}{line}\n"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn book() -> Book {
+        let mut book: Book = serde_json::from_str(
+            r#"{
+                "id": "",
+                "title": "Book",
+                "summary": "",
+                "total_character_count": 1000,
+                "progress": 0,
+                "last_read_time": 0
+            }"#,
+        )
+        .unwrap();
+        // 2026-10-19 12:00:00 UTC
+        let time = 1_792_411_200;
+        book.add_bookmark(
+            250,
+            Some(String::from("Come back")),
+            String::from("他推开门"),
+            time,
+        );
+        book.add_highlight(500, 510, String::from("第一行\n第二行"), None, time);
+        book.add_highlight(
+            100,
+            105,
+            String::from("早"),
+            Some(String::from("Why?")),
+            time,
+        );
+        book
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        assert_eq!(
+            to_markdown(&book(), &Utc),
+            "# Book
+
+## Bookmarks
+
+- 25.0% · 2026-10-19 12:00 · 他推开门
+  Come back
+
+## Highlights
+
+> 早
+
+*10.0% · 2026-10-19 12:00*
+
+Why?
+
+> 第一行
+> 第二行
+
+*50.0% · 2026-10-19 12:00*
+"
+        );
+        assert_eq!(
+            to_markdown(
+                &Book {
+                    bookmarks: vec![],
+                    highlights: vec![],
+                    ..book()
+                },
+                &Utc
+            ),
+            "# Book\n"
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let json: serde_json::Value = serde_json::from_str(&to_json(&book())).unwrap();
+        assert_eq!(json["title"], "Book");
+        assert_eq!(json["bookmarks"][0]["offset"], 250);
+        assert_eq!(json["highlights"][1]["text"], "第一行\n第二行");
+    }
+}
@@ -0,0 +1,97 @@
+//! Finding where chapters start in the text of a book, from heading lines such
+//! as `第十二章 归来`, `Chapter 12` or `楔子`.
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Longer lines are taken as text that happens to start like a heading.
+const MAX_HEADING_LENGTH: usize = 40;
+
+fn heading_regex() -> &'static Regex {
+    static HEADING: OnceLock<Regex> = OnceLock::new();
+    HEADING.get_or_init(|| {
+        Regex::new(concat!(
+            r"^(?:",
+            r"第[0-9０-９零〇一二三四五六七八九十百千万两]+[章回节節卷集部篇]",
+            r"|(?i:chapter\s+(?:[0-9]+|[ivxlcdm]+|one|two|three|four|five|six|seven|eight|nine|ten)\b)",
+            r"|(?i:prologue|epilogue)\b",
+            r"|(?:序章|序言|楔子|引子|尾声|尾聲|后记|後記|番外)",
+            r")"
+        ))
+        .unwrap()
+    })
+}
+
+pub fn is_heading(line: &str) -> bool {
+    let line = line.trim();
+    line.chars().count() <= MAX_HEADING_LENGTH && heading_regex().is_match(line)
+}
+
+/// Where each chapter starts, in UTF-16 code units like reading progress. Text
+/// before the first heading is not counted as a chapter.
+pub fn chapter_starts(content: &str) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if is_heading(line) {
+            starts.push(offset);
+        }
+        offset += line.encode_utf16().count();
+    }
+    starts
+}
+
+/// The start and end of the chapter `progress` is in, if it is in one.
+pub fn chapter_at(
+    chapter_starts: &[usize],
+    total: usize,
+    progress: usize,
+) -> Option<(usize, usize)> {
+    let next = chapter_starts.partition_point(|&start| start <= progress);
+    let start = *chapter_starts.get(next.checked_sub(1)?)?;
+    let end = chapter_starts.get(next).copied().unwrap_or(total);
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_heading() {
+        assert!(is_heading("第十二章 归来"));
+        assert!(is_heading("第12回"));
+        assert!(is_heading("  第一卷 风起  "));
+        assert!(is_heading("Chapter 3: The Road"));
+        assert!(is_heading("CHAPTER XII"));
+        assert!(is_heading("Chapter One"));
+        assert!(is_heading("Prologue"));
+        assert!(is_heading("楔子"));
+        assert!(!is_heading(
+            "第一次见到他的时候，他正坐在门口的台阶上晒太阳。"
+        ));
+        assert!(!is_heading("第二天"));
+        assert!(!is_heading("Chapters are hard to find."));
+        assert!(!is_heading("Chapter and verse."));
+        assert!(!is_heading(
+            "Chapter 3 is where everything changes, and this line is a sentence about it."
+        ));
+    }
+
+    #[test]
+    fn test_chapter_starts() {
+        let content = "书名\n第一章 开始\n正文😀\n第二章 继续\n正文";
+        assert_eq!(chapter_starts(content), [3, 15]);
+        assert!(chapter_starts("no chapters\nat all").is_empty());
+    }
+
+    #[test]
+    fn test_chapter_at() {
+        let starts = [10, 50];
+        assert_eq!(chapter_at(&starts, 100, 5), None);
+        assert_eq!(chapter_at(&starts, 100, 10), Some((10, 50)));
+        assert_eq!(chapter_at(&starts, 100, 49), Some((10, 50)));
+        assert_eq!(chapter_at(&starts, 100, 70), Some((50, 100)));
+        assert_eq!(chapter_at(&[], 100, 70), None);
+    }
+}
@@ -0,0 +1,178 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// How many lines of a book are used when previewing cleanup rules.
+pub const PREVIEW_SAMPLE_LINE_COUNT: usize = 500;
+
+/// A user-defined rule applied line by line to the raw text of imported books.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind")]
+pub enum CleanupRule {
+    /// Replace every match of `pattern` in a line with `replacement`. The
+    /// replacement may refer to capture groups, e.g. `$1`.
+    Replace {
+        pattern: String,
+        replacement: String,
+    },
+    /// Delete every line that matches `pattern`.
+    DeleteLine { pattern: String },
+}
+
+#[derive(Debug)]
+enum CompiledRule {
+    Replace(Regex, String),
+    DeleteLine(Regex),
+}
+
+/// Cleanup rules with their regexes compiled, ready to be applied.
+#[derive(Debug)]
+pub struct CompiledRules(Vec<CompiledRule>);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CleanupPreviewLine {
+    /// 1-based line number in the sample.
+    pub line_number: usize,
+    pub before: String,
+    /// `None` if the line is deleted.
+    pub after: Option<String>,
+}
+
+impl CompiledRules {
+    pub fn new(rules: &[CleanupRule]) -> Result<Self, regex::Error> {
+        let compiled = rules
+            .iter()
+            .map(|rule| match rule {
+                CleanupRule::Replace {
+                    pattern,
+                    replacement,
+                } => Ok(CompiledRule::Replace(
+                    Regex::new(pattern)?,
+                    replacement.clone(),
+                )),
+                CleanupRule::DeleteLine { pattern } => {
+                    Ok(CompiledRule::DeleteLine(Regex::new(pattern)?))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self(compiled))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Apply the rules in order to a single line. Returns `None` if the line
+    /// should be deleted.
+    pub fn apply_to_line(&self, line: &str) -> Option<String> {
+        let mut line = line.to_string();
+        for rule in &self.0 {
+            match rule {
+                CompiledRule::Replace(regex, replacement) => {
+                    if let Cow::Owned(replaced) = regex.replace_all(&line, replacement.as_str()) {
+                        line = replaced;
+                    }
+                }
+                CompiledRule::DeleteLine(regex) => {
+                    if regex.is_match(&line) {
+                        return None;
+                    }
+                }
+            }
+        }
+        Some(line)
+    }
+
+    /// Apply the rules to every line of the text.
+    pub fn apply(&self, text: &str) -> String {
+        if self.is_empty() {
+            return text.to_string();
+        }
+
+        let mut ret = String::with_capacity(text.len());
+        for line in text.lines() {
+            if let Some(line) = self.apply_to_line(line) {
+                ret.push_str(&line);
+                ret.push('\n');
+            }
+        }
+        ret
+    }
+
+    /// Apply the rules to the first lines of the text, and return the lines
+    /// that are changed or deleted.
+    pub fn preview(&self, text: &str) -> Vec<CleanupPreviewLine> {
+        text.lines()
+            .take(PREVIEW_SAMPLE_LINE_COUNT)
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let after = self.apply_to_line(line);
+                if after.as_deref() == Some(line) {
+                    return None;
+                }
+                Some(CleanupPreviewLine {
+                    line_number: i + 1,
+                    before: line.to_string(),
+                    after,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_rules() -> Vec<CleanupRule> {
+        vec![
+            CleanupRule::DeleteLine {
+                pattern: String::from(r"^\s*（本章完）\s*$"),
+            },
+            CleanupRule::Replace {
+                pattern: String::from(r"www\.[a-z0-9]+\.com"),
+                replacement: String::new(),
+            },
+            CleanupRule::Replace {
+                pattern: String::from(r"第(\d+)章"),
+                replacement: String::from("Chapter $1"),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_apply_rules_in_order() {
+        let rules = CompiledRules::new(&sample_rules()).unwrap();
+        let input = "第1章\n正文www.example.com内容\n（本章完）\n下一段\n";
+        assert_eq!(rules.apply(input), "Chapter 1\n正文内容\n下一段\n");
+    }
+
+    #[test]
+    fn test_preview_only_contains_changed_lines() {
+        let rules = CompiledRules::new(&sample_rules()).unwrap();
+        let input = "第1章\n正文\n（本章完）\n";
+        assert_eq!(
+            rules.preview(input),
+            vec![
+                CleanupPreviewLine {
+                    line_number: 1,
+                    before: String::from("第1章"),
+                    after: Some(String::from("Chapter 1")),
+                },
+                CleanupPreviewLine {
+                    line_number: 3,
+                    before: String::from("（本章完）"),
+                    after: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let rules = [CleanupRule::DeleteLine {
+            pattern: String::from("(unclosed"),
+        }];
+        assert!(CompiledRules::new(&rules).is_err());
+    }
+}
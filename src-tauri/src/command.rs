@@ -1,85 +1,232 @@
-use super::{config, fsm, library, listener};
-use std::{ops::DerefMut, sync::Mutex};
+use super::{
+    annotations, cleanup, config,
+    error::{Error, Result},
+    fonts, fsm, library, listener, logstream, normalize, placement, profile, search_index, stats,
+    tray, zhconv,
+};
+use std::{collections::HashMap, ops::DerefMut, sync::Mutex};
 use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
 
 #[tauri::command]
-pub fn start_changing_styles(app: AppHandle) {
+pub fn start_changing_styles(app: AppHandle) -> Result<()> {
     let fsm = app.state::<Mutex<fsm::Fsm>>();
     let mut fsm = fsm.lock().unwrap();
-    fsm.reset_and_pause();
+    fsm.reset_and_pause(&app);
 
-    let window_reader = get_reader_window(&app);
-
-    window_reader
-        .emit("start-changing-styles", ())
-        .expect("Cannot emit start-changing-styles");
-
-    window_reader
-        .set_ignore_cursor_events(false)
-        .expect("Cannot un-ignore cursor events");
+    let window_reader = get_reader_window(&app)?;
+    window_reader.emit("start-changing-styles", ())?;
+    window_reader.set_ignore_cursor_events(false)?;
+    Ok(())
 }
 
 #[tauri::command]
-pub fn end_changing_styles(app: AppHandle) {
-    end_changing_styles_aux(&app);
+pub fn end_changing_styles(app: AppHandle) -> Result<()> {
+    end_changing_styles_aux(&app)
 }
 
-pub fn end_changing_styles_aux(app: &AppHandle) {
+pub fn end_changing_styles_aux(app: &AppHandle) -> Result<()> {
+    snap_reader_to_anchor(app)?;
+
     let fsm = app.state::<Mutex<fsm::Fsm>>();
     let mut fsm = fsm.lock().unwrap();
     fsm.continue_from_pause();
 
-    let window_reader = get_reader_window(app);
-    window_reader
-        .emit("end-changing-styles", ())
-        .expect("Cannot emit end-changing-styles");
-
-    window_reader
-        .set_ignore_cursor_events(true)
-        .expect("Cannot ignore cursor events");
+    let window_reader = get_reader_window(app)?;
+    window_reader.emit("end-changing-styles", ())?;
+    window_reader.set_ignore_cursor_events(true)?;
+    Ok(())
 }
 
 #[tauri::command]
-pub fn persist_appearance(app: AppHandle) {
-    persist_appearance_aux(&app);
+pub fn persist_appearance(app: AppHandle) -> Result<()> {
+    persist_appearance_aux(&app)
 }
 
-pub fn persist_appearance_aux(app: &AppHandle) {
-    let window_reader = get_reader_window(app);
-    let position_reader = window_reader
-        .outer_position()
-        .expect("Cannot get window position");
-    let size_reader = window_reader.inner_size().expect("Cannot get window size");
-    let scale_factor_reader = window_reader
-        .scale_factor()
-        .expect("Cannot get scale factor");
+/// Where the windows are is also kept for the current monitor layout, to be
+/// restored when the monitors are arranged like this again.
+pub fn persist_appearance_aux(app: &AppHandle) -> Result<()> {
+    let monitor_layout = profile::monitor_layout(app)?;
 
     let app_state = app.state::<Mutex<config::Config>>();
     let mut config = app_state.lock().unwrap();
-    let appearance = &mut config.appearance;
+    read_window_geometry(app, &mut config.appearance)?;
+    store_window_geometry(&mut config, monitor_layout);
+
+    config::write_config(&config)?;
+    Ok(())
+}
+
+/// Update the appearance with where the windows are now.
+fn read_window_geometry(app: &AppHandle, appearance: &mut config::Appearance) -> Result<()> {
+    let window_reader = get_reader_window(app)?;
+    let position_reader = window_reader.outer_position()?;
+    let size_reader = window_reader.inner_size()?;
+    let scale_factor_reader = window_reader.scale_factor()?;
     appearance.position_reader = position_reader.to_logical(scale_factor_reader);
     appearance.size_reader = size_reader.to_logical(scale_factor_reader);
 
     if let Some(window_settings) = app.get_webview_window("settings") {
-        let position_settings = window_settings
-            .outer_position()
-            .expect("Cannot get window position");
-        let size_settings = window_settings
-            .inner_size()
-            .expect("Cannot get window size");
-
-        let scale_factor_settings = window_settings
-            .scale_factor()
-            .expect("Cannot get scale factor");
+        let position_settings = window_settings.outer_position()?;
+        let size_settings = window_settings.inner_size()?;
+        let scale_factor_settings = window_settings.scale_factor()?;
         appearance.position_settings = position_settings.to_logical(scale_factor_settings);
         appearance.size_settings = size_settings.to_logical(scale_factor_settings);
     }
+    Ok(())
+}
+
+fn store_window_geometry(config: &mut config::Config, monitor_layout: String) {
+    // There are no monitors to tell apart while they are being switched.
+    if monitor_layout.is_empty() {
+        return;
+    }
+    let window_geometry = config::WindowGeometry::of(&config.appearance);
+    config
+        .window_geometries
+        .insert(monitor_layout, window_geometry);
+}
+
+/// Move the windows to where the appearance has them, within the work areas of
+/// the monitors, and update the appearance to match.
+fn place_windows(app: &AppHandle, appearance: &mut config::Appearance) -> Result<()> {
+    let work_areas = placement::work_areas(app)?;
+
+    let (position_reader, size_reader) = placement::place_reader(
+        appearance.position_reader,
+        appearance.size_reader,
+        &appearance.reader_anchor,
+        &work_areas,
+    );
+    appearance.position_reader = position_reader;
+    appearance.size_reader = size_reader;
+    let window_reader = get_reader_window(app)?;
+    window_reader.set_position(position_reader)?;
+    window_reader.set_size(size_reader)?;
+
+    let (position_settings, size_settings) = placement::clamp_to_work_areas(
+        appearance.position_settings,
+        appearance.size_settings,
+        &work_areas,
+    );
+    appearance.position_settings = position_settings;
+    appearance.size_settings = size_settings;
+    if let Some(window_settings) = app.get_webview_window("settings") {
+        window_settings.set_position(position_settings)?;
+        window_settings.set_size(size_settings)?;
+    }
+    Ok(())
+}
+
+/// Move the reader to where its anchor has it, if it is anchored.
+fn anchor_reader(app: &AppHandle, appearance: &mut config::Appearance) -> Result<()> {
+    let window_reader = get_reader_window(app)?;
+    let scale_factor = window_reader.scale_factor()?;
+    let position = window_reader.outer_position()?.to_logical(scale_factor);
+    let size = window_reader.inner_size()?.to_logical(scale_factor);
+    let work_areas = placement::work_areas(app)?;
+
+    let anchored_position =
+        placement::work_area_of(position, size, &work_areas).and_then(|work_area| {
+            placement::anchored_position(&appearance.reader_anchor, size, work_area)
+        });
+    if let Some(anchored_position) = anchored_position {
+        window_reader.set_position(anchored_position)?;
+        appearance.position_reader = anchored_position;
+    }
+    Ok(())
+}
+
+/// Keep the reader anchored as it is resized, except while it is moved and
+/// resized by hand, after which it is anchored where it was left.
+pub fn anchor_reader_aux(app: &AppHandle) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    {
+        let fsm = app.state::<Mutex<fsm::Fsm>>();
+        let fsm = fsm.lock().unwrap();
+        if fsm.is_paused() {
+            return Ok(());
+        }
+    }
+    anchor_reader(app, &mut config.appearance)
+}
+
+/// Keep the anchor of the reader, with margins to where it was moved, and snap
+/// it to the edges of the anchor if it is close to them.
+fn snap_reader_to_anchor(app: &AppHandle) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    let anchor = config.appearance.reader_anchor.anchor;
+    if anchor == config::Anchor::None {
+        return Ok(());
+    }
+
+    let window_reader = get_reader_window(app)?;
+    let scale_factor = window_reader.scale_factor()?;
+    let position = window_reader.outer_position()?.to_logical(scale_factor);
+    let size = window_reader.inner_size()?.to_logical(scale_factor);
+    let work_areas = placement::work_areas(app)?;
+    let Some(work_area) = placement::work_area_of(position, size, &work_areas) else {
+        return Ok(());
+    };
+    config.appearance.reader_anchor = placement::anchor_at(anchor, position, size, work_area);
+    anchor_reader(app, &mut config.appearance)
+}
+
+#[tauri::command]
+pub fn update_reader_anchor(app: AppHandle, reader_anchor: config::ReaderAnchor) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.appearance.reader_anchor = reader_anchor;
+    anchor_reader(&app, &mut config.appearance)?;
+    config::write_config(&config)?;
+    Ok(())
+}
+
+/// Apply the profile of a new monitor layout, or else move the windows to
+/// where they were when the monitors were last arranged like this. Either way,
+/// they are kept on screen.
+pub fn on_monitor_layout_changed_aux(app: &AppHandle, monitor_layout: &str) -> Result<()> {
+    if apply_profile_for_monitor_layout_aux(app, monitor_layout)? {
+        return Ok(());
+    }
+
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    match config.window_geometries.get(monitor_layout).copied() {
+        Some(window_geometry) => window_geometry.apply_to(&mut config.appearance),
+        None => read_window_geometry(app, &mut config.appearance)?,
+    }
+    place_windows(app, &mut config.appearance)
+}
+
+/// Center the reader on the primary monitor, for when it is lost off screen.
+pub fn bring_reader_to_primary_monitor_aux(app: &AppHandle) -> Result<()> {
+    let work_areas = placement::work_areas(app)?;
+    let Some(primary) = work_areas.first() else {
+        return Ok(());
+    };
+    let monitor_layout = profile::monitor_layout(app)?;
+
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    read_window_geometry(app, &mut config.appearance)?;
+    let (position_reader, size_reader) =
+        placement::center_in(config.appearance.size_reader, primary);
+    config.appearance.position_reader = position_reader;
+    config.appearance.size_reader = size_reader;
+
+    let window_reader = get_reader_window(app)?;
+    window_reader.set_position(position_reader)?;
+    window_reader.set_size(size_reader)?;
 
-    config::write_config(&config);
+    store_window_geometry(&mut config, monitor_layout);
+    config::write_config(&config)?;
+    Ok(())
 }
 
 #[tauri::command]
-pub fn persist_basic_control_mode(app: AppHandle, mode: config::ControlBasicMode) {
+pub fn persist_basic_control_mode(app: AppHandle, mode: config::ControlBasicMode) -> Result<()> {
     let config = app.state::<Mutex<config::Config>>();
     let mut config = config.lock().unwrap();
     config.control.is_advanced = false;
@@ -91,7 +238,8 @@ pub fn persist_basic_control_mode(app: AppHandle, mode: config::ControlBasicMode
     fsm.set_show_hide_with_basic_control(mode, basic_control.show_hide);
     basic_control.mode = mode;
 
-    config::write_config(&config);
+    config::write_config(&config)?;
+    Ok(())
 }
 
 #[tauri::command]
@@ -99,7 +247,7 @@ pub fn persist_basic_control_key_button(
     app: AppHandle,
     name: String,
     key_button: listener::KeyButton,
-) {
+) -> Result<()> {
     let config = app.state::<Mutex<config::Config>>();
     let mut config = config.lock().unwrap();
     config.control.is_advanced = false;
@@ -121,10 +269,54 @@ pub fn persist_basic_control_key_button(
             fsm.set_prev_page_with_basic_control(key_button);
             basic_control.prev_page = key_button;
         }
-        _ => panic!("Unknown name"),
+        "next_profile" => {
+            fsm.set_next_profile_with_basic_control(Some(key_button));
+            basic_control.next_profile = Some(key_button);
+        }
+        "add_bookmark" => {
+            fsm.set_add_bookmark_with_basic_control(Some(key_button));
+            basic_control.add_bookmark = Some(key_button);
+        }
+        "go_back" => {
+            fsm.set_go_back_with_basic_control(Some(key_button));
+            basic_control.go_back = Some(key_button);
+        }
+        _ => return Err(Error::UnknownControlName(name)),
     };
 
-    config::write_config(&config);
+    config::write_config(&config)?;
+    Ok(())
+}
+
+/// Only the controls that are not bound by default can be unbound.
+#[tauri::command]
+pub fn unbind_basic_control_key_button(app: AppHandle, name: String) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.control.is_advanced = false;
+    let basic_control = &mut config.control.basic;
+
+    let fsm = app.state::<Mutex<fsm::Fsm>>();
+    let mut fsm = fsm.lock().unwrap();
+
+    match name.as_str() {
+        "next_profile" => {
+            fsm.set_next_profile_with_basic_control(None);
+            basic_control.next_profile = None;
+        }
+        "add_bookmark" => {
+            fsm.set_add_bookmark_with_basic_control(None);
+            basic_control.add_bookmark = None;
+        }
+        "go_back" => {
+            fsm.set_go_back_with_basic_control(None);
+            basic_control.go_back = None;
+        }
+        _ => return Err(Error::UnknownControlName(name)),
+    };
+
+    config::write_config(&config)?;
+    Ok(())
 }
 
 #[tauri::command]
@@ -134,156 +326,1098 @@ pub fn get_config(app: AppHandle) -> config::Config {
     config.clone()
 }
 
+/// How the config was recovered at startup, if it couldn't be parsed.
 #[tauri::command]
-pub fn get_books(app: AppHandle) -> Vec<library::Book> {
+pub fn get_config_recovery(app: AppHandle) -> Option<config::ConfigRecovery> {
+    let recovery = app.state::<Mutex<Option<config::ConfigRecovery>>>();
+    let recovery = recovery.lock().unwrap();
+    recovery.clone()
+}
+
+#[tauri::command]
+pub fn dismiss_config_recovery(app: AppHandle) {
+    let recovery = app.state::<Mutex<Option<config::ConfigRecovery>>>();
+    let mut recovery = recovery.lock().unwrap();
+    *recovery = None;
+}
+
+/// How the library was recovered at startup, if its metadata couldn't be
+/// parsed.
+#[tauri::command]
+pub fn get_library_recovery(app: AppHandle) -> Option<library::LibraryRecovery> {
+    let recovery = app.state::<Mutex<Option<library::LibraryRecovery>>>();
+    let recovery = recovery.lock().unwrap();
+    recovery.clone()
+}
+
+#[tauri::command]
+pub fn dismiss_library_recovery(app: AppHandle) {
+    let recovery = app.state::<Mutex<Option<library::LibraryRecovery>>>();
+    let mut recovery = recovery.lock().unwrap();
+    *recovery = None;
+}
+
+#[tauri::command]
+pub fn get_books(app: AppHandle) -> Vec<library::LibraryBook> {
     let books_aux = app.state::<Mutex<library::BooksAux>>();
     let books_aux = books_aux.lock().unwrap();
-    books_aux.books.clone()
+    library_books(&app, &books_aux.books)
+}
+
+/// The books with how long they may take to finish, at the recent reading
+/// speed.
+fn library_books(app: &AppHandle, books: &[library::Book]) -> Vec<library::LibraryBook> {
+    let speed = {
+        let recorder = app.state::<Mutex<stats::Recorder>>();
+        let recorder = recorder.lock().unwrap();
+        stats::recent_reading_speed(recorder.sessions(), stats::now())
+    };
+    books
+        .iter()
+        .map(|book| library::LibraryBook {
+            book: book.clone(),
+            time_left: speed.map(|speed| stats::time_left(book, speed)),
+        })
+        .collect()
 }
 
 #[tauri::command]
-pub fn change_book(app: AppHandle, title: String) -> Vec<library::Book> {
+pub fn change_book(app: AppHandle, id: String) -> Result<Vec<library::LibraryBook>> {
+    let config = app.state::<Mutex<config::Config>>();
+    let config = config.lock().unwrap();
+
     let books_aux = app.state::<Mutex<library::BooksAux>>();
     let mut books_aux = books_aux.lock().unwrap();
-    let library::BooksAux {
-        books,
-        title_to_index,
-        old_progress,
-    } = books_aux.deref_mut();
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    if index == 0 {
+        return Ok(library_books(&app, &books_aux.books));
+    }
+
+    books_aux.move_to_front(index);
+    library::write_books_to_disk(&books_aux.books)?;
+
+    emit_book_changed(&app, &config, &mut books_aux)?;
 
-    let original_index = *title_to_index.get(&title).expect("Book not found");
-    if original_index == 0 {
-        return books.clone();
+    Ok(library_books(&app, &books_aux.books))
+}
+
+/// Make a book the one being read, at `offset`.
+fn jump_to(
+    app: &AppHandle,
+    config: &config::Config,
+    books_aux: &mut library::BooksAux,
+    index: usize,
+    offset: usize,
+) -> Result<()> {
+    books_aux.jump(index, offset, stats::now());
+    library::write_books_to_disk(&books_aux.books)?;
+    emit_book_changed(app, config, books_aux)
+}
+
+/// Go back to where a book was read before its progress moved far away, such
+/// as by holding the next page key or jumping to a bookmark.
+#[tauri::command]
+pub fn go_back(app: AppHandle, id: String) -> Result<Vec<library::LibraryBook>> {
+    let config = app.state::<Mutex<config::Config>>();
+    let config = config.lock().unwrap();
+
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    go_back_in(&app, &config, &mut books_aux, index)?;
+
+    Ok(library_books(&app, &books_aux.books))
+}
+
+/// Go back in the book being read, from a hotkey.
+pub fn go_back_aux(app: &AppHandle) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let config = config.lock().unwrap();
+
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+    if books_aux.books.is_empty() {
+        return Ok(());
     }
+    go_back_in(app, &config, &mut books_aux, 0)
+}
 
-    books[0..=original_index].rotate_right(1);
-    *title_to_index.get_mut(&title).unwrap() = 0;
-    #[allow(clippy::needless_range_loop)]
-    for i in 1..=original_index {
-        *title_to_index.get_mut(&books[i].title).unwrap() = i;
+fn go_back_in(
+    app: &AppHandle,
+    config: &config::Config,
+    books_aux: &mut library::BooksAux,
+    index: usize,
+) -> Result<()> {
+    if !books_aux.go_back(index) {
+        return Err(Error::HistoryEmpty);
     }
-    *old_progress = books[0].progress;
+    library::write_books_to_disk(&books_aux.books)?;
+    emit_book_changed(app, config, books_aux)
+}
 
-    library::write_books_to_disk(books);
+#[tauri::command]
+pub fn get_bookmarks(app: AppHandle, id: String) -> Result<Vec<library::Bookmark>> {
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let books_aux = books_aux.lock().unwrap();
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    Ok(books_aux.books[index].bookmarks.clone())
+}
 
-    let window_reader = get_reader_window(&app);
-    let reader_book_info = library::ReaderBookInfo::new(&books[0]);
-    window_reader
-        .emit("book-changed", reader_book_info)
-        .expect("Cannot emit book-changed");
+/// Bookmark where a book was left off.
+#[tauri::command]
+pub fn add_bookmark(app: AppHandle, id: String, note: Option<String>) -> Result<library::Bookmark> {
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    add_bookmark_at_progress(&app, &mut books_aux, index, note)
+}
 
-    books.clone()
+/// Bookmark the page being read, from a hotkey.
+pub fn add_bookmark_aux(app: &AppHandle) -> Result<()> {
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+    if !books_aux.books.is_empty() {
+        add_bookmark_at_progress(app, &mut books_aux, 0, None)?;
+    }
+    Ok(())
+}
+
+fn add_bookmark_at_progress(
+    app: &AppHandle,
+    books_aux: &mut library::BooksAux,
+    index: usize,
+    note: Option<String>,
+) -> Result<library::Bookmark> {
+    let book = &mut books_aux.books[index];
+    let snippet = library::snippet_at(&library::get_reader_content_from_disk(book)?, book.progress);
+    let bookmark = book
+        .add_bookmark(book.progress, note, snippet, stats::now())
+        .clone();
+    library::write_books_to_disk(&books_aux.books)?;
+    app.emit("bookmarks-changed", &books_aux.books[index].id)?;
+    Ok(bookmark)
 }
 
 #[tauri::command]
-pub fn get_first_reader_book_info(app: AppHandle) -> Option<library::ReaderBookInfo> {
+pub fn update_bookmark_note(
+    app: AppHandle,
+    id: String,
+    bookmark_id: u64,
+    note: Option<String>,
+) -> Result<library::Bookmark> {
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    let bookmark = books_aux.books[index]
+        .bookmark_mut(bookmark_id)
+        .ok_or(Error::BookmarkNotFound(bookmark_id))?;
+    bookmark.note = library::sanitize_note(note);
+    let bookmark = bookmark.clone();
+    library::write_books_to_disk(&books_aux.books)?;
+    Ok(bookmark)
+}
+
+#[tauri::command]
+pub fn remove_bookmark(app: AppHandle, id: String, bookmark_id: u64) -> Result<()> {
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    if !books_aux.books[index].remove_bookmark(bookmark_id) {
+        return Err(Error::BookmarkNotFound(bookmark_id));
+    }
+    library::write_books_to_disk(&books_aux.books)?;
+    Ok(())
+}
+
+/// Read the book of a bookmark from there.
+#[tauri::command]
+pub fn jump_to_bookmark(
+    app: AppHandle,
+    id: String,
+    bookmark_id: u64,
+) -> Result<Vec<library::LibraryBook>> {
+    let config = app.state::<Mutex<config::Config>>();
+    let config = config.lock().unwrap();
+
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    let offset = books_aux.books[index]
+        .bookmarks
+        .iter()
+        .find(|bookmark| bookmark.id == bookmark_id)
+        .ok_or(Error::BookmarkNotFound(bookmark_id))?
+        .offset;
+    jump_to(&app, &config, &mut books_aux, index, offset)?;
+
+    Ok(library_books(&app, &books_aux.books))
+}
+
+#[tauri::command]
+pub fn get_highlights(app: AppHandle, id: String) -> Result<Vec<library::Highlight>> {
     let books_aux = app.state::<Mutex<library::BooksAux>>();
     let books_aux = books_aux.lock().unwrap();
-    let books = &books_aux.books;
-    books.first().map(library::ReaderBookInfo::new)
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    Ok(books_aux.books[index].highlights.clone())
 }
 
+/// Highlight text selected in the reader, from `start` to `end` in its
+/// content.
 #[tauri::command]
-pub fn update_progress(app: AppHandle, title: String, progress: usize) {
+pub fn add_highlight(
+    app: AppHandle,
+    id: String,
+    start: usize,
+    end: usize,
+    note: Option<String>,
+) -> Result<library::Highlight> {
     let books_aux = app.state::<Mutex<library::BooksAux>>();
     let mut books_aux = books_aux.lock().unwrap();
-    let index = *books_aux.title_to_index.get(&title).unwrap();
-    books_aux.books[index].progress = progress;
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    let start = books_aux.to_original_offset(index, start);
+    let end = books_aux.to_original_offset(index, end);
+
+    let book = &mut books_aux.books[index];
+    let content = library::get_reader_content_from_disk(book)?;
+    let text = library::text_between(&content, start, end).trim();
+    if text.is_empty() {
+        return Err(Error::EmptyHighlight);
+    }
+    let highlight = book
+        .add_highlight(start, end, text.to_string(), note, stats::now())
+        .clone();
+    library::write_books_to_disk(&books_aux.books)?;
+    app.emit("highlights-changed", &id)?;
+    Ok(highlight)
+}
+
+#[tauri::command]
+pub fn update_highlight_note(
+    app: AppHandle,
+    id: String,
+    highlight_id: u64,
+    note: Option<String>,
+) -> Result<library::Highlight> {
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    let highlight = books_aux.books[index]
+        .highlight_mut(highlight_id)
+        .ok_or(Error::HighlightNotFound(highlight_id))?;
+    highlight.note = library::sanitize_note(note);
+    let highlight = highlight.clone();
+    library::write_books_to_disk(&books_aux.books)?;
+    Ok(highlight)
+}
+
+#[tauri::command]
+pub fn remove_highlight(app: AppHandle, id: String, highlight_id: u64) -> Result<()> {
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    if !books_aux.books[index].remove_highlight(highlight_id) {
+        return Err(Error::HighlightNotFound(highlight_id));
+    }
+    library::write_books_to_disk(&books_aux.books)?;
+    Ok(())
+}
+
+/// Write the bookmarks and highlights of a book to `path`.
+#[tauri::command]
+pub fn export_annotations(
+    app: AppHandle,
+    id: String,
+    format: annotations::ExportFormat,
+    path: std::path::PathBuf,
+) -> Result<()> {
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let books_aux = books_aux.lock().unwrap();
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    let exported = annotations::export(&books_aux.books[index], format, &chrono::Local);
+    std::fs::write(path, exported)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn search_book(
+    app: AppHandle,
+    id: String,
+    query: String,
+    options: library::SearchOptions,
+) -> Result<library::SearchResult> {
+    let book = {
+        let books_aux = app.state::<Mutex<library::BooksAux>>();
+        let books_aux = books_aux.lock().unwrap();
+        let index = *books_aux
+            .id_to_index
+            .get(&id)
+            .ok_or(Error::BookNotFound(id.clone()))?;
+        books_aux.books[index].clone()
+    };
+    // Searched without holding the lock, as a book may take a while.
+    let content = library::get_reader_content_from_disk(&book)?;
+    library::search_book(&content, &query, options).map_err(Error::InvalidSearchPattern)
+}
+
+/// Find the books that have `query`, best first, and where it is in them.
+/// The case and width of letters are ignored.
+#[tauri::command]
+pub fn search_library(
+    app: AppHandle,
+    query: String,
+) -> Result<Vec<search_index::LibrarySearchHit>> {
+    let candidates = {
+        let search_index = app.state::<Mutex<search_index::SearchIndex>>();
+        let search_index = search_index.lock().unwrap();
+        search_index.search(&query)
+    };
+    let books: HashMap<String, library::Book> = {
+        let books_aux = app.state::<Mutex<library::BooksAux>>();
+        let books_aux = books_aux.lock().unwrap();
+        candidates
+            .iter()
+            .filter_map(|(id, _)| {
+                let index = *books_aux.id_to_index.get(id)?;
+                Some((id.clone(), books_aux.books[index].clone()))
+            })
+            .collect()
+    };
+
+    // The index only tells which books have every part of the query, so the
+    // query as a whole is looked for in each of them.
+    let options = library::SearchOptions {
+        ignore_case: true,
+        ignore_width: true,
+        regex: false,
+    };
+    let mut hits = Vec::new();
+    for (id, score) in candidates {
+        if hits.len() == search_index::MAX_LIBRARY_SEARCH_HITS {
+            break;
+        }
+        let Some(book) = books.get(&id) else {
+            continue;
+        };
+        // A book removed since it was indexed is simply not found.
+        let content = match library::get_reader_content_from_disk(book) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("Cannot search book {:?}: {e}", book.title);
+                continue;
+            }
+        };
+        let result =
+            library::search_book(&content, &query, options).map_err(Error::InvalidSearchPattern)?;
+        if result.matches.is_empty() {
+            continue;
+        }
+        hits.push(search_index::LibrarySearchHit {
+            book_id: id,
+            title: book.title.clone(),
+            score,
+            match_count: result.matches.len(),
+            is_truncated: result.is_truncated,
+            matches: result
+                .matches
+                .into_iter()
+                .take(search_index::MATCHES_PER_HIT)
+                .collect(),
+        });
+    }
+    Ok(hits)
+}
+
+fn spawn_index_books(app: &AppHandle, books: Vec<library::Book>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = index_books_aux(&app, &books) {
+            eprintln!("Cannot index books: {e}");
+        }
+    });
+}
+
+/// (Re)index books, which takes a while for long ones, and write the index.
+/// Books that can't be read, e.g. as they were removed meanwhile, are skipped.
+pub fn index_books_aux(app: &AppHandle, books: &[library::Book]) -> Result<()> {
+    if books.is_empty() {
+        return Ok(());
+    }
+    let search_index = app.state::<Mutex<search_index::SearchIndex>>();
+    for book in books {
+        let content = match library::get_reader_content_from_disk(book) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("Cannot index book {:?}: {e}", book.title);
+                continue;
+            }
+        };
+        let term_counts = search_index::term_counts(&content);
+        search_index
+            .lock()
+            .unwrap()
+            .insert_book(&book.id, term_counts);
+    }
+    let search_index = search_index.lock().unwrap();
+    search_index::write_search_index_to_disk(&search_index)?;
+    Ok(())
+}
+
+pub fn unindex_book_aux(app: &AppHandle, id: &str) -> Result<()> {
+    let search_index = app.state::<Mutex<search_index::SearchIndex>>();
+    let mut search_index = search_index.lock().unwrap();
+    if search_index.remove_book(id) {
+        search_index::write_search_index_to_disk(&search_index)?;
+    }
+    Ok(())
+}
+
+/// Index the books the index is missing, such as those imported before there
+/// was one, and drop the ones no longer in the library.
+pub fn sync_search_index_aux(app: &AppHandle) -> Result<()> {
+    let (missing, removed) = {
+        let books_aux = app.state::<Mutex<library::BooksAux>>();
+        let books_aux = books_aux.lock().unwrap();
+        let search_index = app.state::<Mutex<search_index::SearchIndex>>();
+        let search_index = search_index.lock().unwrap();
+        let missing: Vec<library::Book> = books_aux
+            .books
+            .iter()
+            .filter(|book| !search_index.contains(&book.id))
+            .cloned()
+            .collect();
+        let removed: Vec<String> = search_index
+            .book_ids()
+            .filter(|id| !books_aux.id_to_index.contains_key(*id))
+            .cloned()
+            .collect();
+        (missing, removed)
+    };
+    for id in removed {
+        unindex_book_aux(app, &id)?;
+    }
+    index_books_aux(app, &missing)
+}
+
+/// Read a book from `offset`, such as where a search found something.
+#[tauri::command]
+pub fn jump_to_offset(
+    app: AppHandle,
+    id: String,
+    offset: usize,
+) -> Result<Vec<library::LibraryBook>> {
+    let config = app.state::<Mutex<config::Config>>();
+    let config = config.lock().unwrap();
+
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    jump_to(&app, &config, &mut books_aux, index, offset)?;
+
+    Ok(library_books(&app, &books_aux.books))
+}
+
+#[tauri::command]
+pub fn get_first_reader_book_info(app: AppHandle) -> Result<Option<library::ReaderBookInfo>> {
+    let config = app.state::<Mutex<config::Config>>();
+    let config = config.lock().unwrap();
+
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+    Ok(books_aux.first_reader_book_info(&config.appearance.log_stream)?)
+}
+
+#[tauri::command]
+pub fn update_progress(app: AppHandle, id: String, progress: usize) -> Result<()> {
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    let old_progress = books_aux.books[index].progress;
+    books_aux.update_progress(index, progress, stats::now());
+
+    let recorder = app.state::<Mutex<stats::Recorder>>();
+    recorder.lock().unwrap().record_progress(
+        &id,
+        old_progress,
+        books_aux.books[index].progress,
+        stats::now(),
+    );
 
     // Note here we intentionally don't write the books to disk.
+    Ok(())
+}
+
+/// Start a reading session of the first book as the reader is shown, or end
+/// it as the reader is hidden.
+pub fn record_reader_shown_aux(app: &AppHandle, is_shown: bool) -> Result<()> {
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let books_aux = books_aux.lock().unwrap();
+
+    let recorder = app.state::<Mutex<stats::Recorder>>();
+    let mut recorder = recorder.lock().unwrap();
+    let now = stats::now();
+    if !is_shown {
+        if recorder.hide(now) {
+            stats::write_sessions_to_disk(recorder.sessions())?;
+
+            // The FSM may be locked here, and the config is locked before it.
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = refresh_goals_tooltip_aux(&app) {
+                    eprintln!("Cannot refresh tray tooltip: {e}");
+                }
+            });
+        }
+    } else if let Some(book) = books_aux.books.first() {
+        recorder.show(&book.id, now);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_reading_stats(app: AppHandle) -> stats::ReadingStats {
+    let recorder = app.state::<Mutex<stats::Recorder>>();
+    let recorder = recorder.lock().unwrap();
+    stats::reading_stats(recorder.sessions(), &chrono::Local, stats::now())
+}
+
+#[tauri::command]
+pub fn get_goals_summary(app: AppHandle) -> stats::GoalsSummary {
+    let config = app.state::<Mutex<config::Config>>();
+    let config = config.lock().unwrap();
+    goals_summary(&app, &config.goals)
+}
+
+#[tauri::command]
+pub fn update_reading_goals(
+    app: AppHandle,
+    goals: stats::ReadingGoals,
+) -> Result<stats::GoalsSummary> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.goals = goals;
+    config::write_config(&config)?;
+
+    let summary = goals_summary(&app, &config.goals);
+    tray::refresh_tooltip(&app, &summary)?;
+    Ok(summary)
+}
+
+fn goals_summary(app: &AppHandle, goals: &stats::ReadingGoals) -> stats::GoalsSummary {
+    let recorder = app.state::<Mutex<stats::Recorder>>();
+    let recorder = recorder.lock().unwrap();
+    stats::goals_summary(recorder.sessions(), goals, &chrono::Local, stats::now())
+}
+
+/// Show how far the goals are reached in the tray tooltip.
+pub fn refresh_goals_tooltip_aux(app: &AppHandle) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let config = config.lock().unwrap();
+    tray::refresh_tooltip(app, &goals_summary(app, &config.goals))?;
+    Ok(())
 }
 
 #[tauri::command]
 pub fn new_books(
     app: AppHandle,
-    book_infos: Vec<library::ReaderBookInfo>,
-) -> library::NewBooksResult {
+    book_infos: Vec<library::NewBookInfo>,
+) -> Result<library::NewBooksResult> {
+    let config = app.state::<Mutex<config::Config>>();
+    let config = config.lock().unwrap();
+
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+
+    // The cleanup rules are validated when they are updated, so they only fail
+    // to compile if the config file is edited by hand.
+    let pipeline =
+        library::ImportPipeline::new(&config.import).map_err(Error::InvalidCleanupRule)?;
+
+    let new_books_result =
+        library::new_and_standardize_books(&book_infos, &books_aux.id_to_index, &pipeline);
+    let index = books_aux.books.len().min(1);
+    books_aux
+        .books
+        .splice(index..index, new_books_result.successful.clone());
+    books_aux.reindex();
+
+    library::write_books_to_disk(&books_aux.books)?;
+    spawn_index_books(&app, new_books_result.successful.clone());
+    Ok(new_books_result)
+}
+
+#[tauri::command]
+pub fn update_cleanup_rules(
+    app: AppHandle,
+    cleanup_rules: Vec<cleanup::CleanupRule>,
+) -> Result<()> {
+    cleanup::CompiledRules::new(&cleanup_rules).map_err(Error::InvalidCleanupRule)?;
+
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.import.cleanup_rules = cleanup_rules;
+    config::write_config(&config)?;
+    Ok(())
+}
+
+/// Show what the given cleanup rules would change in the first lines of a text
+/// about to be imported, normalized first with the current import settings as
+/// `new_books` does.
+#[tauri::command]
+pub fn preview_cleanup_rules(
+    app: AppHandle,
+    content: String,
+    cleanup_rules: Vec<cleanup::CleanupRule>,
+) -> Result<Vec<cleanup::CleanupPreviewLine>> {
+    let config = app.state::<Mutex<config::Config>>();
+    let config = config.lock().unwrap();
+    let import = config::Import {
+        cleanup_rules,
+        ..config.import.clone()
+    };
+    let pipeline = library::ImportPipeline::new(&import).map_err(Error::InvalidCleanupRule)?;
+    Ok(pipeline.preview_cleanup(&content))
+}
+
+#[tauri::command]
+pub fn update_import_normalization(
+    app: AppHandle,
+    normalization: normalize::NormalizeOptions,
+) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.import.normalization = normalization;
+    config::write_config(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_import_chinese_conversion(
+    app: AppHandle,
+    chinese_conversion: zhconv::ChineseConversion,
+) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.import.chinese_conversion = chinese_conversion;
+    config::write_config(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_book_chinese_conversion(
+    app: AppHandle,
+    id: String,
+    chinese_conversion: zhconv::ChineseConversion,
+) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let config = config.lock().unwrap();
+
     let books_aux = app.state::<Mutex<library::BooksAux>>();
     let mut books_aux = books_aux.lock().unwrap();
 
     let library::BooksAux {
-        books,
-        title_to_index,
-        ..
+        books, id_to_index, ..
     } = books_aux.deref_mut();
 
-    let new_books_result = library::new_and_standardize_books(&book_infos, title_to_index);
-    books.splice(1..1, new_books_result.successful.clone());
-    for (i, book) in books.iter().enumerate() {
-        title_to_index.insert(book.title.clone(), i);
-    }
+    let index = *id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    books[index].chinese_conversion = chinese_conversion;
+    library::write_books_to_disk(books)?;
+    // The index is of the converted content, as searches are.
+    spawn_index_books(&app, vec![books[index].clone()]);
 
-    library::write_books_to_disk(books);
-    new_books_result
+    if index == 0 {
+        emit_book_changed(&app, &config, &mut books_aux)?;
+    }
+    Ok(())
 }
 
+/// Settings that are `None` fall back to the global ones.
 #[tauri::command]
-pub fn rename_book(app: AppHandle, original_title: String, new_title: String) {
+pub fn update_book_settings(
+    app: AppHandle,
+    id: String,
+    settings: library::BookSettings,
+) -> Result<library::BookSettings> {
     let books_aux = app.state::<Mutex<library::BooksAux>>();
     let mut books_aux = books_aux.lock().unwrap();
 
     let library::BooksAux {
-        books,
-        title_to_index,
-        ..
+        books, id_to_index, ..
     } = books_aux.deref_mut();
 
-    let index = title_to_index
-        .remove(&original_title)
-        .expect("Original title not found");
-    books[index].title = new_title.clone();
-    title_to_index.insert(new_title, index);
+    let index = *id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    let settings = settings.sanitize();
+    books[index].settings = settings.clone();
 
-    library::write_books_to_disk(books);
+    if index == 0 {
+        let window_reader = get_reader_window(&app)?;
+        window_reader.emit("book-settings-changed", settings.clone())?;
+    }
+
+    library::write_books_to_disk(books)?;
+    Ok(settings)
 }
 
+/// Returns the title actually stored, after sanitizing.
 #[tauri::command]
-pub fn remove_book(app: AppHandle, title: String) {
+pub fn rename_book(app: AppHandle, id: String, new_title: String) -> Result<String> {
     let books_aux = app.state::<Mutex<library::BooksAux>>();
     let mut books_aux = books_aux.lock().unwrap();
 
     let library::BooksAux {
-        books,
-        title_to_index,
-        ..
+        books, id_to_index, ..
     } = books_aux.deref_mut();
 
-    if books.len() <= 1 {
-        return;
+    let index = *id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    let new_title = library::sanitize_title(&new_title);
+    books[index].title = new_title.clone();
+
+    library::write_books_to_disk(books)?;
+    Ok(new_title)
+}
+
+#[tauri::command]
+pub fn remove_book(app: AppHandle, id: String) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let config = config.lock().unwrap();
+
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+
+    let index = *books_aux
+        .id_to_index
+        .get(&id)
+        .ok_or(Error::BookNotFound(id.clone()))?;
+    if books_aux.books.len() <= 1 {
+        return Err(Error::CannotRemoveLastBook);
     }
 
-    let index = *title_to_index.get(&title).unwrap();
-    books.remove(index);
-    title_to_index.remove(&title);
-    for (i, book) in books.iter().enumerate().skip(index) {
-        *title_to_index.get_mut(&book.title).unwrap() = i;
+    books_aux.remove(index);
+    library::write_books_to_disk(&books_aux.books)?;
+    library::remove_book_content_from_disk(&id)?;
+    {
+        let app = app.clone();
+        let id = id.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Err(e) = unindex_book_aux(&app, &id) {
+                eprintln!("Cannot remove book from search index: {e}");
+            }
+        });
     }
 
     if index == 0 {
-        let window_reader = get_reader_window(&app);
-        let reader_book_info = library::ReaderBookInfo::new(&books[0]);
-        window_reader
-            .emit("book-changed", reader_book_info)
-            .expect("Cannot emit book-changed");
+        emit_book_changed(&app, &config, &mut books_aux)?;
     }
 
-    library::write_books_to_disk(books);
+    Ok(())
 }
 
 #[tauri::command]
-pub fn update_text_size(app: AppHandle, text_size: usize) {
+pub fn update_text_size(app: AppHandle, text_size: usize) -> Result<()> {
     let config = app.state::<Mutex<config::Config>>();
     let mut config = config.lock().unwrap();
     config.appearance.text_size = text_size;
-    config::write_config(&config);
+    config::write_config(&config)?;
+    Ok(())
 }
 
 #[tauri::command]
-pub fn update_text_color(app: AppHandle, text_color: String) {
+pub fn update_text_color(app: AppHandle, text_color: String) -> Result<()> {
     let config = app.state::<Mutex<config::Config>>();
     let mut config = config.lock().unwrap();
     config.appearance.text_color = text_color;
-    config::write_config(&config);
+    config::write_config(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_font_family(app: AppHandle, font_family: String) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.appearance.font_family = font_family;
+    config::write_config(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_line_height(app: AppHandle, line_height: f64) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.appearance.line_height = line_height;
+    config::write_config(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_letter_spacing(app: AppHandle, letter_spacing: f64) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.appearance.letter_spacing = letter_spacing;
+    config::write_config(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_paragraph_spacing(app: AppHandle, paragraph_spacing: f64) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.appearance.paragraph_spacing = paragraph_spacing;
+    config::write_config(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_first_line_indent(app: AppHandle, first_line_indent: f64) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.appearance.first_line_indent = first_line_indent;
+    config::write_config(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_text_stroke(app: AppHandle, text_stroke: config::TextStroke) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.appearance.text_stroke = text_stroke;
+    config::write_config(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_text_shadow(app: AppHandle, text_shadow: config::TextShadow) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.appearance.text_shadow = text_shadow;
+    config::write_config(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_background_plate(
+    app: AppHandle,
+    background_plate: config::BackgroundPlate,
+) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.appearance.background_plate = background_plate;
+    config::write_config(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_camouflage(app: AppHandle, camouflage: config::Camouflage) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.appearance.camouflage = camouflage;
+    config::write_config(&config)?;
+    Ok(())
+}
+
+/// The reader is sent the book again, as its content changes.
+#[tauri::command]
+pub fn update_log_stream(app: AppHandle, log_stream: logstream::LogStreamOptions) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.appearance.log_stream = log_stream;
+    config::write_config(&config)?;
+
+    let books_aux = app.state::<Mutex<library::BooksAux>>();
+    let mut books_aux = books_aux.lock().unwrap();
+    emit_book_changed(&app, &config, &mut books_aux)
+}
+
+/// Save the current appearance, with where the reader is now, as a profile.
+/// A profile of the same name is overwritten.
+#[tauri::command]
+pub fn save_profile(app: AppHandle, name: String) -> Result<Vec<config::AppearanceProfile>> {
+    persist_appearance_aux(&app)?;
+
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    let appearance = config.appearance.clone();
+    match config
+        .profiles
+        .iter_mut()
+        .find(|profile| profile.name == name)
+    {
+        Some(profile) => profile.appearance = appearance,
+        None => config.profiles.push(config::AppearanceProfile {
+            name: name.clone(),
+            monitor_layout: None,
+            appearance,
+        }),
+    }
+    config.active_profile = Some(name);
+    config::write_config(&config)?;
+
+    tray::refresh_menu(&app, &config)?;
+    Ok(config.profiles.clone())
+}
+
+#[tauri::command]
+pub fn apply_profile(app: AppHandle, name: String) -> Result<()> {
+    apply_profile_aux(&app, &name)
+}
+
+/// Replace the appearance with the one of a profile, and move the reader to
+/// where it is in it.
+pub fn apply_profile_aux(app: &AppHandle, name: &str) -> Result<()> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    let profile = config
+        .profiles
+        .iter()
+        .find(|profile| profile.name == name)
+        .ok_or_else(|| Error::ProfileNotFound(name.to_string()))?;
+    let appearance = profile::appearance_of(profile, &config.appearance);
+    let is_log_stream_changed = appearance.log_stream != config.appearance.log_stream;
+    config.appearance = appearance;
+    config.active_profile = Some(name.to_string());
+    place_windows(app, &mut config.appearance)?;
+    config::write_config(&config)?;
+
+    app.emit("appearance-changed", &config.appearance)?;
+
+    if is_log_stream_changed {
+        let books_aux = app.state::<Mutex<library::BooksAux>>();
+        let mut books_aux = books_aux.lock().unwrap();
+        emit_book_changed(app, &config, &mut books_aux)?;
+    }
+
+    tray::refresh_menu(app, &config)?;
+    Ok(())
+}
+
+/// Apply the profile after the active one, if there is any profile.
+pub fn apply_next_profile_aux(app: &AppHandle) -> Result<()> {
+    let name = {
+        let config = app.state::<Mutex<config::Config>>();
+        let config = config.lock().unwrap();
+        profile::next_profile(&config.profiles, config.active_profile.as_deref())
+            .map(|profile| profile.name.clone())
+    };
+    match name {
+        Some(name) => apply_profile_aux(app, &name),
+        None => Ok(()),
+    }
+}
+
+/// Apply the profile of a monitor layout, unless it is already active, so that
+/// changes made since it was applied are kept. Returns whether it was applied.
+fn apply_profile_for_monitor_layout_aux(app: &AppHandle, monitor_layout: &str) -> Result<bool> {
+    let name = {
+        let config = app.state::<Mutex<config::Config>>();
+        let config = config.lock().unwrap();
+        profile::profile_for_monitor_layout(&config.profiles, monitor_layout)
+            .map(|profile| profile.name.clone())
+            .filter(|name| config.active_profile.as_ref() != Some(name))
+    };
+    match name {
+        Some(name) => apply_profile_aux(app, &name).map(|()| true),
+        None => Ok(false),
+    }
+}
+
+#[tauri::command]
+pub fn remove_profile(app: AppHandle, name: String) -> Result<Vec<config::AppearanceProfile>> {
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    config.profiles.retain(|profile| profile.name != name);
+    if config.active_profile.as_ref() == Some(&name) {
+        config.active_profile = None;
+    }
+    config::write_config(&config)?;
+
+    tray::refresh_menu(&app, &config)?;
+    Ok(config.profiles.clone())
+}
+
+/// Apply a profile whenever the monitors are arranged as they are now, or
+/// stop doing so. A monitor layout has at most one profile.
+#[tauri::command]
+pub fn update_profile_monitor_layout(
+    app: AppHandle,
+    name: String,
+    is_auto: bool,
+) -> Result<Vec<config::AppearanceProfile>> {
+    let monitor_layout = profile::monitor_layout(&app)?;
+
+    let config = app.state::<Mutex<config::Config>>();
+    let mut config = config.lock().unwrap();
+    if !config.profiles.iter().any(|profile| profile.name == name) {
+        return Err(Error::ProfileNotFound(name));
+    }
+    for profile in config.profiles.iter_mut() {
+        if profile.name == name {
+            profile.monitor_layout = is_auto.then(|| monitor_layout.clone());
+        } else if is_auto && profile.monitor_layout.as_ref() == Some(&monitor_layout) {
+            profile.monitor_layout = None;
+        }
+    }
+    config::write_config(&config)?;
+    Ok(config.profiles.clone())
+}
+
+#[tauri::command]
+pub fn get_monitor_layout(app: AppHandle) -> Result<String> {
+    Ok(profile::monitor_layout(&app)?)
+}
+
+/// Family names of the fonts installed on the system, for the font setting.
+#[tauri::command]
+pub async fn get_system_font_families() -> Result<Vec<String>> {
+    // Reading every font file takes a while, so it doesn't block the async
+    // runtime meanwhile.
+    let families = tauri::async_runtime::spawn_blocking(fonts::get_system_font_families).await?;
+    Ok(families)
 }
 
 #[tauri::command]
@@ -294,12 +1428,44 @@ pub fn update_frontend_listen_state(app: AppHandle, name: String, allow_wheel: b
     frontend_listen_state.allow_wheel = allow_wheel;
 }
 
+/// Translate the tray into the language of a BCP 47 tag, which the reader
+/// gives at start as the settings window does not always exist.
+#[tauri::command]
+pub fn set_tray_language(app: AppHandle, language: String) -> Result<()> {
+    {
+        let tray_language = app.state::<Mutex<tray::Language>>();
+        *tray_language.lock().unwrap() = tray::Language::from_tag(&language);
+    }
+
+    let config = app.state::<Mutex<config::Config>>();
+    let config = config.lock().unwrap();
+    tray::refresh_menu(&app, &config)?;
+    tray::refresh_tooltip(&app, &goals_summary(&app, &config.goals))?;
+    Ok(())
+}
+
 #[tauri::command]
 pub fn get_is_trial_version() -> bool {
     cfg!(feature = "trial")
 }
 
-fn get_reader_window(app: &AppHandle) -> WebviewWindow {
+/// Send the first book to the reader, after it changed or the way it is shown
+/// did.
+fn emit_book_changed(
+    app: &AppHandle,
+    config: &config::Config,
+    books_aux: &mut library::BooksAux,
+) -> Result<()> {
+    let window_reader = get_reader_window(app)?;
+    if let Some(reader_book_info) =
+        books_aux.first_reader_book_info(&config.appearance.log_stream)?
+    {
+        window_reader.emit("book-changed", reader_book_info)?;
+    }
+    Ok(())
+}
+
+fn get_reader_window(app: &AppHandle) -> Result<WebviewWindow> {
     app.get_webview_window("main")
-        .expect("Cannot get main window")
+        .ok_or(Error::Tauri(tauri::Error::WebviewNotFound))
 }
@@ -1,25 +1,201 @@
-use crate::{listener::KeyButton, DATA_ROOT_DIR};
+use crate::{
+    cleanup::CleanupRule, listener::KeyButton, logstream::LogStreamOptions,
+    normalize::NormalizeOptions, persist, stats::ReadingGoals, zhconv::ChineseConversion,
+    DATA_ROOT_DIR,
+};
 use rdev::Key;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::BTreeMap, io, path::PathBuf};
 use tauri::{LogicalPosition, LogicalSize};
 
 pub const CONFIG_FILENAME: &str = "config.json";
+/// Where a config file that can't be parsed is kept.
+pub const BAD_CONFIG_FILENAME: &str = "config.json.bad";
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// Bump this and append to `MIGRATIONS` whenever a change to `Config` needs
+/// more than default values for new fields to read older config files.
+pub const CURRENT_SCHEMA_VERSION: u64 = 1;
+
+/// `MIGRATIONS[i]` upgrades a config of schema version `i` to `i + 1`. They
+/// work on the raw JSON, so that they can handle fields that were renamed or
+/// restructured.
+const MIGRATIONS: &[fn(&mut Value)] = &[migrate_v0_to_v1];
+
+const _: () = assert!(MIGRATIONS.len() as u64 == CURRENT_SCHEMA_VERSION);
+
+// Every struct of the config falls back to its default values for missing
+// fields, so that adding a field doesn't need a migration.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
+    /// Configs written before versioning have none, which counts as 0.
+    pub schema_version: u64,
     pub appearance: Appearance,
+    pub profiles: Vec<AppearanceProfile>,
+    /// The name of the profile applied last, if any. Changes made to the
+    /// appearance since are only kept in the profile once it is saved again.
+    pub active_profile: Option<String>,
+    /// Where the windows were last, for every monitor layout they were used
+    /// with, see `profile::monitor_layout`.
+    pub window_geometries: BTreeMap<String, WindowGeometry>,
     pub control: Control,
+    pub import: Import,
+    pub goals: ReadingGoals,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Appearance {
     pub position_reader: LogicalPosition<f64>,
     pub size_reader: LogicalSize<f64>,
     pub position_settings: LogicalPosition<f64>,
     pub size_settings: LogicalSize<f64>,
+    /// Keeps `position_reader` relative to the work area of its monitor.
+    pub reader_anchor: ReaderAnchor,
 
     pub text_size: usize,
     pub text_color: String,
+    /// A CSS `font-family` value. Empty for the default font of the webview.
+    pub font_family: String,
+    /// A multiple of the text size.
+    pub line_height: f64,
+    /// In em, like the spacing and indent below.
+    pub letter_spacing: f64,
+    pub paragraph_spacing: f64,
+    pub first_line_indent: f64,
+
+    pub text_stroke: TextStroke,
+    pub text_shadow: TextShadow,
+    pub background_plate: BackgroundPlate,
+
+    pub camouflage: Camouflage,
+    /// Serve the content as fake application log lines.
+    pub log_stream: LogStreamOptions,
+}
+
+/// Where the reader is kept in the work area of its monitor, whatever its
+/// size and the resolution of the monitor.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Anchor {
+    /// The reader stays where it is put.
+    #[default]
+    None,
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+    /// Centered along the edge of the work area next to the taskbar, wherever
+    /// the taskbar is.
+    AboveTaskbar,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct ReaderAnchor {
+    pub anchor: Anchor,
+    /// In logical pixels, from the edge the reader is anchored to, or from
+    /// the middle if it is centered. For `Anchor::AboveTaskbar`, `margin_y` is
+    /// the distance from the taskbar and `margin_x` the offset along it.
+    pub margin_x: f64,
+    pub margin_y: f64,
+}
+
+/// The part of `Appearance` that is about where the windows are.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WindowGeometry {
+    pub position_reader: LogicalPosition<f64>,
+    pub size_reader: LogicalSize<f64>,
+    pub position_settings: LogicalPosition<f64>,
+    pub size_settings: LogicalSize<f64>,
+}
+
+impl Default for WindowGeometry {
+    fn default() -> Self {
+        Self::of(&Appearance::default())
+    }
+}
+
+impl WindowGeometry {
+    pub fn of(appearance: &Appearance) -> Self {
+        Self {
+            position_reader: appearance.position_reader,
+            size_reader: appearance.size_reader,
+            position_settings: appearance.position_settings,
+            size_settings: appearance.size_settings,
+        }
+    }
+
+    pub fn apply_to(&self, appearance: &mut Appearance) {
+        appearance.position_reader = self.position_reader;
+        appearance.size_reader = self.size_reader;
+        appearance.position_settings = self.position_settings;
+        appearance.size_settings = self.size_settings;
+    }
+}
+
+/// A named copy of the appearance, such as "laptop", "docked" or "meeting".
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct AppearanceProfile {
+    pub name: String,
+    /// The monitor layout this profile is applied for when it is connected,
+    /// see `profile::monitor_layout`.
+    pub monitor_layout: Option<String>,
+    /// The geometry of the settings window in it is ignored.
+    pub appearance: Appearance,
+}
+
+/// A preset that makes the reader look like another application. Except
+/// `None`, it replaces the font, colors and decorations above, and prefixes
+/// fake line numbers or timestamps to every paragraph.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Camouflage {
+    #[default]
+    None,
+    Terminal,
+    CodeEditor,
+    Spreadsheet,
+    Chat,
+}
+
+/// An outline around every glyph, drawn under the fill.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TextStroke {
+    pub enabled: bool,
+    /// In px.
+    pub width: f64,
+    pub color: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TextShadow {
+    pub enabled: bool,
+    /// In px, like the blur radius.
+    pub offset_x: f64,
+    pub offset_y: f64,
+    pub blur: f64,
+    pub color: String,
+}
+
+/// A plate behind the text of the reader window, which is otherwise
+/// transparent. Its transparency is the alpha channel of `color`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct BackgroundPlate {
+    pub enabled: bool,
+    pub color: String,
+    /// In px.
+    pub corner_radius: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default)]
@@ -31,19 +207,56 @@ pub enum ControlBasicMode {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ControlBasic {
     pub mode: ControlBasicMode,
     pub show_hide: KeyButton,
     pub next_page: KeyButton,
     pub prev_page: KeyButton,
+    /// Switch to the next appearance profile. Not bound by default.
+    pub next_profile: Option<KeyButton>,
+    /// Bookmark the page being read. Not bound by default.
+    pub add_bookmark: Option<KeyButton>,
+    /// Go back to where the book was read before a jump. Not bound by
+    /// default.
+    pub go_back: Option<KeyButton>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct Control {
     pub is_advanced: bool,
     pub basic: ControlBasic,
 }
 
+/// Options applied to the text of books when they are imported.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Import {
+    /// Applied to the raw text before anything else.
+    pub normalization: NormalizeOptions,
+    /// Applied in order to every line before the text is standardized.
+    pub cleanup_rules: Vec<CleanupRule>,
+    /// Applied to the standardized text. Unlike `Book::chinese_conversion`,
+    /// this changes the stored text.
+    pub chinese_conversion: ChineseConversion,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            appearance: Default::default(),
+            profiles: Vec::new(),
+            active_profile: None,
+            window_geometries: BTreeMap::new(),
+            control: Default::default(),
+            import: Default::default(),
+            goals: Default::default(),
+        }
+    }
+}
+
 impl Default for Appearance {
     fn default() -> Self {
         Self {
@@ -51,9 +264,54 @@ impl Default for Appearance {
             size_reader: LogicalSize::new(300.0, 400.0),
             position_settings: LogicalPosition::new(900.0, 150.0),
             size_settings: LogicalSize::new(800.0, 600.0),
+            reader_anchor: Default::default(),
 
             text_size: 16,
             text_color: String::from("#1cb8c3ff"),
+            font_family: String::new(),
+            line_height: 1.2,
+            letter_spacing: 0.0,
+            paragraph_spacing: 0.5,
+            first_line_indent: 0.0,
+
+            text_stroke: Default::default(),
+            text_shadow: Default::default(),
+            background_plate: Default::default(),
+
+            camouflage: Default::default(),
+            log_stream: Default::default(),
+        }
+    }
+}
+
+impl Default for TextStroke {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            width: 1.0,
+            color: String::from("#000000cc"),
+        }
+    }
+}
+
+impl Default for TextShadow {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            offset_x: 1.0,
+            offset_y: 1.0,
+            blur: 2.0,
+            color: String::from("#000000cc"),
+        }
+    }
+}
+
+impl Default for BackgroundPlate {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            color: String::from("#00000066"),
+            corner_radius: 4.0,
         }
     }
 }
@@ -65,37 +323,287 @@ impl Default for ControlBasic {
             show_hide: KeyButton::Key(Key::ControlLeft),
             next_page: KeyButton::Key(Key::Alt),
             prev_page: KeyButton::Key(Key::ShiftLeft),
+            next_profile: None,
+            add_bookmark: None,
+            go_back: None,
         }
     }
 }
 
+/// Version 0 covers every config written before `schema_version` existed. All
+/// the fields added until then have default values, so nothing has to change.
+fn migrate_v0_to_v1(_config: &mut Value) {}
+
+/// Run the migrations needed to bring a config to the current schema version.
+/// Configs from a newer version of the app are left as they are.
+pub fn migrate(config: &mut Value) {
+    let Some(object) = config.as_object_mut() else {
+        return;
+    };
+    let version = object
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    if version >= CURRENT_SCHEMA_VERSION {
+        return;
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(config);
+    }
+    if let Some(object) = config.as_object_mut() {
+        object.insert(
+            String::from("schema_version"),
+            Value::from(CURRENT_SCHEMA_VERSION),
+        );
+    }
+}
+
+pub fn parse_config(mut config: Value) -> Result<Config, serde_json::Error> {
+    migrate(&mut config);
+    serde_json::from_value(config)
+}
+
+/// What was done to load a config file that couldn't be parsed, to be shown
+/// in the settings window.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigRecovery {
+    /// Where the broken file is kept, if there was one.
+    pub bad_file: Option<PathBuf>,
+    /// Whether a backup could be used as is.
+    pub is_from_backup: bool,
+    /// Dotted paths of the fields that were reset to their defaults.
+    pub reset_fields: Vec<String>,
+    /// Whether nothing could be recovered, as neither the file nor any backup
+    /// is valid JSON, e.g. as it was cut off. Every field is then reset.
+    pub is_reset_to_defaults: bool,
+    /// Why the recovered config could not be saved, if it couldn't. It is
+    /// still used until the app quits.
+    pub write_error: Option<String>,
+    /// Whether the config was written by a newer version, so that it is not
+    /// saved to keep the fields this version doesn't know.
+    pub is_from_newer_version: bool,
+}
+
+#[derive(Debug)]
+pub struct ReadConfigResult {
+    pub config: Config,
+    /// Whether the configuration is newly created.
+    pub is_first_start: bool,
+    pub recovery: Option<ConfigRecovery>,
+}
+
 /// Read the configuration from the config file, or create a new one if it doesn't exist.
-/// Returns the configuration and a boolean indicating whether the configuration is newly created.
-pub fn read_config() -> (Config, bool) {
+///
+/// If the file can't be parsed, it is kept as `config.json.bad`, and the newest
+/// backup that can be parsed is used instead. If there is none, every valid
+/// field of the file is kept and the others are reset to their defaults.
+///
+/// A config written by a newer version is read, but reported, as it is never
+/// saved; see [`write_config`].
+pub fn read_config() -> ReadConfigResult {
     let data_dir = dirs::data_dir().unwrap().join(DATA_ROOT_DIR);
     if !data_dir.exists() {
         std::fs::create_dir_all(&data_dir).unwrap();
     }
 
     let config_file = data_dir.join(CONFIG_FILENAME);
-    if config_file.exists() {
-        let file = std::fs::File::open(config_file).expect("Cannot open config file");
-        let reader = std::io::BufReader::new(file);
-        (
-            serde_json::from_reader(reader).expect("Error parsing config file"),
-            false,
-        )
-    } else {
+    let config = persist::read_json(&config_file).and_then(|config| parse_config(config).ok());
+    if let Some(config) = config {
+        let recovery = is_from_newer_version(&config).then(|| ConfigRecovery {
+            bad_file: None,
+            is_from_backup: false,
+            reset_fields: Vec::new(),
+            is_reset_to_defaults: false,
+            write_error: None,
+            is_from_newer_version: true,
+        });
+        return ReadConfigResult {
+            config,
+            is_first_start: false,
+            recovery,
+        };
+    }
+
+    if !config_file.exists() && !persist::has_backups(&config_file) {
         let config = Config::default();
-        write_config(&config);
-        (config, true)
+        write_config(&config).expect("Cannot write config file");
+        return ReadConfigResult {
+            config,
+            is_first_start: true,
+            recovery: None,
+        };
     }
+
+    // If the broken file can't be copied, it is kept where it is instead, so
+    // the recovered config is not written over it.
+    let mut write_error = None;
+    let bad_file = config_file.exists().then(|| {
+        let bad_file = data_dir.join(BAD_CONFIG_FILENAME);
+        match std::fs::copy(&config_file, &bad_file) {
+            Ok(_) => bad_file,
+            Err(e) => {
+                eprintln!("Cannot keep broken config file: {e}");
+                write_error = Some(e.to_string());
+                config_file.clone()
+            }
+        }
+    });
+
+    let backup =
+        persist::read_json_with_backups_as(&config_file, |config| parse_config(config).ok());
+    let recovery = match backup {
+        Some(config) => (config, true, Vec::new(), false),
+        None => match persist::read_json_with_backups::<Value>(&config_file) {
+            Some(mut value) => {
+                migrate(&mut value);
+                let (config, reset_fields) =
+                    persist::recover_json_fields(&value, &Config::default());
+                (config, false, reset_fields, false)
+            }
+            None => (Config::default(), false, Vec::new(), true),
+        },
+    };
+    let (config, is_from_backup, reset_fields, is_reset_to_defaults) = recovery;
+    if write_error.is_none() {
+        if let Err(e) = write_config(&config) {
+            eprintln!("Cannot write config file: {e}");
+            write_error = Some(e.to_string());
+        }
+    }
+
+    let is_from_newer_version = is_from_newer_version(&config);
+    ReadConfigResult {
+        config,
+        is_first_start: false,
+        recovery: Some(ConfigRecovery {
+            bad_file,
+            is_from_backup,
+            reset_fields,
+            is_reset_to_defaults,
+            write_error,
+            is_from_newer_version,
+        }),
+    }
+}
+
+fn is_from_newer_version(config: &Config) -> bool {
+    config.schema_version > CURRENT_SCHEMA_VERSION
 }
 
-pub fn write_config(config: &Config) {
+/// Write the config to the config file, unless it was written by a newer
+/// version. Saving it would drop the fields this version doesn't know, so
+/// changes are only kept until the app quits then.
+pub fn write_config(config: &Config) -> io::Result<()> {
+    if is_from_newer_version(config) {
+        eprintln!(
+            "Not writing config file of schema version {}, newer than {CURRENT_SCHEMA_VERSION}",
+            config.schema_version
+        );
+        return Ok(());
+    }
     let data_dir = dirs::data_dir().unwrap().join(DATA_ROOT_DIR);
     let config_file = data_dir.join(CONFIG_FILENAME);
-    let file = std::fs::File::create(&config_file).expect("Cannot write config file");
-    let writer = std::io::BufWriter::new(file);
-    serde_json::to_writer(writer, config).expect("Error serializing config");
+    persist::write_json_atomically(&config_file, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rdev::Button;
+
+    fn parse(text: &str) -> Config {
+        parse_config(serde_json::from_str(text).unwrap()).unwrap()
+    }
+
+    fn assert_frozen_appearance_and_control(config: &Config) {
+        let appearance = &config.appearance;
+        assert_eq!(
+            appearance.position_reader,
+            LogicalPosition::new(120.0, 80.0)
+        );
+        assert_eq!(appearance.size_reader, LogicalSize::new(320.0, 480.0));
+        assert_eq!(appearance.text_size, 18);
+        assert_eq!(appearance.text_color, "#ffffffcc");
+
+        let basic = &config.control.basic;
+        assert_eq!(basic.mode, ControlBasicMode::VerySafe);
+        assert_eq!(basic.show_hide, KeyButton::Key(Key::ControlLeft));
+        assert_eq!(basic.next_page, KeyButton::Button(Button::Left));
+        assert_eq!(basic.prev_page, KeyButton::WheelDown);
+    }
+
+    #[test]
+    fn test_parse_config_v0() {
+        let config = parse(include_str!("texts/config_v0.json"));
+        assert_eq!(config.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_frozen_appearance_and_control(&config);
+        assert!(config.import.cleanup_rules.is_empty());
+        assert_eq!(config.import.chinese_conversion, ChineseConversion::None);
+    }
+
+    #[test]
+    fn test_parse_config_v1() {
+        let config = parse(include_str!("texts/config_v1.json"));
+        assert_eq!(config.schema_version, 1);
+        assert_frozen_appearance_and_control(&config);
+        assert!(config.import.normalization.full_width_to_half_width);
+        assert!(config.import.normalization.unify_ellipses);
+        assert!(!config.import.normalization.nfkc);
+        assert_eq!(
+            config.import.cleanup_rules,
+            [CleanupRule::DeleteLine {
+                pattern: String::from("^（本章完）$"),
+            }]
+        );
+        assert_eq!(
+            config.import.chinese_conversion,
+            ChineseConversion::TraditionalToSimplified
+        );
+    }
+
+    #[test]
+    fn test_missing_fields_take_defaults() {
+        let config = parse(
+            r#"{"schema_version":1,"appearance":{"text_size":20,"text_stroke":{"enabled":true}}}"#,
+        );
+        let default = Config::default();
+        assert_eq!(config.appearance.text_size, 20);
+        assert_eq!(config.appearance.text_color, default.appearance.text_color);
+        assert_eq!(
+            config.appearance.line_height,
+            default.appearance.line_height
+        );
+        assert_eq!(config.control.basic.mode, default.control.basic.mode);
+    }
+
+    #[test]
+    fn test_newer_schema_version_is_kept() {
+        let config = parse(r#"{"schema_version":99,"unknown_field":true}"#);
+        assert_eq!(config.schema_version, 99);
+        assert!(is_from_newer_version(&config));
+        assert!(!is_from_newer_version(&Config::default()));
+    }
+
+    /// Every value in `expected` is in `actual` under the same path. Fields
+    /// added after `expected` was written are ignored.
+    fn assert_json_contains(actual: &Value, expected: &Value) {
+        match (actual, expected) {
+            (Value::Object(actual), Value::Object(expected)) => {
+                for (key, expected) in expected {
+                    let actual = actual.get(key).unwrap_or_else(|| panic!("Missing {key}"));
+                    assert_json_contains(actual, expected);
+                }
+            }
+            _ => assert_eq!(actual, expected),
+        }
+    }
+
+    #[test]
+    fn test_serialized_config_round_trips() {
+        let config = parse(include_str!("texts/config_v1.json"));
+        let value = serde_json::to_value(&config).unwrap();
+        let expected: Value = serde_json::from_str(include_str!("texts/config_v1.json")).unwrap();
+        assert_json_contains(&value, &expected);
+    }
 }
@@ -0,0 +1,113 @@
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use std::fmt;
+
+/// Errors returned by commands. They are serialized as `{ kind, message }`,
+/// where `kind` is the variant name, so that the frontend can tell them apart.
+#[derive(Debug)]
+pub enum Error {
+    BookNotFound(String),
+    BookmarkNotFound(u64),
+    HighlightNotFound(u64),
+    EmptyHighlight,
+    HistoryEmpty,
+    CannotRemoveLastBook,
+    ProfileNotFound(String),
+    UnknownControlName(String),
+    InvalidCleanupRule(regex::Error),
+    InvalidSearchPattern(regex::Error),
+    Io(std::io::Error),
+    Tauri(tauri::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::BookNotFound(_) => "BookNotFound",
+            Error::BookmarkNotFound(_) => "BookmarkNotFound",
+            Error::HighlightNotFound(_) => "HighlightNotFound",
+            Error::EmptyHighlight => "EmptyHighlight",
+            Error::HistoryEmpty => "HistoryEmpty",
+            Error::CannotRemoveLastBook => "CannotRemoveLastBook",
+            Error::ProfileNotFound(_) => "ProfileNotFound",
+            Error::UnknownControlName(_) => "UnknownControlName",
+            Error::InvalidCleanupRule(_) => "InvalidCleanupRule",
+            Error::InvalidSearchPattern(_) => "InvalidSearchPattern",
+            Error::Io(_) => "Io",
+            Error::Tauri(_) => "Tauri",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::BookNotFound(id) => write!(f, "Book not found: {id}"),
+            Error::BookmarkNotFound(id) => write!(f, "Bookmark not found: {id}"),
+            Error::HighlightNotFound(id) => write!(f, "Highlight not found: {id}"),
+            Error::EmptyHighlight => write!(f, "Nothing to highlight"),
+            Error::HistoryEmpty => write!(f, "No earlier position to go back to"),
+            Error::CannotRemoveLastBook => write!(f, "Cannot remove the last book"),
+            Error::ProfileNotFound(name) => write!(f, "Profile not found: {name}"),
+            Error::UnknownControlName(name) => write!(f, "Unknown control name: {name}"),
+            Error::InvalidCleanupRule(e) => write!(f, "Invalid cleanup rule: {e}"),
+            Error::InvalidSearchPattern(e) => write!(f, "Invalid search pattern: {e}"),
+            Error::Io(e) => write!(f, "{e}"),
+            Error::Tauri(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidCleanupRule(e) => Some(e),
+            Error::InvalidSearchPattern(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::Tauri(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<tauri::Error> for Error {
+    fn from(e: tauri::Error) -> Self {
+        Error::Tauri(e)
+    }
+}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Error", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        let error = Error::BookNotFound(String::from("abc"));
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "kind": "BookNotFound", "message": "Book not found: abc" })
+        );
+
+        let error = Error::from(std::io::Error::other("disk full"));
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "kind": "Io", "message": "disk full" })
+        );
+    }
+}
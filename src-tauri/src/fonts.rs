@@ -0,0 +1,301 @@
+//! Enumerate the font families installed on the system, by reading the `name`
+//! table of every font file in the system and user font directories. Only the
+//! table directories and `name` tables are read, not the whole files, as font
+//! files can be tens of megabytes.
+
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+const FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "ttc", "otc"];
+
+/// Font directories are not expected to be deeper than this, and it keeps
+/// symlink loops from recursing forever.
+const MAX_DIR_DEPTH: usize = 8;
+
+const NAME_ID_FAMILY: u16 = 1;
+const NAME_ID_TYPOGRAPHIC_FAMILY: u16 = 16;
+
+const PLATFORM_MACINTOSH: u16 = 1;
+const PLATFORM_WINDOWS: u16 = 3;
+const LANGUAGE_MACINTOSH_ENGLISH: u16 = 0;
+const LANGUAGE_WINDOWS_ENGLISH_US: u16 = 0x0409;
+
+fn font_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    {
+        let windir = std::env::var_os("WINDIR").unwrap_or_else(|| "C:\\Windows".into());
+        dirs.push(PathBuf::from(windir).join("Fonts"));
+        if let Some(local) = dirs::data_local_dir() {
+            dirs.push(local.join("Microsoft").join("Windows").join("Fonts"));
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        dirs.push(PathBuf::from("/System/Library/Fonts"));
+        dirs.push(PathBuf::from("/Library/Fonts"));
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        dirs.push(PathBuf::from("/usr/share/fonts"));
+        dirs.push(PathBuf::from("/usr/local/share/fonts"));
+    }
+
+    if let Some(font) = dirs::font_dir() {
+        dirs.push(font);
+    }
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join(".fonts"));
+    }
+
+    dirs
+}
+
+/// Family names of the installed fonts, sorted and without duplicates. Files
+/// that can't be read or parsed are skipped.
+pub fn get_system_font_families() -> Vec<String> {
+    let mut font_files = Vec::new();
+    for dir in font_dirs() {
+        collect_font_files(&dir, 0, &mut font_files);
+    }
+
+    let mut families = BTreeSet::new();
+    for file in font_files {
+        if let Ok(file) = File::open(&file) {
+            families.extend(font_families(&mut BufReader::new(file)));
+        }
+    }
+    families.into_iter().collect()
+}
+
+fn collect_font_files(dir: &Path, depth: usize, font_files: &mut Vec<PathBuf>) {
+    if depth > MAX_DIR_DEPTH {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_font_files(&path, depth + 1, font_files);
+        } else if path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                FONT_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+            })
+        {
+            font_files.push(path);
+        }
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// `length` bytes at `offset` of the file, or `None` if it is shorter.
+fn read_at<R: Read + Seek>(reader: &mut R, offset: u64, length: usize) -> Option<Vec<u8>> {
+    reader.seek(SeekFrom::Start(offset)).ok()?;
+    // Not allocated up front, as a broken file may claim any length.
+    let mut bytes = Vec::new();
+    reader.take(length as u64).read_to_end(&mut bytes).ok()?;
+    (bytes.len() == length).then_some(bytes)
+}
+
+/// Family names of every font in a font file, which holds several fonts if it
+/// is a collection.
+pub fn font_families<R: Read + Seek>(reader: &mut R) -> Vec<String> {
+    let Some(header) = read_at(reader, 0, 12) else {
+        return Vec::new();
+    };
+    let font_offsets = if header.starts_with(b"ttcf") {
+        let font_count = read_u32(&header, 8).unwrap_or(0) as usize;
+        let Some(offsets) = read_at(reader, 12, font_count * 4) else {
+            return Vec::new();
+        };
+        offsets
+            .chunks_exact(4)
+            .filter_map(|offset| read_u32(offset, 0))
+            .map(u64::from)
+            .collect()
+    } else {
+        vec![0]
+    };
+
+    font_offsets
+        .into_iter()
+        .filter_map(|offset| font_family(reader, offset))
+        .collect()
+}
+
+/// The family name of the font whose table directory starts at `font_offset`.
+fn font_family<R: Read + Seek>(reader: &mut R, font_offset: u64) -> Option<String> {
+    let offset_table = read_at(reader, font_offset, 12)?;
+    let table_count = read_u16(&offset_table, 4)? as usize;
+    let table_records = read_at(reader, font_offset + 12, table_count * 16)?;
+    let (name_table_offset, name_table_length) =
+        table_records.chunks_exact(16).find_map(|record| {
+            (&record[..4] == b"name")
+                .then(|| Some((read_u32(record, 8)?, read_u32(record, 12)?)))?
+        })?;
+
+    let name_table = read_at(reader, name_table_offset as u64, name_table_length as usize)?;
+    family_name(&name_table)
+}
+
+/// The family name in a `name` table, preferably in English, as that is what
+/// every platform matches in CSS.
+fn family_name(data: &[u8]) -> Option<String> {
+    let record_count = read_u16(data, 2)? as usize;
+    let strings_offset = read_u16(data, 4)? as usize;
+
+    // Lower is better.
+    let mut best: Option<(u8, String)> = None;
+    for i in 0..record_count {
+        let record = 6 + i * 12;
+        let platform_id = read_u16(data, record)?;
+        let language_id = read_u16(data, record + 4)?;
+        let name_id = read_u16(data, record + 6)?;
+        let length = read_u16(data, record + 8)? as usize;
+        let offset = strings_offset + read_u16(data, record + 10)? as usize;
+
+        let rank = match (platform_id, language_id, name_id) {
+            (PLATFORM_WINDOWS, LANGUAGE_WINDOWS_ENGLISH_US, NAME_ID_TYPOGRAPHIC_FAMILY) => 0,
+            (PLATFORM_WINDOWS, LANGUAGE_WINDOWS_ENGLISH_US, NAME_ID_FAMILY) => 1,
+            (PLATFORM_MACINTOSH, LANGUAGE_MACINTOSH_ENGLISH, NAME_ID_TYPOGRAPHIC_FAMILY) => 2,
+            (PLATFORM_MACINTOSH, LANGUAGE_MACINTOSH_ENGLISH, NAME_ID_FAMILY) => 3,
+            (PLATFORM_WINDOWS, _, NAME_ID_TYPOGRAPHIC_FAMILY) => 4,
+            (PLATFORM_WINDOWS, _, NAME_ID_FAMILY) => 5,
+            _ => continue,
+        };
+        if best
+            .as_ref()
+            .is_some_and(|(best_rank, _)| *best_rank <= rank)
+        {
+            continue;
+        }
+
+        let Some(bytes) = data.get(offset..offset + length) else {
+            continue;
+        };
+        let name = if platform_id == PLATFORM_WINDOWS {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        } else {
+            // Mac Roman, which matches ASCII for the names that matter here.
+            bytes.iter().map(|&b| b as char).collect()
+        };
+        let name = name.trim().to_string();
+        if !name.is_empty() {
+            best = Some((rank, name));
+        }
+    }
+
+    best.map(|(_, name)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn families(data: &[u8]) -> Vec<String> {
+        font_families(&mut Cursor::new(data))
+    }
+
+    /// A font with only a `name` table, holding the given records of
+    /// `(platform_id, language_id, name_id, bytes)`.
+    fn font_with_names(records: &[(u16, u16, u16, Vec<u8>)]) -> Vec<u8> {
+        let mut name_table = Vec::new();
+        let strings_offset = 6 + records.len() * 12;
+        name_table.extend(0u16.to_be_bytes());
+        name_table.extend((records.len() as u16).to_be_bytes());
+        name_table.extend((strings_offset as u16).to_be_bytes());
+        let mut strings: Vec<u8> = Vec::new();
+        for (platform_id, language_id, name_id, bytes) in records {
+            name_table.extend(platform_id.to_be_bytes());
+            name_table.extend(0u16.to_be_bytes());
+            name_table.extend(language_id.to_be_bytes());
+            name_table.extend(name_id.to_be_bytes());
+            name_table.extend((bytes.len() as u16).to_be_bytes());
+            name_table.extend((strings.len() as u16).to_be_bytes());
+            strings.extend(bytes);
+        }
+        name_table.extend(strings);
+
+        let mut font = Vec::new();
+        font.extend(0x00010000u32.to_be_bytes());
+        font.extend(1u16.to_be_bytes());
+        font.extend([0; 6]);
+        font.extend(b"name");
+        font.extend(0u32.to_be_bytes());
+        font.extend(28u32.to_be_bytes());
+        font.extend((name_table.len() as u32).to_be_bytes());
+        font.extend(name_table);
+        font
+    }
+
+    fn utf16(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    #[test]
+    fn test_font_family_prefers_english() {
+        let font = font_with_names(&[
+            (PLATFORM_WINDOWS, 0x0804, NAME_ID_FAMILY, utf16("微软雅黑")),
+            (PLATFORM_MACINTOSH, 0, NAME_ID_FAMILY, b"Mac Name".to_vec()),
+            (
+                PLATFORM_WINDOWS,
+                0x0409,
+                NAME_ID_FAMILY,
+                utf16("Microsoft YaHei"),
+            ),
+        ]);
+        assert_eq!(families(&font), ["Microsoft YaHei"]);
+
+        let font = font_with_names(&[(PLATFORM_WINDOWS, 0x0804, NAME_ID_FAMILY, utf16("宋体"))]);
+        assert_eq!(families(&font), ["宋体"]);
+    }
+
+    #[test]
+    fn test_font_families_of_collection() {
+        let mut font =
+            font_with_names(&[(PLATFORM_WINDOWS, 0x0409, NAME_ID_FAMILY, utf16("Ming"))]);
+        // The `name` table moves with the font, after the collection header.
+        font[20..24].copy_from_slice(&(28u32 + 16).to_be_bytes());
+        let mut collection = Vec::new();
+        collection.extend(b"ttcf");
+        collection.extend(0x00010000u32.to_be_bytes());
+        collection.extend(1u32.to_be_bytes());
+        collection.extend(16u32.to_be_bytes());
+        collection.extend(font);
+        assert_eq!(families(&collection), ["Ming"]);
+    }
+
+    #[test]
+    fn test_font_family_of_invalid_data() {
+        assert!(families(b"").is_empty());
+        assert!(families(b"ttcf\0\x01\0\0\0\0\0\x09").is_empty());
+        let mut font = font_with_names(&[(PLATFORM_WINDOWS, 0x0409, NAME_ID_FAMILY, utf16("Cut"))]);
+        font.truncate(40);
+        assert!(families(&font).is_empty());
+    }
+}
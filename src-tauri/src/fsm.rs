@@ -1,7 +1,11 @@
 use std::time::SystemTime;
 
 use super::listener::{KeyButton, KeyButtonAction, UpDown};
-use crate::config::{Control, ControlBasicMode};
+use crate::{
+    command,
+    config::{Control, ControlBasicMode},
+    error::Error,
+};
 use tauri::{AppHandle, Emitter, Manager};
 
 const COMBO_DURATION: u128 = 250;
@@ -27,6 +31,12 @@ pub struct Fsm {
     edges_hide: Vec<KeyButtonAction>,
     edges_next: Vec<KeyButtonAction>,
     edges_prev: Vec<KeyButtonAction>,
+    /// Empty if not bound. Works whether the reader is shown or not.
+    edges_next_profile: Vec<KeyButtonAction>,
+    /// Empty if not bound. Only works while the reader is shown.
+    edges_add_bookmark: Vec<KeyButtonAction>,
+    /// Empty if not bound. Only works while the reader is shown.
+    edges_go_back: Vec<KeyButtonAction>,
 
     cur_state: FsmState,
     prev_stable_state: FsmState,
@@ -43,6 +53,9 @@ impl Fsm {
             edges_hide: Vec::new(),
             edges_next: Vec::new(),
             edges_prev: Vec::new(),
+            edges_next_profile: Vec::new(),
+            edges_add_bookmark: Vec::new(),
+            edges_go_back: Vec::new(),
             cur_state: FsmState::Hide,
             prev_stable_state: FsmState::Hide,
             prev_time: SystemTime::now(),
@@ -75,6 +88,9 @@ impl Fsm {
             let basic = &control.basic;
             self.set_next_page_with_basic_control(basic.next_page);
             self.set_prev_page_with_basic_control(basic.prev_page);
+            self.set_next_profile_with_basic_control(basic.next_profile);
+            self.set_add_bookmark_with_basic_control(basic.add_bookmark);
+            self.set_go_back_with_basic_control(basic.go_back);
             self.set_show_hide_with_basic_control(basic.mode, basic.show_hide);
         }
 
@@ -89,6 +105,24 @@ impl Fsm {
         self.edges_prev = vec![KeyButtonAction(key_button, UpDown::Down)];
     }
 
+    pub fn set_next_profile_with_basic_control(&mut self, key_button: Option<KeyButton>) {
+        self.edges_next_profile = key_button
+            .map(|key_button| vec![KeyButtonAction(key_button, UpDown::Down)])
+            .unwrap_or_default();
+    }
+
+    pub fn set_add_bookmark_with_basic_control(&mut self, key_button: Option<KeyButton>) {
+        self.edges_add_bookmark = key_button
+            .map(|key_button| vec![KeyButtonAction(key_button, UpDown::Down)])
+            .unwrap_or_default();
+    }
+
+    pub fn set_go_back_with_basic_control(&mut self, key_button: Option<KeyButton>) {
+        self.edges_go_back = key_button
+            .map(|key_button| vec![KeyButtonAction(key_button, UpDown::Down)])
+            .unwrap_or_default();
+    }
+
     pub fn set_show_hide_with_basic_control(
         &mut self,
         mode: ControlBasicMode,
@@ -116,15 +150,26 @@ impl Fsm {
         }
     }
 
-    pub fn reset_and_pause(&mut self) {
+    /// Moving or restyling the reader is not reading, so the reading session
+    /// ends here, as it does when the reader is hidden.
+    pub fn reset_and_pause(&mut self, app: &AppHandle) {
         self.go_to_new_stable_state(FsmState::Hide);
         self.is_paused = true;
+        if let Err(e) = command::record_reader_shown_aux(app, false) {
+            eprintln!("Cannot record reading session: {e}");
+        }
     }
 
+    /// The reader is left hidden, so the next session starts when it is shown
+    /// again.
     pub fn continue_from_pause(&mut self) {
         self.is_paused = false;
     }
 
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
     pub fn try_next_state(&mut self, key_button: KeyButton, up_down: UpDown, app: &AppHandle) {
         if self.is_paused {
             return;
@@ -147,6 +192,9 @@ impl Fsm {
         match self.cur_state {
             FsmState::Hide => {
                 if action != self.edges_show[0] {
+                    if self.edges_next_profile.first() == Some(&action) {
+                        self.switch_to_next_profile(app);
+                    }
                     return;
                 }
                 if self.edges_show.len() == 1 {
@@ -188,6 +236,12 @@ impl Fsm {
                     } else {
                         self.cur_state = FsmState::ToPrevPage(0);
                     }
+                } else if self.edges_next_profile.first() == Some(&action) {
+                    self.switch_to_next_profile(app);
+                } else if self.edges_add_bookmark.first() == Some(&action) {
+                    self.add_bookmark(app);
+                } else if self.edges_go_back.first() == Some(&action) {
+                    self.go_back(app);
                 }
             }
             FsmState::ToHide(step) => {
@@ -232,10 +286,51 @@ impl Fsm {
         self.prev_stable_state = state;
     }
 
+    fn switch_to_next_profile(&self, app: &AppHandle) {
+        // Applying a profile locks the config, which is elsewhere locked before
+        // the FSM, so it is done once the FSM is unlocked.
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = command::apply_next_profile_aux(&app) {
+                eprintln!("Cannot apply profile: {e}");
+            }
+        });
+    }
+
+    fn add_bookmark(&self, app: &AppHandle) {
+        // Reading the book to take a snippet shouldn't hold up the input.
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = command::add_bookmark_aux(&app) {
+                eprintln!("Cannot add bookmark: {e}");
+            }
+        });
+    }
+
+    fn go_back(&self, app: &AppHandle) {
+        // Going back changes the book, which locks the config.
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            match command::go_back_aux(&app) {
+                Ok(()) | Err(Error::HistoryEmpty) => {}
+                Err(e) => eprintln!("Cannot go back: {e}"),
+            }
+        });
+    }
+
     fn emit_event(&self, app: &AppHandle, event: &str) {
-        let window_reader = app
-            .get_webview_window("main")
-            .expect("Cannot get webview window");
-        window_reader.emit(event, ()).expect("Cannot emit event");
+        if event == EVENT_SHOW || event == EVENT_HIDE {
+            if let Err(e) = command::record_reader_shown_aux(app, event == EVENT_SHOW) {
+                eprintln!("Cannot record reading session: {e}");
+            }
+        }
+
+        let Some(window_reader) = app.get_webview_window("main") else {
+            eprintln!("Cannot get webview window to emit {event}");
+            return;
+        };
+        if let Err(e) = window_reader.emit(event, ()) {
+            eprintln!("Cannot emit {event}: {e}");
+        }
     }
 }
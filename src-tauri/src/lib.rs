@@ -1,19 +1,37 @@
-use std::{collections::HashMap, sync::Mutex};
-use tauri::{
-    menu::{Menu, MenuItem},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent,
-};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
 use tauri_plugin_fs::FsExt;
 
+mod annotations;
+mod chapters;
+mod cleanup;
 mod command;
 mod config;
+mod error;
+mod fonts;
 mod fsm;
 mod library;
 mod listener;
+mod logstream;
+mod normalize;
+mod persist;
+mod placement;
+mod profile;
+mod search_index;
+mod stats;
+mod tray;
+mod zhconv;
 
 pub const DATA_ROOT_DIR: &str = "sneaky-reader";
 
+/// How often to check whether the monitors changed, to apply the profile of
+/// the new layout.
+const MONITOR_LAYOUT_POLL_SECONDS: u64 = 5;
+
+/// How often the reading goals in the tray tooltip are refreshed, besides
+/// after every reading session.
+const GOALS_TOOLTIP_REFRESH_SECONDS: u64 = 60;
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     #[allow(unused_mut)]
@@ -30,9 +48,17 @@ pub fn run() {
         }))
         .plugin(tauri_plugin_fs::init())
         .setup(|app| {
-            // First read the config and books so that app panics at the very beginning
-            let (config, is_first_start) = config::read_config();
-            let books = library::get_books_from_disk();
+            // First read the config and books. A broken config or library metadata
+            // is recovered instead, and reported in the settings window.
+            let config::ReadConfigResult {
+                config,
+                is_first_start,
+                recovery,
+            } = config::read_config();
+            let library::ReadBooksResult {
+                books,
+                recovery: library_recovery,
+            } = library::get_books_from_disk();
 
             app.fs_scope()
                 .allow_directory("/", true)
@@ -76,11 +102,20 @@ pub fn run() {
                 .expect("Cannot ignore cursor events");
 
             let window_reader_clone = window_reader.clone();
+            let app_handle = app.handle().clone();
             window_reader.on_window_event(move |event| {
                 if let WindowEvent::Resized(_) = event {
                     window_reader_clone
                         .emit("refresh-content", ())
                         .expect("Cannot emit refresh-content");
+
+                    // Whatever resized the reader may still hold the config.
+                    let app = app_handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = command::anchor_reader_aux(&app) {
+                            eprintln!("Cannot anchor reader: {e}");
+                        }
+                    });
                 }
             });
 
@@ -91,19 +126,22 @@ pub fn run() {
                 window_reader.open_devtools();
             }
 
-            let mut title_to_index = HashMap::new();
-            for (i, book) in books.iter().enumerate() {
-                title_to_index.insert(book.title.clone(), i);
+            app.manage(Mutex::new(library::BooksAux::new(books)));
+            app.manage(Mutex::new(stats::Recorder::new(stats::compact_sessions(
+                stats::read_sessions_from_disk(),
+                &chrono::Local,
+                stats::now(),
+            ))));
+            app.manage(Mutex::new(search_index::read_search_index_from_disk()));
+
+            {
+                let app = app.handle().clone();
+                tauri::async_runtime::spawn_blocking(move || {
+                    if let Err(e) = command::sync_search_index_aux(&app) {
+                        eprintln!("Cannot update search index: {e}");
+                    }
+                });
             }
-            let old_progress = books
-                .first()
-                .map(|book| book.progress)
-                .unwrap_or(usize::MAX);
-            app.manage(Mutex::new(library::BooksAux {
-                books,
-                title_to_index,
-                old_progress,
-            }));
 
             {
                 let app = app.handle().clone();
@@ -115,14 +153,29 @@ pub fn run() {
                         let books_aux = app.state::<Mutex<library::BooksAux>>();
                         let mut books_aux = books_aux.lock().unwrap();
                         if books_aux.books[0].progress != books_aux.old_progress {
-                            library::write_books_to_disk(&books_aux.books);
-                            books_aux.old_progress = books_aux.books[0].progress;
+                            match library::write_books_to_disk(&books_aux.books) {
+                                Ok(()) => books_aux.old_progress = books_aux.books[0].progress,
+                                Err(e) => eprintln!("Cannot write metadata file: {e}"),
+                            }
+                        }
+                        drop(books_aux);
+
+                        let recorder = app.state::<Mutex<stats::Recorder>>();
+                        let mut recorder = recorder.lock().unwrap();
+                        if let Some(sessions) = recorder.unsaved_sessions(stats::now()) {
+                            if let Err(e) = stats::write_sessions_to_disk(&sessions) {
+                                eprintln!("Cannot write reading sessions: {e}");
+                            }
                         }
                     }
                 });
             }
 
-            if is_first_start {
+            let is_recovered = recovery.is_some() || library_recovery.is_some();
+            app.manage(Mutex::new(recovery));
+            app.manage(Mutex::new(library_recovery));
+
+            if is_first_start || is_recovered {
                 open_or_create_settings_window(app.handle());
             }
 
@@ -131,53 +184,66 @@ pub fn run() {
                 allow_wheel: false,
             }));
 
-            // Create the tray icon
-            let menu_item_settings =
-                MenuItem::with_id(app, "settings", "Open settings", true, None::<&str>).unwrap();
-            let menu_item_quit =
-                MenuItem::with_id(app, "quit", "Quit", true, None::<&str>).unwrap();
-            let menu = Menu::with_items(app, &[&menu_item_settings, &menu_item_quit])
-                .expect("Cannot create menu");
-
-            let mut tray_builder = TrayIconBuilder::new()
-                .menu(&menu)
-                .icon(tauri::include_image!("icons/32x32.png"))
-                .on_menu_event(move |app, event| match event.id.as_ref() {
-                    "settings" => {
-                        open_or_create_settings_window(app);
-                    }
-                    "quit" => {
-                        command::persist_appearance_aux(app);
+            // Shown in English until the reader tells the language of the
+            // system
+            app.manage(Mutex::new(tray::Language::default()));
 
-                        let books_aux = app.state::<Mutex<library::BooksAux>>();
-                        let books_aux = books_aux.lock().unwrap();
-                        library::write_books_to_disk(&books_aux.books);
+            // Create the tray icon
+            {
+                let config = app.state::<Mutex<config::Config>>();
+                let config = config.lock().unwrap();
+                tray::create_tray(app.handle(), &config).expect("Cannot create tray icon");
+            }
 
-                        app.exit(0);
-                    }
-                    _ => {}
-                })
-                .on_tray_icon_event(move |tray, event| {
-                    if let TrayIconEvent::Click {
-                        id: _,
-                        position: _,
-                        rect: _,
-                        button,
-                        button_state,
-                    } = event
-                    {
-                        if button == MouseButton::Left && button_state == MouseButtonState::Up {
-                            open_or_create_settings_window(tray.app_handle());
+            // Keep the goals in the tray tooltip current, also when a new day
+            // or week starts
+            {
+                let app = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                        GOALS_TOOLTIP_REFRESH_SECONDS,
+                    ));
+                    loop {
+                        interval.tick().await;
+                        if let Err(e) = command::refresh_goals_tooltip_aux(&app) {
+                            eprintln!("Cannot refresh tray tooltip: {e}");
                         }
                     }
                 });
+            }
 
-            #[cfg(not(target_os = "linux"))]
+            // Apply the profile or window geometry of the monitor layout, and keep
+            // the windows on screen, at start and whenever the layout changes
             {
-                tray_builder = tray_builder.show_menu_on_left_click(false)
+                let app = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                        MONITOR_LAYOUT_POLL_SECONDS,
+                    ));
+                    let mut last_monitor_layout = None;
+                    loop {
+                        interval.tick().await;
+
+                        let monitor_layout = match profile::monitor_layout(&app) {
+                            Ok(monitor_layout) => monitor_layout,
+                            Err(e) => {
+                                eprintln!("Cannot get monitor layout: {e}");
+                                continue;
+                            }
+                        };
+                        if last_monitor_layout.as_ref() == Some(&monitor_layout) {
+                            continue;
+                        }
+                        if let Err(e) =
+                            command::on_monitor_layout_changed_aux(&app, &monitor_layout)
+                        {
+                            eprintln!("Cannot place windows for the monitor layout: {e}");
+                        }
+                        last_monitor_layout = Some(monitor_layout);
+                    }
+                });
             }
 
-            let _tray = tray_builder.build(app).unwrap();
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -186,18 +252,64 @@ pub fn run() {
             command::persist_appearance,
             command::persist_basic_control_mode,
             command::persist_basic_control_key_button,
+            command::unbind_basic_control_key_button,
             command::get_config,
+            command::get_config_recovery,
+            command::dismiss_config_recovery,
+            command::get_library_recovery,
+            command::dismiss_library_recovery,
             command::get_books,
             command::change_book,
+            command::get_bookmarks,
+            command::add_bookmark,
+            command::update_bookmark_note,
+            command::remove_bookmark,
+            command::jump_to_bookmark,
+            command::get_highlights,
+            command::add_highlight,
+            command::update_highlight_note,
+            command::remove_highlight,
+            command::export_annotations,
+            command::search_book,
+            command::jump_to_offset,
+            command::go_back,
+            command::search_library,
             command::get_first_reader_book_info,
             command::update_progress,
+            command::get_reading_stats,
+            command::get_goals_summary,
+            command::update_reading_goals,
             command::new_books,
+            command::update_cleanup_rules,
+            command::preview_cleanup_rules,
+            command::update_import_normalization,
+            command::update_import_chinese_conversion,
+            command::update_book_chinese_conversion,
+            command::update_book_settings,
             command::rename_book,
             command::remove_book,
             command::update_text_size,
             command::update_text_color,
+            command::update_font_family,
+            command::update_line_height,
+            command::update_letter_spacing,
+            command::update_paragraph_spacing,
+            command::update_first_line_indent,
+            command::update_text_stroke,
+            command::update_text_shadow,
+            command::update_background_plate,
+            command::update_camouflage,
+            command::update_log_stream,
+            command::update_reader_anchor,
+            command::save_profile,
+            command::apply_profile,
+            command::remove_profile,
+            command::update_profile_monitor_layout,
+            command::get_monitor_layout,
+            command::get_system_font_families,
             command::update_frontend_listen_state,
             command::get_is_trial_version,
+            command::set_tray_language,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -239,8 +351,12 @@ fn open_or_create_settings_window(app: &AppHandle) {
             let app = app.clone();
             window_settings.on_window_event(move |event| {
                 if let WindowEvent::CloseRequested { .. } = event {
-                    command::end_changing_styles_aux(&app);
-                    command::persist_appearance_aux(&app);
+                    if let Err(e) = command::end_changing_styles_aux(&app) {
+                        eprintln!("Cannot end changing styles: {e}");
+                    }
+                    if let Err(e) = command::persist_appearance_aux(&app) {
+                        eprintln!("Cannot persist appearance: {e}");
+                    }
                 }
             });
 
@@ -1,105 +1,1013 @@
 use std::{
-    collections::{HashMap, HashSet},
-    io::Write,
+    collections::{HashMap, HashSet, VecDeque},
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
     time::{SystemTime, UNIX_EPOCH},
 };
 
-use crate::DATA_ROOT_DIR;
+use crate::{
+    chapters,
+    cleanup::{self, CleanupPreviewLine, CompiledRules},
+    config,
+    logstream::{LogStream, LogStreamOptions},
+    normalize::{self, NormalizeOptions},
+    persist, stats,
+    zhconv::{self, ChineseConversion},
+    DATA_ROOT_DIR,
+};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 pub const LIBRARY_DIR_NAME: &str = "library";
 pub const LIBRARY_METADATA_FILENAME: &str = "_metadata.json";
+pub const BAD_LIBRARY_METADATA_FILENAME: &str = "_metadata.json.bad";
+
+/// Number of bytes of the SHA-256 digest kept in a book ID.
+const BOOK_ID_BYTE_COUNT: usize = 16;
+
+/// Longest title kept when importing or renaming a book, in characters.
+pub const MAX_TITLE_LENGTH: usize = 200;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Book {
+    /// Derived from the content when the book is imported, and never changed
+    /// afterwards. The content is stored in `{id}.txt`. Empty for books from
+    /// older libraries before they are migrated.
+    #[serde(default)]
+    pub id: String,
     pub title: String,
     pub summary: String,
     pub total_character_count: usize,
     pub progress: usize,
     pub last_read_time: u64,
+    /// Applied when the content is served to the reader. The stored text is
+    /// kept as is.
+    #[serde(default)]
+    pub chinese_conversion: ChineseConversion,
+    #[serde(default)]
+    pub settings: BookSettings,
+    /// Where each chapter starts, in the same units as `progress`. `None` for
+    /// books from older libraries until they are looked through.
+    #[serde(default)]
+    pub chapter_starts: Option<Vec<usize>>,
+    /// Sorted by offset.
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    /// Sorted by start.
+    #[serde(default)]
+    pub highlights: Vec<Highlight>,
+    /// Where the book was read before its progress moved far away, oldest
+    /// first.
+    #[serde(default)]
+    pub history: VecDeque<HistoryEntry>,
 }
 
 impl Book {
     pub const SUMMARY_LENGTH: usize = 200;
+
+    pub fn add_bookmark(
+        &mut self,
+        offset: usize,
+        note: Option<String>,
+        snippet: String,
+        created_time: u64,
+    ) -> &Bookmark {
+        let id = self.bookmarks.iter().map(|bookmark| bookmark.id + 1).max();
+        let bookmark = Bookmark {
+            id: id.unwrap_or(0),
+            offset,
+            created_time,
+            note: sanitize_note(note),
+            snippet,
+        };
+        let i = self
+            .bookmarks
+            .partition_point(|bookmark| bookmark.offset <= offset);
+        self.bookmarks.insert(i, bookmark);
+        &self.bookmarks[i]
+    }
+
+    pub fn bookmark_mut(&mut self, id: u64) -> Option<&mut Bookmark> {
+        self.bookmarks.iter_mut().find(|bookmark| bookmark.id == id)
+    }
+
+    /// Returns whether the bookmark was there.
+    pub fn remove_bookmark(&mut self, id: u64) -> bool {
+        let count = self.bookmarks.len();
+        self.bookmarks.retain(|bookmark| bookmark.id != id);
+        self.bookmarks.len() != count
+    }
+
+    /// Highlight `text`, which is what is from `start` to `end` in the content.
+    pub fn add_highlight(
+        &mut self,
+        start: usize,
+        end: usize,
+        text: String,
+        note: Option<String>,
+        created_time: u64,
+    ) -> &Highlight {
+        let id = self
+            .highlights
+            .iter()
+            .map(|highlight| highlight.id + 1)
+            .max();
+        let highlight = Highlight {
+            id: id.unwrap_or(0),
+            start,
+            end,
+            created_time,
+            note: sanitize_note(note),
+            text,
+        };
+        let i = self
+            .highlights
+            .partition_point(|highlight| highlight.start <= start);
+        self.highlights.insert(i, highlight);
+        &self.highlights[i]
+    }
+
+    pub fn highlight_mut(&mut self, id: u64) -> Option<&mut Highlight> {
+        self.highlights
+            .iter_mut()
+            .find(|highlight| highlight.id == id)
+    }
+
+    /// Returns whether the highlight was there.
+    pub fn remove_highlight(&mut self, id: u64) -> bool {
+        let count = self.highlights.len();
+        self.highlights.retain(|highlight| highlight.id != id);
+        self.highlights.len() != count
+    }
+
+    /// Remember a position, dropping the oldest one if there are already
+    /// `HISTORY_LENGTH`.
+    pub fn push_history(&mut self, progress: usize, time: u64) {
+        if self
+            .history
+            .back()
+            .is_some_and(|entry| entry.progress == progress)
+        {
+            return;
+        }
+        if self.history.len() == HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+        self.history.push_back(HistoryEntry { progress, time });
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Bookmark {
+    /// Unique within the book.
+    pub id: u64,
+    /// In the same units as `Book::progress`.
+    pub offset: usize,
+    pub created_time: u64,
+    pub note: Option<String>,
+    /// The text at the offset, to tell bookmarks apart.
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Highlight {
+    /// Unique within the book.
+    pub id: u64,
+    /// In the same units as `Book::progress`, `end` excluded.
+    pub start: usize,
+    pub end: usize,
+    pub created_time: u64,
+    pub note: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// In the same units as `Book::progress`.
+    pub progress: usize,
+    /// When the book was moved away from it.
+    pub time: u64,
+}
+
+/// Most positions kept in the history of a book.
+pub const HISTORY_LENGTH: usize = 20;
+
+/// How long the reader has to stay at a position for it to count as where
+/// the book was being read, in seconds. Pages turned by a held key don't.
+pub const SETTLE_SECONDS: u64 = 3;
+
+/// How far the progress has to move from where the book was being read for
+/// that position to be remembered. Turning a few pages back and forth
+/// doesn't.
+pub const HISTORY_MIN_DISTANCE: usize = 2000;
+
+/// Longest snippet kept with a bookmark, in characters.
+pub const SNIPPET_LENGTH: usize = 60;
+
+/// Empty notes are no notes.
+pub fn sanitize_note(note: Option<String>) -> Option<String> {
+    note.map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty())
+}
+
+/// The index of the byte at `offset` UTF-16 code units into `text`, or the
+/// length of `text` if it is shorter.
+pub fn byte_index_of_utf16_offset(text: &str, offset: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in text.char_indices() {
+        if units >= offset {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+/// The text from `start` to `end`, both clamped to the content.
+pub fn text_between(content: &str, start: usize, end: usize) -> &str {
+    let start = byte_index_of_utf16_offset(content, start);
+    let end = byte_index_of_utf16_offset(content, end).max(start);
+    &content[start..end]
+}
+
+/// The text from `offset` to the end of its line, up to `SNIPPET_LENGTH`
+/// characters.
+pub fn snippet_at(content: &str, offset: usize) -> String {
+    let rest = &content[byte_index_of_utf16_offset(content, offset)..];
+    let line = rest.trim_start().lines().next().unwrap_or_default();
+    line.chars().take(SNIPPET_LENGTH).collect()
+}
+
+/// Most matches returned by one search, so that searching for a single common
+/// character doesn't flood the settings window.
+pub const MAX_SEARCH_MATCHES: usize = 500;
+
+/// Characters of context kept on each side of a match.
+pub const SEARCH_CONTEXT_LENGTH: usize = 20;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct SearchOptions {
+    pub ignore_case: bool,
+    /// Fullwidth letters, digits and punctuation match their halfwidth forms.
+    pub ignore_width: bool,
+    /// The query is a regular expression. Its own fullwidth characters are
+    /// not folded, even if `ignore_width` is set.
+    pub regex: bool,
+}
+
+/// Where a query was found, in the same units as `progress`, with the text
+/// around it on the same line.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub start: usize,
+    pub end: usize,
+    pub before: String,
+    pub text: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Serialize, Default, PartialEq, Eq)]
+pub struct SearchResult {
+    pub matches: Vec<SearchMatch>,
+    /// Whether there were more than `MAX_SEARCH_MATCHES` matches.
+    pub is_truncated: bool,
+}
+
+/// The halfwidth form of a fullwidth ASCII character or the ideographic space.
+/// Both forms are a single UTF-16 code unit, so folding keeps offsets.
+fn fold_width(c: char) -> char {
+    match c {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFF01 + 0x21).unwrap(),
+        '\u{3000}' => ' ',
+        _ => c,
+    }
+}
+
+/// Find `query` in the content of a book. Empty matches are skipped.
+pub fn search_book(
+    content: &str,
+    query: &str,
+    options: SearchOptions,
+) -> Result<SearchResult, regex::Error> {
+    if query.is_empty() {
+        return Ok(SearchResult::default());
+    }
+    let fold = |text: &str| -> String { text.chars().map(fold_width).collect() };
+
+    let pattern = if options.regex {
+        query.to_string()
+    } else if options.ignore_width {
+        regex::escape(&fold(query))
+    } else {
+        regex::escape(query)
+    };
+    let regex = regex::RegexBuilder::new(&pattern)
+        .case_insensitive(options.ignore_case)
+        .multi_line(true)
+        .build()?;
+
+    let haystack = if options.ignore_width {
+        fold(content)
+    } else {
+        content.to_string()
+    };
+
+    let mut result = SearchResult::default();
+    // Walk both texts along, as folded characters may differ in byte length.
+    let mut chars = content.char_indices().peekable();
+    let mut folded_chars = haystack.char_indices().peekable();
+    let mut units = 0;
+    let mut to_offset = |byte_index: usize| {
+        while let Some(&(i, c)) = folded_chars.peek() {
+            if i >= byte_index {
+                break;
+            }
+            folded_chars.next();
+            chars.next();
+            units += c.len_utf16();
+        }
+        (units, chars.peek().map_or(content.len(), |&(i, _)| i))
+    };
+    for found in regex.find_iter(&haystack) {
+        if found.is_empty() {
+            continue;
+        }
+        if result.matches.len() == MAX_SEARCH_MATCHES {
+            result.is_truncated = true;
+            break;
+        }
+        let (start, start_byte) = to_offset(found.start());
+        let (end, end_byte) = to_offset(found.end());
+
+        let line_start = content[..start_byte].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = content[end_byte..]
+            .find('\n')
+            .map_or(content.len(), |i| end_byte + i);
+        let before = &content[line_start..start_byte];
+        let before_length = before.chars().count();
+        result.matches.push(SearchMatch {
+            start,
+            end,
+            before: before
+                .chars()
+                .skip(before_length.saturating_sub(SEARCH_CONTEXT_LENGTH))
+                .collect(),
+            text: content[start_byte..end_byte].to_string(),
+            after: content[end_byte..line_end]
+                .chars()
+                .take(SEARCH_CONTEXT_LENGTH)
+                .collect(),
+        });
+    }
+    Ok(result)
+}
+
+/// A book as listed in the library, with how long it may take to finish.
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryBook {
+    #[serde(flatten)]
+    pub book: Book,
+    /// `None` until the reading speed is known.
+    pub time_left: Option<stats::TimeLeft>,
+}
+
+/// Reading settings of a single book. Each one that is `None` falls back to
+/// the global one in `config::Appearance`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(default)]
+pub struct BookSettings {
+    pub text_size: Option<usize>,
+    pub text_color: Option<String>,
+    /// A multiple of the text size.
+    pub line_height: Option<f64>,
+    /// A CSS `font-family` value.
+    pub font_family: Option<String>,
+}
+
+impl BookSettings {
+    /// Drop the values the reader can't use, so that they fall back to the
+    /// global settings instead.
+    pub fn sanitize(mut self) -> Self {
+        self.text_size = self.text_size.filter(|&size| size > 0);
+        self.line_height = self
+            .line_height
+            .filter(|height| height.is_finite() && *height > 0.0);
+        self.text_color = self.text_color.filter(|color| !color.trim().is_empty());
+        self.font_family = self.font_family.filter(|font| !font.trim().is_empty());
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct BooksAux {
     pub books: Vec<Book>,
-    pub title_to_index: HashMap<String, usize>,
+    pub id_to_index: HashMap<String, usize>,
     pub old_progress: usize,
+    /// What the content of the first book was turned into for the reader, if
+    /// it is shown as log lines. Progress reported by the reader is in its
+    /// offsets.
+    pub log_stream: Option<LogStream>,
+    /// Where the first book was last read for at least `SETTLE_SECONDS`.
+    pub settled_progress: usize,
+    /// When the progress of the first book last changed. 0 if it hasn't since
+    /// the book was opened, so that it is settled.
+    pub progress_time: u64,
+}
+
+impl BooksAux {
+    pub fn new(books: Vec<Book>) -> Self {
+        let old_progress = books
+            .first()
+            .map(|book| book.progress)
+            .unwrap_or(usize::MAX);
+        let mut books_aux = Self {
+            books,
+            id_to_index: HashMap::new(),
+            old_progress,
+            log_stream: None,
+            settled_progress: old_progress,
+            progress_time: 0,
+        };
+        books_aux.reindex();
+        books_aux
+    }
+
+    /// Rebuild `id_to_index` after books are added, removed or reordered.
+    pub fn reindex(&mut self) {
+        self.id_to_index = self
+            .books
+            .iter()
+            .enumerate()
+            .map(|(i, book)| (book.id.clone(), i))
+            .collect();
+    }
+
+    /// The first book, as it is sent to the reader.
+    pub fn first_reader_book_info(
+        &mut self,
+        log_stream_options: &LogStreamOptions,
+    ) -> io::Result<Option<ReaderBookInfo>> {
+        self.log_stream = None;
+        let Some(book) = self.books.first() else {
+            return Ok(None);
+        };
+        let mut reader_book_info = ReaderBookInfo::new(book)?;
+        if log_stream_options.enabled {
+            let log_stream = LogStream::new_now(
+                &reader_book_info.content,
+                log_stream_options,
+                reader_book_info.progress,
+            );
+            reader_book_info.progress = log_stream.to_log_offset(reader_book_info.progress);
+            reader_book_info.content = log_stream.content.clone();
+            reader_book_info.is_log_stream = true;
+            self.log_stream = Some(log_stream);
+        }
+        Ok(Some(reader_book_info))
+    }
+
+    /// Make a book the first one, which is the one being read.
+    pub fn move_to_front(&mut self, index: usize) {
+        self.books[0..=index].rotate_right(1);
+        self.reindex();
+        self.set_first_progress(self.books[0].progress);
+    }
+
+    /// Take a book out of the library. If it was the first one, the next
+    /// book is read from where it was left.
+    pub fn remove(&mut self, index: usize) -> Book {
+        let book = self.books.remove(index);
+        self.reindex();
+        if index == 0 {
+            self.log_stream = None;
+            if let Some(progress) = self.books.first().map(|book| book.progress) {
+                self.set_first_progress(progress);
+            }
+        }
+        book
+    }
+
+    /// Store the progress reported by the reader for a book. Once the first
+    /// book moves far from where it was being read, that position is
+    /// remembered in its history.
+    pub fn update_progress(&mut self, index: usize, progress: usize, now: u64) {
+        let progress = self.to_original_offset(index, progress);
+        let book = &mut self.books[index];
+        if index == 0 {
+            if now.saturating_sub(self.progress_time) >= SETTLE_SECONDS {
+                self.settled_progress = book.progress;
+            }
+            if progress != book.progress {
+                self.progress_time = now;
+            }
+            if progress.abs_diff(self.settled_progress) >= HISTORY_MIN_DISTANCE {
+                book.push_history(self.settled_progress, now);
+            }
+        }
+        book.progress = progress;
+    }
+
+    /// Read a book from `offset`, remembering where it was.
+    pub fn jump(&mut self, index: usize, offset: usize, now: u64) {
+        let book = &mut self.books[index];
+        if book.progress != offset {
+            book.push_history(book.progress, now);
+        }
+        self.move_to_front(index);
+        self.set_first_progress(offset);
+    }
+
+    /// Read a book from the last position in its history that isn't where it
+    /// is, which is taken out of the history. The position left is not
+    /// remembered, as it is usually where the book was moved to by accident.
+    /// Returns whether there was one.
+    pub fn go_back(&mut self, index: usize) -> bool {
+        let book = &mut self.books[index];
+        let progress = book.progress;
+        while let Some(entry) = book.history.pop_back() {
+            if entry.progress != progress {
+                self.move_to_front(index);
+                self.set_first_progress(entry.progress);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn set_first_progress(&mut self, progress: usize) {
+        self.books[0].progress = progress;
+        self.old_progress = progress;
+        self.settled_progress = progress;
+        self.progress_time = 0;
+    }
+
+    /// An offset into a book as the reader has it, into the book as it is
+    /// stored.
+    pub fn to_original_offset(&self, index: usize, offset: usize) -> usize {
+        match &self.log_stream {
+            Some(log_stream) if index == 0 => log_stream.to_original_offset(offset),
+            _ => offset,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReaderBookInfo {
+    pub id: String,
     pub title: String,
     pub content: String,
     pub progress: usize,
+    pub settings: BookSettings,
+    /// Whether the content was turned into fake log lines.
+    pub is_log_stream: bool,
 }
 
 impl ReaderBookInfo {
-    pub fn new(book: &Book) -> Self {
-        Self {
+    pub fn new(book: &Book) -> io::Result<Self> {
+        Ok(Self {
+            id: book.id.clone(),
             title: book.title.clone(),
-            content: get_book_content_from_disk(&book.title),
+            content: get_reader_content_from_disk(book)?,
             progress: book.progress,
-        }
+            settings: book.settings.clone(),
+            is_log_stream: false,
+        })
     }
 }
 
-/// Get all books in the library directory. If no library directory, will first
-/// create one and add a default book.
-pub fn get_books_from_disk() -> Vec<Book> {
-    let library_dir = dirs::data_dir()
+/// A book to be imported, as sent by the settings window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewBookInfo {
+    pub title: String,
+    pub content: String,
+}
+
+fn library_dir() -> PathBuf {
+    dirs::data_dir()
         .unwrap()
         .join(DATA_ROOT_DIR)
-        .join(LIBRARY_DIR_NAME);
+        .join(LIBRARY_DIR_NAME)
+}
+
+/// The only place where a book ID becomes a path. IDs are checked so that
+/// whatever the frontend sends, or the metadata file says, the path can't
+/// leave the library directory.
+fn book_content_path(library_dir: &Path, id: &str) -> io::Result<PathBuf> {
+    if !is_valid_book_id(id) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid book ID: {id:?}"),
+        ));
+    }
+    Ok(library_dir.join(format!("{id}.txt")))
+}
+
+pub fn is_valid_book_id(id: &str) -> bool {
+    id.len() == BOOK_ID_BYTE_COUNT * 2
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// Where older versions stored the content of a book, i.e. `{title}.txt`.
+/// Returns `None` if such a file can't be directly inside the library
+/// directory.
+fn legacy_book_content_path(library_dir: &Path, title: &str) -> Option<PathBuf> {
+    if title.contains(['/', '\\']) {
+        return None;
+    }
+    let file_name = format!("{title}.txt");
+    let mut components = Path::new(&file_name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Some(library_dir.join(file_name)),
+        _ => None,
+    }
+}
+
+/// Clean up a title given by the user, so that it shows well on a single line.
+/// Titles are only for display, so any character is fine except control
+/// characters.
+pub fn sanitize_title(title: &str) -> String {
+    let title: String = title
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    let title: String = title.trim().chars().take(MAX_TITLE_LENGTH).collect();
+    if title.is_empty() {
+        String::from("Untitled")
+    } else {
+        title
+    }
+}
+
+pub fn book_id_from_content(content: &str) -> String {
+    let digest = Sha256::digest(content.as_bytes());
+    digest[..BOOK_ID_BYTE_COUNT]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// How the library was recovered at startup, if its metadata file couldn't be
+/// parsed.
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryRecovery {
+    /// Where the broken metadata file is kept.
+    pub bad_file: PathBuf,
+    /// How many books were found again from their content files. Their titles
+    /// are taken from their first lines, and they are read from the start.
+    pub recovered_count: usize,
+    /// Why the recovered metadata could not be saved, if it couldn't.
+    pub write_error: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ReadBooksResult {
+    pub books: Vec<Book>,
+    pub recovery: Option<LibraryRecovery>,
+}
+
+/// Get all books in the library directory. If no library directory, will first
+/// create one and add a default book.
+///
+/// If the metadata file and its backups can't be parsed, the file is kept as
+/// `_metadata.json.bad`, and the books are found again from their content
+/// files, like `read_config` does for the config.
+pub fn get_books_from_disk() -> ReadBooksResult {
+    let library_dir = library_dir();
     if !library_dir.exists() {
         std::fs::create_dir_all(&library_dir).unwrap();
     }
 
     let metadata = library_dir.join(LIBRARY_METADATA_FILENAME);
-    if metadata.exists() {
-        let metadata = std::fs::File::open(metadata).expect("Cannot read metadata file");
-        serde_json::from_reader(metadata).expect("Cannot deserialize from metadata file")
-    } else {
-        let books = vec![
-            write_book_with_title_content(
-                String::from("Lorem Ipsum"),
-                include_str!("texts/sample_lorem_ipsum.txt"),
-            )
-            .unwrap(),
-            write_book_with_title_content(
-                String::from("中国劳动法"),
-                include_str!("texts/sample_chinese.txt"),
-            )
-            .unwrap(),
-        ];
+    if let Some(mut books) = persist::read_json_with_backups::<Vec<Book>>(&metadata) {
+        if drop_books_with_invalid_ids(&mut books) {
+            if let Err(e) = write_books_to_disk(&books) {
+                eprintln!("Cannot write metadata file: {e}");
+            }
+        }
+        if books.iter().any(|book| book.id.is_empty()) {
+            match migrate_books_to_ids(&library_dir, books.clone()) {
+                Ok(migrated_books) => books = migrated_books,
+                Err(e) => eprintln!("Cannot migrate library to book IDs: {e}"),
+            }
+            drop_unmigrated_books(&mut books);
+            if let Err(e) = write_books_to_disk(&books) {
+                eprintln!("Cannot write metadata file: {e}");
+            }
+        }
+        if books.iter().any(|book| book.chapter_starts.is_none()) {
+            find_chapters(&mut books);
+            if let Err(e) = write_books_to_disk(&books) {
+                eprintln!("Cannot write metadata file: {e}");
+            }
+        }
+        return ReadBooksResult {
+            books,
+            recovery: None,
+        };
+    }
+
+    if !metadata.exists() {
+        let books = sample_books();
+        write_books_to_disk(&books).expect("Cannot write metadata file");
+        return ReadBooksResult {
+            books,
+            recovery: None,
+        };
+    }
+
+    eprintln!("Cannot deserialize from metadata file, recovering the library");
+    // If the broken file can't be copied, it is kept where it is instead, so
+    // the recovered metadata is not written over it.
+    let mut write_error = None;
+    let bad_file = library_dir.join(BAD_LIBRARY_METADATA_FILENAME);
+    let bad_file = match std::fs::copy(&metadata, &bad_file) {
+        Ok(_) => bad_file,
+        Err(e) => {
+            eprintln!("Cannot keep broken metadata file: {e}");
+            write_error = Some(e.to_string());
+            metadata.clone()
+        }
+    };
 
-        write_books_to_disk(&books);
+    let mut books = books_from_content_files(&library_dir);
+    let recovered_count = books.len();
+    if books.is_empty() {
+        books = sample_books();
+    }
+    if write_error.is_none() {
+        if let Err(e) = write_books_to_disk(&books) {
+            eprintln!("Cannot write metadata file: {e}");
+            write_error = Some(e.to_string());
+        }
+    }
 
-        books
+    ReadBooksResult {
+        books,
+        recovery: Some(LibraryRecovery {
+            bad_file,
+            recovered_count,
+            write_error,
+        }),
     }
 }
 
-pub fn write_books_to_disk(books: &[Book]) {
-    let metadata = dirs::data_dir()
-        .unwrap()
-        .join(DATA_ROOT_DIR)
-        .join(LIBRARY_DIR_NAME)
-        .join(LIBRARY_METADATA_FILENAME);
+/// The books a new library starts with.
+fn sample_books() -> Vec<Book> {
+    vec![
+        write_book_with_title_content(
+            String::from("Lorem Ipsum"),
+            include_str!("texts/sample_lorem_ipsum.txt"),
+        )
+        .unwrap(),
+        write_book_with_title_content(
+            String::from("中国劳动法"),
+            include_str!("texts/sample_chinese.txt"),
+        )
+        .unwrap(),
+    ]
+}
 
-    let metadata = std::fs::File::create(metadata).expect("Cannot create metadata file");
-    serde_json::to_writer(metadata, books).expect("Error serializing metadata to file");
+/// Every book whose content is in the library directory, as `{id}.txt`, most
+/// recently written first. Files that can't be read are skipped.
+fn books_from_content_files(library_dir: &Path) -> Vec<Book> {
+    let Ok(entries) = std::fs::read_dir(library_dir) else {
+        return Vec::new();
+    };
+    let mut books: Vec<Book> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "txt" {
+                return None;
+            }
+            let id = path.file_stem()?.to_str()?;
+            if !is_valid_book_id(id) {
+                return None;
+            }
+            let content = std::fs::read_to_string(&path).ok()?;
+            let title = content
+                .lines()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or_default();
+            let mut book = book_with_content(id.to_string(), sanitize_title(title), &content);
+            if let Some(modified) = std::fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            {
+                book.last_read_time = modified.as_secs();
+            }
+            Some(book)
+        })
+        .collect();
+    books.sort_by(|a, b| {
+        b.last_read_time
+            .cmp(&a.last_read_time)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    books
 }
 
-pub fn get_book_content_from_disk(title: &str) -> String {
-    let book_content = dirs::data_dir()
-        .unwrap()
-        .join(DATA_ROOT_DIR)
-        .join(LIBRARY_DIR_NAME)
-        .join(format!("{title}.txt"));
-    std::fs::read_to_string(book_content).expect("Cannot read book content to string")
+/// Drop the books whose ID was broken, e.g. by editing the metadata file by
+/// hand, as their content can't be found. Books without an ID are kept to be
+/// migrated. Returns whether any were dropped.
+fn drop_books_with_invalid_ids(books: &mut Vec<Book>) -> bool {
+    let count = books.len();
+    books.retain(|book| {
+        let is_valid = book.id.is_empty() || is_valid_book_id(&book.id);
+        if !is_valid {
+            eprintln!(
+                "Dropping book {:?} with invalid ID {:?} from the library",
+                book.title, book.id
+            );
+        }
+        is_valid
+    });
+    books.len() != count
+}
+
+/// Drop the books that are still without an ID after migrating, as they can't
+/// be told apart in `BooksAux::id_to_index`. Their content is kept in its old
+/// file. Returns whether any were dropped.
+fn drop_unmigrated_books(books: &mut Vec<Book>) -> bool {
+    let count = books.len();
+    books.retain(|book| {
+        if book.id.is_empty() {
+            eprintln!(
+                "Dropping book {:?} from the library, as its content could not be moved",
+                book.title
+            );
+        }
+        !book.id.is_empty()
+    });
+    books.len() != count
+}
+
+/// Give an ID to every book from a library where books were keyed by title,
+/// and move its content from `{title}.txt` to `{id}.txt`.
+///
+/// Renaming a book used to keep the content under the original title, so if
+/// `{title}.txt` doesn't exist, the content is looked for among the text files
+/// not used by any other book, by its summary and length. Books whose content
+/// can't be found are dropped, as they couldn't be read anyway.
+///
+/// All the contents are read before any is moved, so if one can't be read,
+/// nothing is changed. A book whose content can't be moved keeps its old file
+/// and no ID.
+fn migrate_books_to_ids(library_dir: &Path, books: Vec<Book>) -> io::Result<Vec<Book>> {
+    let used_paths: HashSet<PathBuf> = books
+        .iter()
+        .filter_map(|book| {
+            if book.id.is_empty() {
+                legacy_book_content_path(library_dir, &book.title)
+            } else {
+                book_content_path(library_dir, &book.id).ok()
+            }
+        })
+        .filter(|path| path.exists())
+        .collect();
+    let mut orphan_paths: Vec<PathBuf> = std::fs::read_dir(library_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+                .filter(|path| !used_paths.contains(path))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut ids: HashSet<String> = books.iter().map(|book| book.id.clone()).collect();
+    // Each book with where its content is to be moved from, if it is to be.
+    let mut planned_books: Vec<(Book, Option<PathBuf>)> = Vec::with_capacity(books.len());
+
+    for mut book in books {
+        if !book.id.is_empty() {
+            planned_books.push((book, None));
+            continue;
+        }
+
+        let legacy_path = legacy_book_content_path(library_dir, &book.title);
+        let content = legacy_path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok());
+        let (path, content) = match (legacy_path, content) {
+            (Some(path), Some(content)) => (path, content),
+            _ => {
+                let found = orphan_paths.iter().position(|orphan_path| {
+                    std::fs::read_to_string(orphan_path).is_ok_and(|content| {
+                        content.chars().count() == book.total_character_count
+                            && content.starts_with(&book.summary)
+                    })
+                });
+                let Some(found) = found else {
+                    continue;
+                };
+                let path = orphan_paths.swap_remove(found);
+                let content = std::fs::read_to_string(&path)?;
+                (path, content)
+            }
+        };
+
+        // Older libraries may have the same content under different titles.
+        let mut id = book_id_from_content(&content);
+        if ids.contains(&id) {
+            id = book_id_from_content(&format!("{}\n{content}", book.title));
+        }
+
+        ids.insert(id.clone());
+        book.id = id;
+        planned_books.push((book, Some(path)));
+    }
+
+    let mut migrated_books = Vec::with_capacity(planned_books.len());
+    for (mut book, path) in planned_books {
+        if let Some(path) = path {
+            let moved = book_content_path(library_dir, &book.id)
+                .and_then(|new_path| std::fs::rename(&path, new_path));
+            if let Err(e) = moved {
+                eprintln!("Cannot move content of book {:?}: {e}", book.title);
+                book.id = String::new();
+            }
+        }
+        migrated_books.push(book);
+    }
+
+    Ok(migrated_books)
+}
+
+/// Find the chapters of the books imported before chapters were looked for. A
+/// book whose content can't be read is tried again next time.
+fn find_chapters(books: &mut [Book]) {
+    for book in books
+        .iter_mut()
+        .filter(|book| book.chapter_starts.is_none())
+    {
+        if let Ok(content) = get_book_content_from_disk(&book.id) {
+            book.chapter_starts = Some(chapters::chapter_starts(&content));
+        }
+    }
+}
+
+pub fn write_books_to_disk(books: &[Book]) -> io::Result<()> {
+    let metadata = library_dir().join(LIBRARY_METADATA_FILENAME);
+    persist::write_json_atomically(&metadata, &books)
+}
+
+pub fn get_book_content_from_disk(id: &str) -> io::Result<String> {
+    let book_content = book_content_path(&library_dir(), id)?;
+    std::fs::read_to_string(book_content)
+}
+
+/// The content of a book as it is sent to the reader, which progress and
+/// bookmarks are offsets into.
+pub fn get_reader_content_from_disk(book: &Book) -> io::Result<String> {
+    Ok(zhconv::convert(
+        &get_book_content_from_disk(&book.id)?,
+        book.chinese_conversion,
+    ))
+}
+
+pub fn remove_book_content_from_disk(id: &str) -> io::Result<()> {
+    let book_content = book_content_path(&library_dir(), id)?;
+    std::fs::remove_file(book_content)
+}
+
+/// Everything applied to the text of a book when it is imported.
+#[derive(Debug)]
+pub struct ImportPipeline {
+    normalization: NormalizeOptions,
+    cleanup_rules: CompiledRules,
+    chinese_conversion: ChineseConversion,
+}
+
+impl ImportPipeline {
+    pub fn new(import: &config::Import) -> Result<Self, regex::Error> {
+        Ok(Self {
+            normalization: import.normalization.clone(),
+            cleanup_rules: CompiledRules::new(&import.cleanup_rules)?,
+            chinese_conversion: import.chinese_conversion,
+        })
+    }
+
+    pub fn process(&self, text: &str) -> String {
+        let normalized_text = normalize::normalize(text, &self.normalization);
+        let cleaned_text = self.cleanup_rules.apply(&normalized_text);
+        let standardized_text = standardize_text(&cleaned_text);
+        zhconv::convert(&standardized_text, self.chinese_conversion)
+    }
+
+    /// What the cleanup rules change in the first lines of a text about to be
+    /// imported, which are normalized first as they are on import.
+    pub fn preview_cleanup(&self, text: &str) -> Vec<CleanupPreviewLine> {
+        let sample: Vec<&str> = text
+            .lines()
+            .take(cleanup::PREVIEW_SAMPLE_LINE_COUNT)
+            .collect();
+        let normalized_sample = normalize::normalize(&sample.join("\n"), &self.normalization);
+        self.cleanup_rules.preview(&normalized_sample)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,28 +1017,31 @@ pub struct NewBooksResult {
 }
 
 pub fn new_and_standardize_books(
-    book_infos: &[ReaderBookInfo],
-    title_to_index: &HashMap<String, usize>,
+    book_infos: &[NewBookInfo],
+    id_to_index: &HashMap<String, usize>,
+    pipeline: &ImportPipeline,
 ) -> NewBooksResult {
     let mut successful = Vec::new();
     let mut failed = Vec::new();
-    let mut successful_titles = HashSet::new();
+    let mut successful_ids = HashSet::new();
 
     for book_info in book_infos {
-        let ReaderBookInfo { title, content, .. } = book_info;
+        let NewBookInfo { title, content } = book_info;
 
-        if title_to_index.get(title).is_some() {
+        let title = &sanitize_title(title);
+        let standardized_text = pipeline.process(content);
+        let id = book_id_from_content(&standardized_text);
+        if id_to_index.contains_key(&id) || successful_ids.contains(&id) {
             failed.push(title.clone());
             continue;
         }
 
-        let standardized_text = standardize_text(content);
         match write_book_with_title_content(title.clone(), &standardized_text) {
-            Ok(book) if !successful_titles.contains(&book.title) => {
-                successful_titles.insert(book.title.clone());
+            Ok(book) => {
+                successful_ids.insert(book.id.clone());
                 successful.push(book);
             }
-            _ => {
+            Err(_) => {
                 failed.push(title.clone());
             }
         }
@@ -224,16 +1135,19 @@ fn standardize_text(text: &str) -> String {
 }
 
 fn write_book_with_title_content(title: String, standardized_text: &str) -> std::io::Result<Book> {
-    let standardized_file = dirs::data_dir()
-        .unwrap()
-        .join(DATA_ROOT_DIR)
-        .join(LIBRARY_DIR_NAME)
-        .join(format!("{title}.txt"));
+    let id = book_id_from_content(standardized_text);
+    let standardized_file = book_content_path(&library_dir(), &id)?;
     let mut standardized_file = std::fs::File::create(standardized_file)?;
 
     standardized_file.write_all(standardized_text.as_bytes())?;
 
-    Ok(Book {
+    Ok(book_with_content(id, title, standardized_text))
+}
+
+/// A new book, not read yet, whose content is stored under `id`.
+fn book_with_content(id: String, title: String, standardized_text: &str) -> Book {
+    Book {
+        id,
         title,
         summary: standardized_text
             .chars()
@@ -245,7 +1159,13 @@ fn write_book_with_title_content(title: String, standardized_text: &str) -> std:
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_secs(),
-    })
+        chinese_conversion: ChineseConversion::None,
+        settings: BookSettings::default(),
+        chapter_starts: Some(chapters::chapter_starts(standardized_text)),
+        bookmarks: Vec::new(),
+        highlights: Vec::new(),
+        history: VecDeque::new(),
+    }
 }
 
 #[cfg(test)]
@@ -259,8 +1179,188 @@ mod tests {
         dbg!(books);
     }
 
+    fn legacy_book(title: &str, content: &str) -> Book {
+        Book {
+            id: String::new(),
+            title: title.to_string(),
+            summary: content.chars().take(Book::SUMMARY_LENGTH).collect(),
+            total_character_count: content.chars().count(),
+            progress: 0,
+            last_read_time: 0,
+            chinese_conversion: ChineseConversion::None,
+            settings: BookSettings::default(),
+            chapter_starts: None,
+            bookmarks: Vec::new(),
+            highlights: Vec::new(),
+            history: VecDeque::new(),
+        }
+    }
+
+    fn new_temp_library_dir(name: &str) -> PathBuf {
+        let library_dir =
+            std::env::temp_dir().join(format!("sneaky-reader-test-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&library_dir);
+        std::fs::create_dir_all(&library_dir).unwrap();
+        library_dir
+    }
+
+    #[test]
+    fn test_book_id_from_content() {
+        let id = book_id_from_content("Hello\n");
+        assert_eq!(id.len(), BOOK_ID_BYTE_COUNT * 2);
+        assert_eq!(id, book_id_from_content("Hello\n"));
+        assert_ne!(id, book_id_from_content("Hello!\n"));
+    }
+
+    #[test]
+    fn test_is_valid_book_id() {
+        assert!(is_valid_book_id(&book_id_from_content("Hello\n")));
+        assert!(!is_valid_book_id(""));
+        assert!(!is_valid_book_id("../../../etc/passwd"));
+        assert!(!is_valid_book_id("0123456789ABCDEF0123456789ABCDEF"));
+        assert!(!is_valid_book_id("0123456789abcdef0123456789abcde/"));
+    }
+
+    #[test]
+    fn test_book_content_path_rejects_traversal() {
+        let error = book_content_path(Path::new("library"), "../config").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_drop_books_with_invalid_ids() {
+        let mut valid = legacy_book("Valid", "content");
+        valid.id = book_id_from_content("content");
+        let mut invalid = legacy_book("Invalid", "content");
+        invalid.id = String::from("../config");
+        let mut books = vec![valid, invalid, legacy_book("Legacy", "content")];
+        assert!(drop_books_with_invalid_ids(&mut books));
+        let titles: Vec<&str> = books.iter().map(|book| book.title.as_str()).collect();
+        assert_eq!(titles, ["Valid", "Legacy"]);
+        assert!(!drop_books_with_invalid_ids(&mut books));
+    }
+
+    #[test]
+    fn test_unmigrated_books_are_dropped() {
+        let library_dir = new_temp_library_dir("migrate-dropped");
+        std::fs::write(library_dir.join("First.txt"), "First content\n").unwrap();
+        std::fs::write(library_dir.join("Second.txt"), "Second content\n").unwrap();
+        std::fs::write(library_dir.join("Third.txt"), "Third content\n").unwrap();
+        for content in ["First content\n", "Second content\n"] {
+            let blocked_path = book_content_path(&library_dir, &book_id_from_content(content));
+            std::fs::create_dir(blocked_path.unwrap()).unwrap();
+        }
+
+        let books = vec![
+            legacy_book("First", "First content\n"),
+            legacy_book("Second", "Second content\n"),
+            legacy_book("Third", "Third content\n"),
+        ];
+        let mut books = migrate_books_to_ids(&library_dir, books).unwrap();
+        assert!(drop_unmigrated_books(&mut books));
+        let titles: Vec<&str> = books.iter().map(|book| book.title.as_str()).collect();
+        assert_eq!(titles, ["Third"]);
+        assert!(library_dir.join("First.txt").exists());
+        assert!(library_dir.join("Second.txt").exists());
+
+        let books_aux = BooksAux::new(books);
+        assert!(!books_aux.id_to_index.contains_key(""));
+        assert_eq!(books_aux.id_to_index.len(), 1);
+
+        std::fs::remove_dir_all(&library_dir).unwrap();
+    }
+
+    #[test]
+    fn test_legacy_book_content_path() {
+        let library_dir = Path::new("library");
+        assert_eq!(
+            legacy_book_content_path(library_dir, "A: Book?"),
+            Some(library_dir.join("A: Book?.txt"))
+        );
+        assert_eq!(legacy_book_content_path(library_dir, "../secret"), None);
+        assert_eq!(legacy_book_content_path(library_dir, "a/b"), None);
+        assert_eq!(legacy_book_content_path(library_dir, "a\\b"), None);
+    }
+
+    #[test]
+    fn test_sanitize_title() {
+        assert_eq!(sanitize_title("  CON/PRN:?\n"), "CON/PRN:?");
+        assert_eq!(sanitize_title("a\tb"), "a b");
+        assert_eq!(sanitize_title(" \u{7} "), "Untitled");
+        assert_eq!(
+            sanitize_title(&"长".repeat(MAX_TITLE_LENGTH + 1)),
+            "长".repeat(MAX_TITLE_LENGTH)
+        );
+    }
+
+    #[test]
+    fn test_migrate_books_to_ids() {
+        let library_dir = new_temp_library_dir("migrate");
+        std::fs::write(library_dir.join("First.txt"), "First content\n").unwrap();
+        // Renamed from "Old title" by an older version, which kept the file.
+        std::fs::write(library_dir.join("Old title.txt"), "Second content\n").unwrap();
+        // Same content as the first book, under another title.
+        std::fs::write(library_dir.join("Copy.txt"), "First content\n").unwrap();
+        // Outside of the library directory, so never to be moved.
+        let escaped_title = format!("../sneaky-reader-test-escaped-{}", std::process::id());
+        let escaped_path = std::env::temp_dir().join(format!("{}.txt", &escaped_title[3..]));
+        std::fs::write(&escaped_path, "Escaped content\n").unwrap();
+
+        let books = vec![
+            legacy_book("First", "First content\n"),
+            legacy_book("New title", "Second content\n"),
+            legacy_book("Copy", "First content\n"),
+            legacy_book("Missing", "Missing content\n"),
+            legacy_book(&escaped_title, "Escaped content\n"),
+        ];
+        let books = migrate_books_to_ids(&library_dir, books).unwrap();
+
+        let titles: Vec<&str> = books.iter().map(|book| book.title.as_str()).collect();
+        assert_eq!(titles, ["First", "New title", "Copy"]);
+        assert_eq!(books[0].id, book_id_from_content("First content\n"));
+        assert_eq!(books[1].id, book_id_from_content("Second content\n"));
+        assert_ne!(books[2].id, books[0].id);
+        for (book, content) in
+            books
+                .iter()
+                .zip(["First content\n", "Second content\n", "First content\n"])
+        {
+            let path = book_content_path(&library_dir, &book.id).unwrap();
+            assert_eq!(std::fs::read_to_string(path).unwrap(), content);
+        }
+        assert!(!library_dir.join("First.txt").exists());
+        assert!(!library_dir.join("Old title.txt").exists());
+        assert!(escaped_path.exists());
+
+        std::fs::remove_dir_all(&library_dir).unwrap();
+        std::fs::remove_file(&escaped_path).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_books_to_ids_keeps_unmoved_content() {
+        let library_dir = new_temp_library_dir("migrate-unmoved");
+        std::fs::write(library_dir.join("First.txt"), "First content\n").unwrap();
+        std::fs::write(library_dir.join("Second.txt"), "Second content\n").unwrap();
+        // Nothing can be moved over a directory.
+        let blocked_path =
+            book_content_path(&library_dir, &book_id_from_content("Second content\n"));
+        std::fs::create_dir(blocked_path.unwrap()).unwrap();
+
+        let books = vec![
+            legacy_book("First", "First content\n"),
+            legacy_book("Second", "Second content\n"),
+        ];
+        let books = migrate_books_to_ids(&library_dir, books).unwrap();
+
+        assert_eq!(books[0].id, book_id_from_content("First content\n"));
+        assert_eq!(books[1].id, "");
+        assert!(library_dir.join("Second.txt").exists());
+
+        std::fs::remove_dir_all(&library_dir).unwrap();
+    }
+
     fn test_with_input_output(input: &str, expected_output: &str) {
-        let actual_output = standardize_text(&input);
+        let actual_output = standardize_text(input);
         assert_eq!(actual_output, expected_output);
     }
 
@@ -295,4 +1395,242 @@ mod tests {
             include_str!("texts/test4_output.txt"),
         );
     }
+
+    #[test]
+    fn test_book_settings_sanitize() {
+        let settings = BookSettings {
+            text_size: Some(0),
+            text_color: Some(String::from("#ffffffff")),
+            line_height: Some(f64::NAN),
+            font_family: Some(String::from("  ")),
+        };
+        assert_eq!(
+            settings.sanitize(),
+            BookSettings {
+                text_color: Some(String::from("#ffffffff")),
+                ..Default::default()
+            }
+        );
+
+        let settings = BookSettings {
+            text_size: Some(20),
+            text_color: None,
+            line_height: Some(1.8),
+            font_family: Some(String::from("serif")),
+        };
+        assert_eq!(settings.clone().sanitize(), settings);
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let mut book = legacy_book("a", "content");
+        assert_eq!(book.add_bookmark(50, None, String::new(), 0).id, 0);
+        assert_eq!(
+            book.add_bookmark(10, Some(String::from("  ")), String::new(), 0)
+                .id,
+            1
+        );
+        assert_eq!(book.bookmarks[0].offset, 10);
+        assert_eq!(book.bookmarks[0].note, None);
+
+        assert!(book.remove_bookmark(0));
+        assert!(!book.remove_bookmark(0));
+        // IDs of removed bookmarks are only reused if they were the last ones.
+        assert_eq!(book.add_bookmark(30, None, String::new(), 0).id, 2);
+        assert!(book.bookmark_mut(1).is_some());
+    }
+
+    #[test]
+    fn test_highlights() {
+        let mut book = legacy_book("a", "content");
+        book.add_highlight(30, 40, String::from("b"), None, 0);
+        let highlight =
+            book.add_highlight(10, 20, String::from("a"), Some(String::from(" note ")), 0);
+        assert_eq!(highlight.id, 1);
+        assert_eq!(highlight.note.as_deref(), Some("note"));
+        assert_eq!(book.highlights[0].text, "a");
+        assert!(book.remove_highlight(0));
+        assert!(book.highlight_mut(0).is_none());
+    }
+
+    #[test]
+    fn test_push_history() {
+        let mut book = legacy_book("a", "content");
+        book.push_history(10, 0);
+        book.push_history(10, 1);
+        assert_eq!(book.history.len(), 1);
+        for progress in 0..HISTORY_LENGTH {
+            book.push_history(100 + progress, 2);
+        }
+        assert_eq!(book.history.len(), HISTORY_LENGTH);
+        assert_eq!(book.history[0].progress, 100);
+    }
+
+    #[test]
+    fn test_history_of_progress() {
+        let mut books_aux = BooksAux::new(vec![
+            legacy_book("a", "content"),
+            legacy_book("b", "content"),
+        ]);
+
+        // Reading page by page is not remembered.
+        for (i, time) in (1000..1100).step_by(10).enumerate() {
+            books_aux.update_progress(0, i * 500, time);
+        }
+        assert!(books_aux.books[0].history.is_empty());
+
+        // A held key skips pages quickly from where the book was read.
+        let start = books_aux.books[0].progress;
+        for progress in (start + 200..start + 10_000).step_by(200) {
+            books_aux.update_progress(0, progress, 1200);
+        }
+        assert_eq!(books_aux.books[0].history.len(), 1);
+        assert_eq!(books_aux.books[0].history[0].progress, start);
+
+        assert!(books_aux.go_back(0));
+        assert_eq!(books_aux.books[0].progress, start);
+        assert!(!books_aux.go_back(0));
+
+        books_aux.jump(1, 300, 1300);
+        assert_eq!(books_aux.books[0].title, "b");
+        assert_eq!(books_aux.books[0].progress, 300);
+        assert_eq!(books_aux.books[0].history[0].progress, 0);
+        assert!(books_aux.go_back(0));
+        assert_eq!(books_aux.books[0].progress, 0);
+    }
+
+    #[test]
+    fn test_remove_first_book_resets_progress() {
+        let mut books_aux = BooksAux::new(vec![
+            legacy_book("a", "content"),
+            legacy_book("b", "content"),
+        ]);
+        books_aux.update_progress(0, 50_000, 1000);
+        books_aux.update_progress(0, 50_500, 1010);
+        books_aux.books[0].history.clear();
+
+        assert_eq!(books_aux.remove(0).title, "a");
+        assert_eq!(books_aux.id_to_index[&books_aux.books[0].id], 0);
+        // Read on from where "b" was, not where "a" was.
+        books_aux.update_progress(0, 500, 1011);
+        assert!(books_aux.books[0].history.is_empty());
+        assert_eq!(books_aux.old_progress, 0);
+    }
+
+    #[test]
+    fn test_preview_cleanup_normalizes_first() {
+        let import = config::Import {
+            normalization: NormalizeOptions {
+                full_width_to_half_width: true,
+                ..Default::default()
+            },
+            cleanup_rules: vec![cleanup::CleanupRule::DeleteLine {
+                pattern: String::from("^AD"),
+            }],
+            ..Default::default()
+        };
+        let pipeline = ImportPipeline::new(&import).unwrap();
+        let preview = pipeline.preview_cleanup("Chapter 1\nＡＤ: buy now\nText");
+        assert_eq!(preview.len(), 1);
+        assert_eq!(preview[0].line_number, 2);
+        assert_eq!(preview[0].before, "AD: buy now");
+        assert_eq!(preview[0].after, None);
+    }
+
+    #[test]
+    fn test_books_from_content_files() {
+        let library_dir = new_temp_library_dir("recover");
+        let content = "\n  The Title \nFirst line.\n";
+        let id = book_id_from_content(content);
+        std::fs::write(library_dir.join(format!("{id}.txt")), content).unwrap();
+        std::fs::write(library_dir.join("Legacy title.txt"), "Legacy\n").unwrap();
+        std::fs::write(library_dir.join(LIBRARY_METADATA_FILENAME), "[{").unwrap();
+
+        let books = books_from_content_files(&library_dir);
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].id, id);
+        assert_eq!(books[0].title, "The Title");
+        assert_eq!(books[0].progress, 0);
+        assert_eq!(books[0].total_character_count, content.chars().count());
+
+        std::fs::remove_dir_all(&library_dir).unwrap();
+    }
+
+    #[test]
+    fn test_text_between() {
+        let content = "第一章\n😀 他推开门";
+        assert_eq!(text_between(content, 4, 7), "😀 ");
+        assert_eq!(text_between(content, 7, 100), "他推开门");
+        assert_eq!(text_between(content, 5, 2), "");
+    }
+
+    #[test]
+    fn test_snippet_at() {
+        let content = "第一章\n😀 他推开门，\n外面在下雨。";
+        assert_eq!(byte_index_of_utf16_offset(content, 4), 10);
+        assert_eq!(byte_index_of_utf16_offset(content, 100), content.len());
+        assert_eq!(snippet_at(content, 4), "😀 他推开门，");
+        assert_eq!(snippet_at(content, 3), "😀 他推开门，");
+        assert_eq!(snippet_at(content, 100), "");
+
+        let long = "字".repeat(SNIPPET_LENGTH * 2);
+        assert_eq!(snippet_at(&long, 0).chars().count(), SNIPPET_LENGTH);
+    }
+
+    #[test]
+    fn test_search_book() {
+        let content = "Chapter One\n😀 Ｈｅｌｌｏ，world!\nhello again, HELLO";
+        let search = |query: &str, options: SearchOptions| {
+            search_book(content, query, options)
+                .unwrap()
+                .matches
+                .into_iter()
+                .map(|found| (found.start, found.end, found.text))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            search("hello", SearchOptions::default()),
+            [(28, 33, String::from("hello"))]
+        );
+        let ignore_case = SearchOptions {
+            ignore_case: true,
+            ..Default::default()
+        };
+        assert_eq!(search("hello", ignore_case).len(), 2);
+        let ignore_width = SearchOptions {
+            ignore_width: true,
+            ..ignore_case
+        };
+        assert_eq!(
+            search("hello,", ignore_width),
+            [(15, 21, String::from("Ｈｅｌｌｏ，"))]
+        );
+        assert_eq!(search("hello", ignore_width).len(), 3);
+        let regex = SearchOptions {
+            regex: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            search(r"^\w+ again", regex),
+            [(28, 39, String::from("hello again"))]
+        );
+        assert!(search("", regex).is_empty());
+        assert!(search("x*", regex).is_empty());
+        assert!(search_book(content, "(", regex).is_err());
+
+        let found = &search_book(content, "world", ignore_width).unwrap().matches[0];
+        assert_eq!(found.before, "😀 Ｈｅｌｌｏ，");
+        assert_eq!(found.after, "!");
+
+        let many = "字".repeat(MAX_SEARCH_MATCHES + 1);
+        let result = search_book(&many, "字", SearchOptions::default()).unwrap();
+        assert_eq!(result.matches.len(), MAX_SEARCH_MATCHES);
+        assert!(result.is_truncated);
+        assert_eq!(result.matches[1].before.chars().count(), 1);
+        assert_eq!(
+            result.matches[100].before.chars().count(),
+            SEARCH_CONTEXT_LENGTH
+        );
+    }
 }
@@ -0,0 +1,327 @@
+//! Serve the content of a book as fake application log lines, such as
+//! `2026-10-18 10:02:13 INFO  [worker-3] ...text...`.
+//!
+//! Offsets sent to and from the reader are in UTF-16 code units of the text it
+//! shows, so [`LogStream`] keeps a map between offsets in the log lines and in
+//! the original content, where progress is stored.
+
+use chrono::{DateTime, Duration, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+const LEVELS: &[&str] = &[
+    "INFO ", "INFO ", "INFO ", "INFO ", "DEBUG", "DEBUG", "WARN ",
+];
+const THREADS: &[&str] = &[
+    "main",
+    "worker-1",
+    "worker-2",
+    "worker-3",
+    "worker-4",
+    "scheduler",
+    "http-nio-8080-exec-2",
+    "pool-1-thread-1",
+];
+
+/// Characters after which a line is preferably broken.
+const SENTENCE_ENDS: &[char] = &['。', '！', '？', '；', '…', '.', '!', '?', ';'];
+
+/// Most seconds between two log lines.
+const MAX_SECONDS_BETWEEN_LINES: u64 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LogStreamOptions {
+    pub enabled: bool,
+    /// Longer paragraphs are split into several log lines, at the end of a
+    /// sentence if there is one.
+    pub max_line_length: usize,
+}
+
+impl Default for LogStreamOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_line_length: 80,
+        }
+    }
+}
+
+/// A part of the original content shown as a single log line. All offsets
+/// are in UTF-16 code units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Segment {
+    /// Where the log line starts, before its prefix.
+    line_start: usize,
+    /// Where the text after the prefix starts.
+    text_start: usize,
+    original_start: usize,
+    length: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogStream {
+    pub content: String,
+    segments: Vec<Segment>,
+}
+
+impl LogStream {
+    /// Turn `content` into log lines, timed so that the line at `progress`
+    /// is logged at `now`, in the time zone of `now`.
+    pub fn new<Tz: TimeZone>(
+        content: &str,
+        options: &LogStreamOptions,
+        progress: usize,
+        now: DateTime<Tz>,
+    ) -> Self
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        let chunks = split_into_chunks(content, options.max_line_length.max(1));
+
+        // The seconds since the first line, for every line.
+        let mut rng = SplitMix64(chunks.len() as u64);
+        let mut elapsed = 0;
+        let elapsed: Vec<u64> = chunks
+            .iter()
+            .map(|_| {
+                let current = elapsed;
+                elapsed += rng.next() % (MAX_SECONDS_BETWEEN_LINES + 1);
+                current
+            })
+            .collect();
+        let progress_chunk = chunks
+            .partition_point(|chunk| chunk.original_start <= progress)
+            .saturating_sub(1);
+        let start_time =
+            now - Duration::seconds(elapsed.get(progress_chunk).copied().unwrap_or(0) as i64);
+
+        let mut log_content = String::with_capacity(content.len() * 2);
+        let mut length = 0;
+        let mut segments = Vec::with_capacity(chunks.len());
+        for (i, chunk) in chunks.iter().enumerate() {
+            if i > 0 {
+                log_content.push('\n');
+                length += 1;
+            }
+            let line_start = length;
+
+            let level = LEVELS[(rng.next() % LEVELS.len() as u64) as usize];
+            let thread = THREADS[(rng.next() % THREADS.len() as u64) as usize];
+            let prefix = format!(
+                "{} {level} [{thread}] ",
+                format_timestamp(&(start_time.clone() + Duration::seconds(elapsed[i] as i64)))
+            );
+            log_content.push_str(&prefix);
+            length += prefix.encode_utf16().count();
+
+            log_content.push_str(chunk.text);
+            segments.push(Segment {
+                line_start,
+                text_start: length,
+                original_start: chunk.original_start,
+                length: chunk.length,
+            });
+            length += chunk.length;
+        }
+
+        Self {
+            content: log_content,
+            segments,
+        }
+    }
+
+    /// Like [`LogStream::new`], timed from the current local time.
+    pub fn new_now(content: &str, options: &LogStreamOptions, progress: usize) -> Self {
+        Self::new(content, options, progress, Local::now())
+    }
+
+    /// Map an offset in the original content to the log lines. An offset at
+    /// the start of a line maps to the start of its prefix, so that a page
+    /// starting there shows the whole log line.
+    pub fn to_log_offset(&self, original: usize) -> usize {
+        let i = self
+            .segments
+            .partition_point(|segment| segment.original_start <= original);
+        let Some(segment) = i.checked_sub(1).map(|i| self.segments[i]) else {
+            return 0;
+        };
+        if original == segment.original_start {
+            segment.line_start
+        } else {
+            segment.text_start + (original - segment.original_start).min(segment.length)
+        }
+    }
+
+    /// Map an offset in the log lines back to the original content. Offsets in
+    /// a prefix map to the start of the text after it.
+    pub fn to_original_offset(&self, log: usize) -> usize {
+        let i = self
+            .segments
+            .partition_point(|segment| segment.line_start <= log);
+        let Some(segment) = i.checked_sub(1).map(|i| self.segments[i]) else {
+            return 0;
+        };
+        segment.original_start + log.saturating_sub(segment.text_start).min(segment.length)
+    }
+}
+
+struct Chunk<'a> {
+    text: &'a str,
+    /// In UTF-16 code units, like `length`.
+    original_start: usize,
+    length: usize,
+}
+
+/// Split every line of `content` into chunks of at most `max_length`
+/// characters, without the spaces between them.
+fn split_into_chunks(content: &str, max_length: usize) -> Vec<Chunk<'_>> {
+    let mut chunks = Vec::new();
+    let mut offset = 0;
+    for line in content.split('\n') {
+        let mut rest = line;
+        let mut rest_offset = offset;
+        loop {
+            // A log line never starts with spaces.
+            let trimmed = rest.trim_start();
+            rest_offset += rest[..rest.len() - trimmed.len()].encode_utf16().count();
+            rest = trimmed;
+            if rest.is_empty() {
+                break;
+            }
+
+            let end = chunk_end(rest, max_length);
+            let text = &rest[..end];
+            let length = text.encode_utf16().count();
+            chunks.push(Chunk {
+                text,
+                original_start: rest_offset,
+                length,
+            });
+            rest = &rest[end..];
+            rest_offset += length;
+        }
+        offset += line.encode_utf16().count() + 1;
+    }
+    chunks
+}
+
+/// The byte length of the first chunk of `text`, breaking after the last
+/// sentence end within `max_length` characters, or at `max_length` if none.
+fn chunk_end(text: &str, max_length: usize) -> usize {
+    let mut last_sentence_end = None;
+    for (count, (i, c)) in text.char_indices().enumerate() {
+        if count == max_length {
+            return last_sentence_end.unwrap_or(i);
+        }
+        if SENTENCE_ENDS.contains(&c) {
+            last_sentence_end = Some(i + c.len_utf8());
+        }
+    }
+    text.len()
+}
+
+/// `YYYY-MM-DD HH:MM:SS` in the time zone of `time`.
+fn format_timestamp<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    time.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// A small deterministic generator, so that the same book gets the same
+/// levels and threads every time.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, Utc};
+
+    /// 2026-10-18 10:02:13 UTC.
+    fn time() -> DateTime<Utc> {
+        Utc.timestamp_opt(1_792_317_733, 0).unwrap()
+    }
+
+    fn options(max_line_length: usize) -> LogStreamOptions {
+        LogStreamOptions {
+            enabled: true,
+            max_line_length,
+        }
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(&time()), "2026-10-18 10:02:13");
+        let beijing = FixedOffset::east_opt(8 * 3600).unwrap();
+        assert_eq!(
+            format_timestamp(&time().with_timezone(&beijing)),
+            "2026-10-18 18:02:13"
+        );
+        let leap_day = Utc.with_ymd_and_hms(2000, 2, 29, 0, 0, 0).unwrap();
+        assert_eq!(format_timestamp(&leap_day), "2000-02-29 00:00:00");
+    }
+
+    #[test]
+    fn test_split_into_chunks() {
+        let content = "第一句。第二句很长很长。\n\nshort";
+        let chunks = split_into_chunks(content, 8);
+        let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.text).collect();
+        assert_eq!(texts, ["第一句。", "第二句很长很长。", "short"]);
+        let starts: Vec<usize> = chunks.iter().map(|chunk| chunk.original_start).collect();
+        assert_eq!(starts, [0, 4, 14]);
+
+        let chunks = split_into_chunks("abcdefg", 3);
+        let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.text).collect();
+        assert_eq!(texts, ["abc", "def", "g"]);
+    }
+
+    #[test]
+    fn test_log_lines() {
+        let content = "Hello. World.\nBye";
+        let log_stream = LogStream::new(content, &options(8), 0, time());
+        let lines: Vec<&str> = log_stream.content.split('\n').collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("2026-10-18 10:02:13 "));
+        assert!(lines[0].ends_with("] Hello."));
+        assert!(lines[1].ends_with("] World."));
+        assert!(lines[2].ends_with("] Bye"));
+    }
+
+    #[test]
+    fn test_offsets_round_trip() {
+        let content = "第一句。第二句。\n𝄞 and more text\n\nLast";
+        let log_stream = LogStream::new(content, &options(5), 3, time());
+        let utf16: Vec<u16> = content.encode_utf16().collect();
+        let log_utf16: Vec<u16> = log_stream.content.encode_utf16().collect();
+
+        for (original, &unit) in utf16.iter().enumerate() {
+            if unit == u16::from(b'\n') {
+                continue;
+            }
+            let log = log_stream.to_log_offset(original);
+            assert_eq!(log_stream.to_original_offset(log), original);
+            // Spaces between log lines aren't shown.
+            if unit == u16::from(b' ') {
+                continue;
+            }
+
+            let is_line_start = log == 0 || log_utf16[log - 1] == u16::from(b'\n');
+            if is_line_start {
+                // The whole log line, prefix included, starts there.
+                assert_eq!(log_utf16[log], u16::from(b'2'));
+            } else {
+                assert_eq!(log_utf16[log], unit);
+            }
+        }
+    }
+}
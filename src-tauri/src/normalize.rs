@@ -0,0 +1,288 @@
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+/// Runs of the same exclamation or question mark longer than this are
+/// shortened to this length.
+const MAX_REPEATED_PUNCTUATION: usize = 3;
+
+/// Punctuation that never needs to be repeated, so any run of it is collapsed
+/// into one.
+const SINGLE_PUNCTUATION: &[char] = &[',', ';', ':', '，', '、', '。', '；', '：'];
+
+/// Punctuation that is sometimes repeated for emphasis.
+const REPEATABLE_PUNCTUATION: &[char] = &['!', '?', '！', '？', '~', '～'];
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct NormalizeOptions {
+    /// Apply Unicode NFKC normalization before everything else.
+    pub nfkc: bool,
+    /// Convert full-width ASCII letters and digits to half-width. Full-width
+    /// punctuation is kept, as it is standard in CJK text.
+    pub full_width_to_half_width: bool,
+    /// Convert straight and vertical double quotes into paired curly ones.
+    pub unify_quotes: bool,
+    /// Convert `...`, `。。。`, `⋯` and lone `…` into `……` next to CJK text,
+    /// or into `...` otherwise.
+    pub unify_ellipses: bool,
+    pub collapse_repeated_punctuation: bool,
+    /// Collapse runs of whitespace inside a line, including ideographic
+    /// spaces, into one space.
+    pub collapse_spaces: bool,
+}
+
+impl NormalizeOptions {
+    pub fn is_noop(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+pub fn normalize(text: &str, options: &NormalizeOptions) -> String {
+    if options.is_noop() {
+        return text.to_string();
+    }
+
+    let mut text = if options.nfkc {
+        text.nfkc().collect()
+    } else {
+        text.to_string()
+    };
+
+    if options.full_width_to_half_width {
+        text = text.chars().map(full_width_to_half_width).collect();
+    }
+
+    let mut ret = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let mut line = line.to_string();
+        if options.unify_ellipses {
+            line = unify_ellipses(&line);
+        }
+        if options.unify_quotes {
+            line = unify_quotes(&line);
+        }
+        if options.collapse_repeated_punctuation {
+            line = collapse_repeated_punctuation(&line);
+        }
+        if options.collapse_spaces {
+            line = collapse_spaces(&line);
+        }
+        ret.push_str(&line);
+    }
+    ret
+}
+
+fn full_width_to_half_width(c: char) -> char {
+    match c {
+        '０'..='９' | 'Ａ'..='Ｚ' | 'ａ'..='ｚ' => {
+            char::from_u32(c as u32 - 0xFEE0).unwrap_or(c)
+        }
+        _ => c,
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{2E80}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF00}'..='\u{FFEF}'
+        | '\u{20000}'..='\u{2FA1F}')
+}
+
+fn unify_ellipses(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut ret = String::with_capacity(line.len());
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let run_length = chars[i..].iter().take_while(|&&d| d == c).count();
+        let is_ellipsis = match c {
+            '.' | '。' | '·' => run_length >= 3,
+            '…' | '⋯' => true,
+            _ => false,
+        };
+        if !is_ellipsis {
+            ret.push(c);
+            i += 1;
+            continue;
+        }
+
+        let prev = i.checked_sub(1).map(|j| chars[j]);
+        let next = chars.get(i + run_length).copied();
+        if prev.is_some_and(is_cjk) || next.is_some_and(is_cjk) {
+            ret.push_str("……");
+        } else {
+            ret.push_str("...");
+        }
+        i += run_length;
+    }
+
+    ret
+}
+
+fn unify_quotes(line: &str) -> String {
+    let mut is_open = false;
+    line.chars()
+        .map(|c| match c {
+            '"' | '＂' => {
+                is_open = !is_open;
+                if is_open {
+                    '“'
+                } else {
+                    '”'
+                }
+            }
+            '〝' => '“',
+            '〞' | '〟' => '”',
+            _ => c,
+        })
+        .collect()
+}
+
+fn collapse_repeated_punctuation(line: &str) -> String {
+    let mut ret = String::with_capacity(line.len());
+    let mut prev = None;
+    let mut run_length = 0;
+
+    for c in line.chars() {
+        if prev == Some(c) {
+            run_length += 1;
+        } else {
+            prev = Some(c);
+            run_length = 1;
+        }
+
+        let max_run_length = if SINGLE_PUNCTUATION.contains(&c) {
+            1
+        } else if REPEATABLE_PUNCTUATION.contains(&c) {
+            MAX_REPEATED_PUNCTUATION
+        } else {
+            usize::MAX
+        };
+        if run_length <= max_run_length {
+            ret.push(c);
+        }
+    }
+
+    ret
+}
+
+fn collapse_spaces(line: &str) -> String {
+    let mut ret = String::with_capacity(line.len());
+    let mut is_prev_space = false;
+
+    for c in line.chars() {
+        if c.is_whitespace() && c != '\n' && c != '\r' {
+            if !is_prev_space {
+                ret.push(' ');
+            }
+            is_prev_space = true;
+        } else {
+            ret.push(c);
+            is_prev_space = false;
+        }
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_options() -> NormalizeOptions {
+        NormalizeOptions {
+            nfkc: false,
+            full_width_to_half_width: true,
+            unify_quotes: true,
+            unify_ellipses: true,
+            collapse_repeated_punctuation: true,
+            collapse_spaces: true,
+        }
+    }
+
+    #[test]
+    fn test_noop() {
+        let text = "ＡＢＣ\"...\"　　";
+        assert_eq!(normalize(text, &NormalizeOptions::default()), text);
+    }
+
+    #[test]
+    fn test_full_width_to_half_width() {
+        let options = NormalizeOptions {
+            full_width_to_half_width: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            normalize("ＡＰＩ版本１２３，完。", &options),
+            "API版本123，完。"
+        );
+    }
+
+    #[test]
+    fn test_nfkc() {
+        let options = NormalizeOptions {
+            nfkc: true,
+            ..Default::default()
+        };
+        assert_eq!(normalize("ﬁｎｅ①", &options), "fine1");
+    }
+
+    #[test]
+    fn test_unify_quotes() {
+        let options = NormalizeOptions {
+            unify_quotes: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            normalize("他说\"你好\"，又说〝再见〞。\n\"Hi\"", &options),
+            "他说“你好”，又说“再见”。\n“Hi”"
+        );
+    }
+
+    #[test]
+    fn test_unify_ellipses() {
+        let options = NormalizeOptions {
+            unify_ellipses: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            normalize("等等。。。好吧…\nWait.... Ok⋯⋯", &options),
+            "等等……好吧……\nWait... Ok..."
+        );
+    }
+
+    #[test]
+    fn test_collapse_repeated_punctuation() {
+        let options = NormalizeOptions {
+            collapse_repeated_punctuation: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            normalize("什么！！！！！！，，真的？？", &options),
+            "什么！！！，真的？？"
+        );
+    }
+
+    #[test]
+    fn test_collapse_spaces() {
+        let options = NormalizeOptions {
+            collapse_spaces: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            normalize("第一章　　　开始\t \tStart\n", &options),
+            "第一章 开始 Start\n"
+        );
+    }
+
+    #[test]
+    fn test_all_options() {
+        assert_eq!(
+            normalize("　　好。。。\"ＯＫ\"！！！！", &all_options()),
+            " 好……“OK”！！！"
+        );
+    }
+}
@@ -0,0 +1,303 @@
+//! Crash-safe persistence of JSON files.
+//!
+//! A file is written to a temporary file next to it, synced to disk, and then
+//! renamed over the original, so the original is never left half-written.
+//! Before that, the previous versions are rotated into `{name}.bak1` (newest)
+//! to `{name}.bak{BACKUP_COUNT}` (oldest), which are read in that order if the
+//! file itself can't be parsed.
+//!
+//! If none of them can be parsed, [`recover_json_fields`] salvages what it can.
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+    ffi::OsString,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Number of previous versions kept for every file.
+pub const BACKUP_COUNT: usize = 3;
+
+fn path_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+pub fn backup_path(path: &Path, n: usize) -> PathBuf {
+    path_with_suffix(path, &format!(".bak{n}"))
+}
+
+fn rotate_backups(path: &Path) -> io::Result<()> {
+    for n in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            std::fs::rename(from, backup_path(path, n + 1))?;
+        }
+    }
+    if path.exists() {
+        std::fs::copy(path, backup_path(path, 1))?;
+    }
+    Ok(())
+}
+
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    write_atomically_with(path, contents, rotate_backups)
+}
+
+/// Like [`write_atomically`], but without keeping previous versions, for files
+/// that can be built again, where backups would only take up space.
+pub fn write_atomically_without_backups(path: &Path, contents: &[u8]) -> io::Result<()> {
+    write_atomically_with(path, contents, |_| Ok(()))
+}
+
+/// Write to a temporary file, then call `before_rename` and rename it over
+/// `path`.
+fn write_atomically_with(
+    path: &Path,
+    contents: &[u8],
+    before_rename: impl FnOnce(&Path) -> io::Result<()>,
+) -> io::Result<()> {
+    let temp_path = path_with_suffix(path, ".tmp");
+    {
+        let mut file = std::fs::File::create(&temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+
+    before_rename(path)?;
+    std::fs::rename(&temp_path, path)?;
+
+    // Make the rename itself durable. Directories can't be opened as files on
+    // Windows, where the rename is durable once it returns anyway.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        std::fs::File::open(dir)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+pub fn write_json_atomically<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let contents = serde_json::to_vec(value)?;
+    write_atomically(path, &contents)
+}
+
+pub fn has_backups(path: &Path) -> bool {
+    (1..=BACKUP_COUNT).any(|n| backup_path(path, n).exists())
+}
+
+/// Read the file alone, without trying its backups.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let contents = std::fs::read(path).ok()?;
+    serde_json::from_slice(&contents).ok()
+}
+
+/// Read the file, or its newest backup that can be parsed if the file is
+/// missing or corrupt. Returns `None` if none of them can be parsed.
+pub fn read_json_with_backups<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    read_json_with_backups_as(path, |value| serde_json::from_value(value).ok())
+}
+
+/// Like [`read_json_with_backups`], but with a custom conversion from JSON,
+/// e.g. to migrate older versions first.
+pub fn read_json_with_backups_as<T>(
+    path: &Path,
+    mut parse: impl FnMut(Value) -> Option<T>,
+) -> Option<T> {
+    std::iter::once(path.to_path_buf())
+        .chain((1..=BACKUP_COUNT).map(|n| backup_path(path, n)))
+        .find_map(|path| parse(read_json(&path)?))
+}
+
+/// Build a `T` from a JSON value that doesn't deserialize as a whole, keeping
+/// every field of it that is valid and taking the others from `default`.
+/// Returns the result and the dotted paths of the fields that were reset.
+///
+/// Each field is first tried as a whole, and only if it is invalid and both
+/// sides are objects, field by field.
+pub fn recover_json_fields<T: Serialize + DeserializeOwned>(
+    value: &Value,
+    default: &T,
+) -> (T, Vec<String>) {
+    let mut recovered = serde_json::to_value(default).expect("Error serializing default value");
+    let mut reset_fields = Vec::new();
+    let mut path = Vec::new();
+    recover_object::<T>(value, &mut recovered, &mut path, &mut reset_fields);
+
+    let recovered = serde_json::from_value(recovered).expect("Error deserializing recovered value");
+    (recovered, reset_fields)
+}
+
+/// Copy the valid fields of `value` into `recovered`, which must deserialize as
+/// `T` before and after.
+fn recover_object<T: DeserializeOwned>(
+    value: &Value,
+    recovered: &mut Value,
+    path: &mut Vec<String>,
+    reset_fields: &mut Vec<String>,
+) -> Option<()> {
+    let keys: Vec<String> = pointer_mut(recovered, path)?
+        .as_object()?
+        .keys()
+        .cloned()
+        .collect();
+
+    for key in keys {
+        path.push(key.clone());
+        match value.as_object().map(|value| value.get(&key)) {
+            None => reset_fields.push(path.join(".")),
+            // Missing fields take their defaults silently, as serde would.
+            Some(None) => {}
+            Some(Some(field)) => {
+                let original = pointer_mut(recovered, path)?.clone();
+                let is_original_object = original.is_object();
+                *pointer_mut(recovered, path)? = field.clone();
+                if serde_json::from_value::<T>(recovered.clone()).is_err() {
+                    *pointer_mut(recovered, path)? = original;
+                    if field.is_object() && is_original_object {
+                        recover_object::<T>(field, recovered, path, reset_fields);
+                    } else {
+                        reset_fields.push(path.join("."));
+                    }
+                }
+            }
+        }
+        path.pop();
+    }
+
+    Some(())
+}
+
+fn pointer_mut<'a>(value: &'a mut Value, path: &[String]) -> Option<&'a mut Value> {
+    path.iter()
+        .try_fold(value, |value, key| value.as_object_mut()?.get_mut(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    fn new_temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("sneaky-reader-test-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_write_rotates_backups() {
+        let dir = new_temp_dir("persist-rotate");
+        let path = dir.join("data.json");
+
+        for i in 0..(BACKUP_COUNT + 2) {
+            write_json_atomically(&path, &i).unwrap();
+        }
+
+        let read = |path: &Path| -> usize {
+            serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+        };
+        assert_eq!(read(&path), BACKUP_COUNT + 1);
+        for n in 1..=BACKUP_COUNT {
+            assert_eq!(read(&backup_path(&path, n)), BACKUP_COUNT + 1 - n);
+        }
+        assert!(!backup_path(&path, BACKUP_COUNT + 1).exists());
+        assert!(!path_with_suffix(&path, ".tmp").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_without_backups() {
+        let dir = new_temp_dir("persist-no-backups");
+        let path = dir.join("data.json");
+
+        write_atomically_without_backups(&path, b"1").unwrap();
+        write_atomically_without_backups(&path, b"2").unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"2");
+        assert!(!has_backups(&path));
+        assert!(!path_with_suffix(&path, ".tmp").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_falls_back_to_backups() {
+        let dir = new_temp_dir("persist-fallback");
+        let path = dir.join("data.json");
+
+        assert_eq!(read_json_with_backups::<usize>(&path), None);
+
+        write_json_atomically(&path, &1).unwrap();
+        write_json_atomically(&path, &2).unwrap();
+        write_json_atomically(&path, &3).unwrap();
+        assert_eq!(read_json_with_backups(&path), Some(3));
+
+        // Truncated by a crash.
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(read_json_with_backups(&path), Some(2));
+
+        std::fs::write(backup_path(&path, 1), "{").unwrap();
+        assert_eq!(read_json_with_backups(&path), Some(1));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Inner {
+        size: usize,
+        color: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Outer {
+        name: String,
+        inner: Inner,
+        tags: Vec<String>,
+    }
+
+    fn default_outer() -> Outer {
+        Outer {
+            name: String::from("default"),
+            inner: Inner {
+                size: 16,
+                color: String::from("#000"),
+            },
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_recover_json_fields() {
+        let value = serde_json::json!({
+            "name": "mine",
+            "inner": { "size": -1, "color": "#fff" },
+            "tags": "not a list",
+        });
+        let (recovered, reset_fields) = recover_json_fields(&value, &default_outer());
+        assert_eq!(
+            recovered,
+            Outer {
+                name: String::from("mine"),
+                inner: Inner {
+                    size: 16,
+                    color: String::from("#fff"),
+                },
+                tags: Vec::new(),
+            }
+        );
+        assert_eq!(reset_fields, ["inner.size", "tags"]);
+    }
+
+    #[test]
+    fn test_recover_json_fields_from_non_object() {
+        let (recovered, reset_fields) =
+            recover_json_fields(&serde_json::json!([1, 2]), &default_outer());
+        assert_eq!(recovered, default_outer());
+        assert_eq!(reset_fields, ["inner", "name", "tags"]);
+    }
+}
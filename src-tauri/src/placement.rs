@@ -0,0 +1,457 @@
+//! Keep the windows on screen as monitors are connected, disconnected or
+//! rearranged.
+
+use crate::config::{Anchor, ReaderAnchor};
+use tauri::{AppHandle, LogicalPosition, LogicalSize, Monitor};
+
+/// Margins closer than this to an edge, or to the middle, snap to it.
+const SNAP_DISTANCE: f64 = 16.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// The part of a monitor not covered by taskbars and docks, in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorkArea {
+    pub position: LogicalPosition<f64>,
+    pub size: LogicalSize<f64>,
+    pub taskbar_edge: Edge,
+}
+
+impl WorkArea {
+    fn of(monitor: &Monitor) -> Self {
+        let work_area = monitor.work_area();
+        let scale_factor = monitor.scale_factor();
+        let position = work_area.position.to_logical(scale_factor);
+        let size = work_area.size.to_logical(scale_factor);
+        let taskbar_edge = taskbar_edge(
+            monitor.position().to_logical(scale_factor),
+            monitor.size().to_logical(scale_factor),
+            position,
+            size,
+        );
+        Self {
+            position,
+            size,
+            taskbar_edge,
+        }
+    }
+}
+
+/// The edge of a monitor with the widest part left out of its work area, or
+/// the bottom one if nothing is.
+fn taskbar_edge(
+    monitor_position: LogicalPosition<f64>,
+    monitor_size: LogicalSize<f64>,
+    position: LogicalPosition<f64>,
+    size: LogicalSize<f64>,
+) -> Edge {
+    let gaps = [
+        (
+            Edge::Bottom,
+            monitor_position.y + monitor_size.height - position.y - size.height,
+        ),
+        (Edge::Top, position.y - monitor_position.y),
+        (Edge::Left, position.x - monitor_position.x),
+        (
+            Edge::Right,
+            monitor_position.x + monitor_size.width - position.x - size.width,
+        ),
+    ];
+    gaps.into_iter()
+        .filter(|(_, gap)| *gap > 0.0)
+        .fold(
+            None,
+            |widest: Option<(Edge, f64)>, (edge, gap)| match widest {
+                Some((_, widest_gap)) if widest_gap >= gap => widest,
+                _ => Some((edge, gap)),
+            },
+        )
+        .map_or(Edge::Bottom, |(edge, _)| edge)
+}
+
+/// The work areas of all monitors, the primary one first.
+pub fn work_areas(app: &AppHandle) -> tauri::Result<Vec<WorkArea>> {
+    let primary = app.primary_monitor()?.map(|monitor| WorkArea::of(&monitor));
+    let mut work_areas: Vec<WorkArea> =
+        app.available_monitors()?.iter().map(WorkArea::of).collect();
+    if let Some(i) = primary.and_then(|primary| work_areas.iter().position(|w| *w == primary)) {
+        work_areas[..=i].rotate_right(1);
+    }
+    Ok(work_areas)
+}
+
+/// Move a window, and shrink it if needed, so that it is entirely within the
+/// work area it overlaps the most, or the primary one if it overlaps none.
+/// Without any work area, as can happen while monitors are switched, it is
+/// left as it is.
+pub fn clamp_to_work_areas(
+    position: LogicalPosition<f64>,
+    size: LogicalSize<f64>,
+    work_areas: &[WorkArea],
+) -> (LogicalPosition<f64>, LogicalSize<f64>) {
+    let Some(work_area) = work_area_of(position, size, work_areas) else {
+        return (position, size);
+    };
+
+    let size = LogicalSize::new(
+        size.width.min(work_area.size.width),
+        size.height.min(work_area.size.height),
+    );
+    let position = LogicalPosition::new(
+        position.x.clamp(
+            work_area.position.x,
+            work_area.position.x + work_area.size.width - size.width,
+        ),
+        position.y.clamp(
+            work_area.position.y,
+            work_area.position.y + work_area.size.height - size.height,
+        ),
+    );
+    (position, size)
+}
+
+/// The work area a window overlaps the most, or the primary one if it
+/// overlaps none.
+pub fn work_area_of(
+    position: LogicalPosition<f64>,
+    size: LogicalSize<f64>,
+    work_areas: &[WorkArea],
+) -> Option<&WorkArea> {
+    work_areas
+        .iter()
+        .map(|work_area| (work_area, overlap(position, size, work_area)))
+        .filter(|(_, overlap)| *overlap > 0.0)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(work_area, _)| work_area)
+        .or(work_areas.first())
+}
+
+/// Keep the reader within the work areas, and where it is anchored if it is.
+pub fn place_reader(
+    position: LogicalPosition<f64>,
+    size: LogicalSize<f64>,
+    reader_anchor: &ReaderAnchor,
+    work_areas: &[WorkArea],
+) -> (LogicalPosition<f64>, LogicalSize<f64>) {
+    let (position, size) = clamp_to_work_areas(position, size, work_areas);
+    let anchored_position = work_area_of(position, size, work_areas)
+        .and_then(|work_area| anchored_position(reader_anchor, size, work_area));
+    (anchored_position.unwrap_or(position), size)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Start,
+    Center,
+    End,
+}
+
+/// How an anchor aligns the reader horizontally and vertically, and whether
+/// `margin_x` is then the vertical margin and `margin_y` the horizontal one.
+fn alignments(anchor: Anchor, taskbar_edge: Edge) -> Option<(Align, Align, bool)> {
+    use Align::*;
+    let alignments = match anchor {
+        Anchor::None => return None,
+        Anchor::TopLeft => (Start, Start, false),
+        Anchor::Top => (Center, Start, false),
+        Anchor::TopRight => (End, Start, false),
+        Anchor::Left => (Start, Center, false),
+        Anchor::Center => (Center, Center, false),
+        Anchor::Right => (End, Center, false),
+        Anchor::BottomLeft => (Start, End, false),
+        Anchor::Bottom => (Center, End, false),
+        Anchor::BottomRight => (End, End, false),
+        Anchor::AboveTaskbar => match taskbar_edge {
+            Edge::Bottom => (Center, End, false),
+            Edge::Top => (Center, Start, false),
+            Edge::Left => (Start, Center, true),
+            Edge::Right => (End, Center, true),
+        },
+    };
+    Some(alignments)
+}
+
+fn align(align: Align, margin: f64, length: f64, area_start: f64, area_length: f64) -> f64 {
+    match align {
+        Align::Start => area_start + margin,
+        Align::Center => area_start + (area_length - length) / 2.0 + margin,
+        Align::End => area_start + area_length - length - margin,
+    }
+}
+
+fn margin_of(align: Align, start: f64, length: f64, area_start: f64, area_length: f64) -> f64 {
+    match align {
+        Align::Start => start - area_start,
+        Align::Center => start - area_start - (area_length - length) / 2.0,
+        Align::End => area_start + area_length - length - start,
+    }
+}
+
+/// Where the reader is in a work area, if it is anchored.
+pub fn anchored_position(
+    reader_anchor: &ReaderAnchor,
+    size: LogicalSize<f64>,
+    work_area: &WorkArea,
+) -> Option<LogicalPosition<f64>> {
+    let (align_x, align_y, is_swapped) = alignments(reader_anchor.anchor, work_area.taskbar_edge)?;
+    let (margin_x, margin_y) = if is_swapped {
+        (reader_anchor.margin_y, reader_anchor.margin_x)
+    } else {
+        (reader_anchor.margin_x, reader_anchor.margin_y)
+    };
+    Some(LogicalPosition::new(
+        align(
+            align_x,
+            margin_x,
+            size.width,
+            work_area.position.x,
+            work_area.size.width,
+        ),
+        align(
+            align_y,
+            margin_y,
+            size.height,
+            work_area.position.y,
+            work_area.size.height,
+        ),
+    ))
+}
+
+/// The anchor with the margins that keep the reader where it was moved to,
+/// snapped to the edges, or the middle, it is close to.
+pub fn anchor_at(
+    anchor: Anchor,
+    position: LogicalPosition<f64>,
+    size: LogicalSize<f64>,
+    work_area: &WorkArea,
+) -> ReaderAnchor {
+    let Some((align_x, align_y, is_swapped)) = alignments(anchor, work_area.taskbar_edge) else {
+        return ReaderAnchor {
+            anchor,
+            ..Default::default()
+        };
+    };
+    let snap = |margin: f64| {
+        if margin.abs() < SNAP_DISTANCE {
+            0.0
+        } else {
+            margin
+        }
+    };
+    let margin_x = snap(margin_of(
+        align_x,
+        position.x,
+        size.width,
+        work_area.position.x,
+        work_area.size.width,
+    ));
+    let margin_y = snap(margin_of(
+        align_y,
+        position.y,
+        size.height,
+        work_area.position.y,
+        work_area.size.height,
+    ));
+    let (margin_x, margin_y) = if is_swapped {
+        (margin_y, margin_x)
+    } else {
+        (margin_x, margin_y)
+    };
+    ReaderAnchor {
+        anchor,
+        margin_x,
+        margin_y,
+    }
+}
+
+/// Where a window of `size` is centered in a work area, shrunk if needed.
+pub fn center_in(
+    size: LogicalSize<f64>,
+    work_area: &WorkArea,
+) -> (LogicalPosition<f64>, LogicalSize<f64>) {
+    let size = LogicalSize::new(
+        size.width.min(work_area.size.width),
+        size.height.min(work_area.size.height),
+    );
+    let position = LogicalPosition::new(
+        work_area.position.x + (work_area.size.width - size.width) / 2.0,
+        work_area.position.y + (work_area.size.height - size.height) / 2.0,
+    );
+    (position, size)
+}
+
+fn overlap(position: LogicalPosition<f64>, size: LogicalSize<f64>, work_area: &WorkArea) -> f64 {
+    let width = (position.x + size.width).min(work_area.position.x + work_area.size.width)
+        - position.x.max(work_area.position.x);
+    let height = (position.y + size.height).min(work_area.position.y + work_area.size.height)
+        - position.y.max(work_area.position.y);
+    width.max(0.0) * height.max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn work_area(x: f64, y: f64, width: f64, height: f64) -> WorkArea {
+        WorkArea {
+            position: LogicalPosition::new(x, y),
+            size: LogicalSize::new(width, height),
+            taskbar_edge: Edge::Bottom,
+        }
+    }
+
+    fn reader_anchor(anchor: Anchor, margin_x: f64, margin_y: f64) -> ReaderAnchor {
+        ReaderAnchor {
+            anchor,
+            margin_x,
+            margin_y,
+        }
+    }
+
+    #[test]
+    fn test_clamp_keeps_visible_window() {
+        let work_areas = [work_area(0.0, 0.0, 1920.0, 1040.0)];
+        let position = LogicalPosition::new(100.0, 200.0);
+        let size = LogicalSize::new(300.0, 400.0);
+        assert_eq!(
+            clamp_to_work_areas(position, size, &work_areas),
+            (position, size)
+        );
+    }
+
+    #[test]
+    fn test_clamp_moves_off_screen_window_to_primary() {
+        // The reader was on a monitor right of the laptop, which is gone.
+        let work_areas = [work_area(0.0, 0.0, 1280.0, 760.0)];
+        let (position, size) = clamp_to_work_areas(
+            LogicalPosition::new(2500.0, 300.0),
+            LogicalSize::new(300.0, 400.0),
+            &work_areas,
+        );
+        assert_eq!(position, LogicalPosition::new(980.0, 300.0));
+        assert_eq!(size, LogicalSize::new(300.0, 400.0));
+    }
+
+    #[test]
+    fn test_clamp_uses_most_overlapping_work_area() {
+        let work_areas = [
+            work_area(0.0, 0.0, 1920.0, 1040.0),
+            work_area(1920.0, 0.0, 1280.0, 1000.0),
+        ];
+        // Mostly on the second monitor, and partly below its work area.
+        let (position, size) = clamp_to_work_areas(
+            LogicalPosition::new(1800.0, 800.0),
+            LogicalSize::new(400.0, 1200.0),
+            &work_areas,
+        );
+        assert_eq!(position, LogicalPosition::new(1920.0, 0.0));
+        assert_eq!(size, LogicalSize::new(400.0, 1000.0));
+    }
+
+    #[test]
+    fn test_clamp_without_work_areas() {
+        let position = LogicalPosition::new(-5000.0, 0.0);
+        let size = LogicalSize::new(300.0, 400.0);
+        assert_eq!(clamp_to_work_areas(position, size, &[]), (position, size));
+    }
+
+    #[test]
+    fn test_center_in() {
+        let (position, size) = center_in(
+            LogicalSize::new(300.0, 2000.0),
+            &work_area(100.0, 0.0, 1000.0, 800.0),
+        );
+        assert_eq!(position, LogicalPosition::new(450.0, 0.0));
+        assert_eq!(size, LogicalSize::new(300.0, 800.0));
+    }
+
+    #[test]
+    fn test_taskbar_edge() {
+        let monitor_position = LogicalPosition::new(0.0, 0.0);
+        let monitor_size = LogicalSize::new(1920.0, 1080.0);
+        let edge = |x: f64, y: f64, width: f64, height: f64| {
+            taskbar_edge(
+                monitor_position,
+                monitor_size,
+                LogicalPosition::new(x, y),
+                LogicalSize::new(width, height),
+            )
+        };
+        assert_eq!(edge(0.0, 0.0, 1920.0, 1040.0), Edge::Bottom);
+        // The menu bar of macOS is thinner than a dock on the left.
+        assert_eq!(edge(70.0, 25.0, 1850.0, 1055.0), Edge::Left);
+        assert_eq!(edge(0.0, 0.0, 1850.0, 1080.0), Edge::Right);
+        assert_eq!(edge(0.0, 40.0, 1920.0, 1040.0), Edge::Top);
+        assert_eq!(edge(0.0, 0.0, 1920.0, 1080.0), Edge::Bottom);
+    }
+
+    #[test]
+    fn test_anchored_position() {
+        let area = work_area(0.0, 0.0, 1920.0, 1040.0);
+        let size = LogicalSize::new(300.0, 400.0);
+        let position = |anchor| anchored_position(&anchor, size, &area);
+
+        assert_eq!(position(reader_anchor(Anchor::None, 10.0, 10.0)), None);
+        assert_eq!(
+            position(reader_anchor(Anchor::TopLeft, 10.0, 20.0)),
+            Some(LogicalPosition::new(10.0, 20.0))
+        );
+        assert_eq!(
+            position(reader_anchor(Anchor::BottomRight, 10.0, 20.0)),
+            Some(LogicalPosition::new(1610.0, 620.0))
+        );
+        assert_eq!(
+            position(reader_anchor(Anchor::Center, -10.0, 0.0)),
+            Some(LogicalPosition::new(800.0, 320.0))
+        );
+        assert_eq!(
+            position(reader_anchor(Anchor::AboveTaskbar, 0.0, 5.0)),
+            Some(LogicalPosition::new(810.0, 635.0))
+        );
+
+        let area = WorkArea {
+            taskbar_edge: Edge::Left,
+            ..work_area(60.0, 0.0, 1860.0, 1080.0)
+        };
+        assert_eq!(
+            anchored_position(&reader_anchor(Anchor::AboveTaskbar, 0.0, 5.0), size, &area),
+            Some(LogicalPosition::new(65.0, 340.0))
+        );
+    }
+
+    #[test]
+    fn test_anchor_at_round_trips_and_snaps() {
+        let size = LogicalSize::new(300.0, 400.0);
+        for taskbar_edge in [Edge::Bottom, Edge::Left] {
+            let area = WorkArea {
+                taskbar_edge,
+                ..work_area(0.0, 0.0, 1920.0, 1040.0)
+            };
+            for anchor in [Anchor::TopRight, Anchor::Center, Anchor::AboveTaskbar] {
+                let position = LogicalPosition::new(700.0, 123.0);
+                let reader_anchor = anchor_at(anchor, position, size, &area);
+                assert_eq!(
+                    anchored_position(&reader_anchor, size, &area),
+                    Some(position)
+                );
+            }
+        }
+
+        let area = work_area(0.0, 0.0, 1920.0, 1040.0);
+        let reader_anchor = anchor_at(
+            Anchor::BottomRight,
+            LogicalPosition::new(1580.0, 630.0),
+            size,
+            &area,
+        );
+        assert_eq!(reader_anchor.margin_x, 40.0);
+        // 10 px from the bottom.
+        assert_eq!(reader_anchor.margin_y, 0.0);
+    }
+}
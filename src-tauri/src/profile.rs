@@ -0,0 +1,114 @@
+//! Appearance profiles, which are named copies of the appearance switched from
+//! the tray menu, with a hotkey, or automatically when the monitors change.
+
+use crate::config::{Appearance, AppearanceProfile};
+use tauri::{AppHandle, Monitor};
+
+/// A description of the connected monitors, their resolutions and how they
+/// are arranged, which is the same for the same setup.
+pub fn monitor_layout(app: &AppHandle) -> tauri::Result<String> {
+    Ok(layout_of(&app.available_monitors()?))
+}
+
+fn layout_of(monitors: &[Monitor]) -> String {
+    let mut monitors: Vec<String> = monitors
+        .iter()
+        .map(|monitor| {
+            let size = monitor.size();
+            let position = monitor.position();
+            format!(
+                "{} {}x{}+{}+{}",
+                monitor.name().map_or("", String::as_str),
+                size.width,
+                size.height,
+                position.x,
+                position.y
+            )
+        })
+        .collect();
+    monitors.sort();
+    monitors.join(", ")
+}
+
+/// The profile to apply when the monitors are arranged in `layout`.
+pub fn profile_for_monitor_layout<'a>(
+    profiles: &'a [AppearanceProfile],
+    layout: &str,
+) -> Option<&'a AppearanceProfile> {
+    profiles
+        .iter()
+        .find(|profile| profile.monitor_layout.as_deref() == Some(layout))
+}
+
+/// The profile after the active one, or the first one if none is active.
+pub fn next_profile<'a>(
+    profiles: &'a [AppearanceProfile],
+    active_profile: Option<&str>,
+) -> Option<&'a AppearanceProfile> {
+    let next = active_profile
+        .and_then(|active| profiles.iter().position(|profile| profile.name == active))
+        .map_or(0, |i| i + 1);
+    profiles.get(next % profiles.len().max(1))
+}
+
+/// The appearance of a profile, except where the settings window is, as that
+/// is not what a profile is about.
+pub fn appearance_of(profile: &AppearanceProfile, current: &Appearance) -> Appearance {
+    Appearance {
+        position_settings: current.position_settings,
+        size_settings: current.size_settings,
+        ..profile.appearance.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tauri::LogicalPosition;
+
+    fn profile(name: &str, monitor_layout: Option<&str>) -> AppearanceProfile {
+        AppearanceProfile {
+            name: String::from(name),
+            monitor_layout: monitor_layout.map(String::from),
+            appearance: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_profile_for_monitor_layout() {
+        let profiles = [
+            profile("laptop", Some("eDP-1 1920x1080+0+0")),
+            profile("meeting", None),
+            profile("docked", Some("DP-1 2560x1440+0+0, eDP-1 1920x1080+2560+0")),
+        ];
+        let found =
+            profile_for_monitor_layout(&profiles, "DP-1 2560x1440+0+0, eDP-1 1920x1080+2560+0");
+        assert_eq!(found.unwrap().name, "docked");
+        assert!(profile_for_monitor_layout(&profiles, "HDMI-1 1280x720+0+0").is_none());
+    }
+
+    #[test]
+    fn test_next_profile() {
+        let profiles = [profile("a", None), profile("b", None)];
+        assert_eq!(next_profile(&profiles, None).unwrap().name, "a");
+        assert_eq!(next_profile(&profiles, Some("a")).unwrap().name, "b");
+        assert_eq!(next_profile(&profiles, Some("b")).unwrap().name, "a");
+        assert_eq!(next_profile(&profiles, Some("removed")).unwrap().name, "a");
+        assert!(next_profile(&[], Some("a")).is_none());
+    }
+
+    #[test]
+    fn test_appearance_of_keeps_settings_window() {
+        let mut profile = profile("a", None);
+        profile.appearance.text_size = 30;
+        profile.appearance.position_settings = LogicalPosition::new(1.0, 2.0);
+        let current = Appearance {
+            position_settings: LogicalPosition::new(500.0, 600.0),
+            ..Default::default()
+        };
+
+        let appearance = appearance_of(&profile, &current);
+        assert_eq!(appearance.text_size, 30);
+        assert_eq!(appearance.position_settings, current.position_settings);
+    }
+}
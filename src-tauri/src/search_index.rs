@@ -0,0 +1,311 @@
+//! A full-text index of the library, to find the books a query is in without
+//! reading all of them.
+//!
+//! Chinese and Japanese are written without spaces, so their text is indexed
+//! as single characters and overlapping pairs of characters, and a query is
+//! looked up by its own pairs. Other scripts are indexed as lowercase words.
+//! Fullwidth forms are folded to halfwidth ones first.
+
+use crate::{library, persist, DATA_ROOT_DIR};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, io, path::PathBuf};
+
+pub const SEARCH_INDEX_FILENAME: &str = "search_index.json";
+
+/// Most books returned by one search of the library.
+pub const MAX_LIBRARY_SEARCH_HITS: usize = 20;
+
+/// Most matches returned for each book found.
+pub const MATCHES_PER_HIT: usize = 3;
+
+/// BM25 parameters, as commonly used.
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchIndex {
+    /// By book ID.
+    books: HashMap<String, IndexedBook>,
+    /// Given to the next book indexed. Postings refer to books by number, as
+    /// that is much shorter than an ID.
+    next_number: u32,
+    /// Term to the numbers of the books it is in, with how many times.
+    postings: HashMap<String, Vec<(u32, u32)>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedBook {
+    number: u32,
+    term_count: u64,
+}
+
+/// A book found by a search of the library.
+#[derive(Debug, Clone, Serialize)]
+pub struct LibrarySearchHit {
+    pub book_id: String,
+    pub title: String,
+    pub score: f64,
+    /// The first `MATCHES_PER_HIT` places the query is in the book.
+    pub matches: Vec<library::SearchMatch>,
+    /// Counted up to `library::MAX_SEARCH_MATCHES`.
+    pub match_count: usize,
+    pub is_truncated: bool,
+}
+
+impl SearchIndex {
+    pub fn contains(&self, id: &str) -> bool {
+        self.books.contains_key(id)
+    }
+
+    pub fn book_ids(&self) -> impl Iterator<Item = &String> {
+        self.books.keys()
+    }
+
+    /// Index a book from the counts of its terms, replacing what was indexed
+    /// for it before.
+    pub fn insert_book(&mut self, id: &str, term_counts: HashMap<String, u32>) {
+        self.remove_book(id);
+        let number = self.next_number;
+        self.next_number += 1;
+        let term_count = term_counts.values().map(|&count| count as u64).sum();
+        for (term, count) in term_counts {
+            self.postings.entry(term).or_default().push((number, count));
+        }
+        self.books
+            .insert(id.to_string(), IndexedBook { number, term_count });
+    }
+
+    /// Returns whether the book was indexed.
+    pub fn remove_book(&mut self, id: &str) -> bool {
+        let Some(book) = self.books.remove(id) else {
+            return false;
+        };
+        self.postings.retain(|_, postings| {
+            postings.retain(|&(number, _)| number != book.number);
+            !postings.is_empty()
+        });
+        true
+    }
+
+    /// The books that have every term of `query`, best first, with their
+    /// BM25 scores. They may still not have the query as a whole.
+    pub fn search(&self, query: &str) -> Vec<(String, f64)> {
+        let mut query_terms = query_terms(query);
+        query_terms.sort();
+        query_terms.dedup();
+        if query_terms.is_empty() || self.books.is_empty() {
+            return Vec::new();
+        }
+
+        let book_count = self.books.len() as f64;
+        let average_term_count = self
+            .books
+            .values()
+            .map(|book| book.term_count as f64)
+            .sum::<f64>()
+            / book_count;
+        let term_counts: HashMap<u32, u64> = self
+            .books
+            .values()
+            .map(|book| (book.number, book.term_count))
+            .collect();
+
+        let mut scores: HashMap<u32, (usize, f64)> = HashMap::new();
+        for term in &query_terms {
+            let Some(postings) = self.postings.get(term) else {
+                return Vec::new();
+            };
+            let document_frequency = postings.len() as f64;
+            let idf =
+                (1.0 + (book_count - document_frequency + 0.5) / (document_frequency + 0.5)).ln();
+            for &(number, count) in postings {
+                let count = count as f64;
+                let length = term_counts.get(&number).copied().unwrap_or_default() as f64;
+                let normalized_length = 1.0 - B + B * length / average_term_count.max(1.0);
+                let score = idf * count * (K1 + 1.0) / (count + K1 * normalized_length);
+                let entry = scores.entry(number).or_default();
+                entry.0 += 1;
+                entry.1 += score;
+            }
+        }
+
+        let ids: HashMap<u32, &String> = self
+            .books
+            .iter()
+            .map(|(id, book)| (book.number, id))
+            .collect();
+        let mut hits: Vec<(String, f64)> = scores
+            .into_iter()
+            .filter(|(_, (matched, _))| *matched == query_terms.len())
+            .filter_map(|(number, (_, score))| Some(((*ids.get(&number)?).clone(), score)))
+            .collect();
+        hits.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        hits
+    }
+}
+
+/// Chinese characters and kana.
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'
+        | '\u{31F0}'..='\u{31FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF66}'..='\u{FF9F}'
+        | '\u{20000}'..='\u{2FA1F}'
+    )
+}
+
+/// The halfwidth form of a fullwidth ASCII character.
+fn fold_width(c: char) -> char {
+    match c {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFF01 + 0x21).unwrap(),
+        _ => c,
+    }
+}
+
+enum Token {
+    Word(String),
+    /// Consecutive CJK characters.
+    Cjk(Vec<char>),
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut run = Vec::new();
+    for c in text.chars().map(fold_width) {
+        if is_cjk(c) {
+            if !word.is_empty() {
+                tokens.push(Token::Word(std::mem::take(&mut word)));
+            }
+            run.push(c);
+            continue;
+        }
+        if !run.is_empty() {
+            tokens.push(Token::Cjk(std::mem::take(&mut run)));
+        }
+        if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+        } else if !word.is_empty() {
+            tokens.push(Token::Word(std::mem::take(&mut word)));
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(Token::Word(word));
+    }
+    if !run.is_empty() {
+        tokens.push(Token::Cjk(run));
+    }
+    tokens
+}
+
+/// How many times each term is in `text`.
+pub fn term_counts(text: &str) -> HashMap<String, u32> {
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for token in tokenize(text) {
+        match token {
+            Token::Word(word) => *counts.entry(word).or_default() += 1,
+            Token::Cjk(run) => {
+                for c in &run {
+                    *counts.entry(c.to_string()).or_default() += 1;
+                }
+                for pair in run.windows(2) {
+                    *counts.entry(pair.iter().collect()).or_default() += 1;
+                }
+            }
+        }
+    }
+    counts
+}
+
+/// The terms a query is looked up by: its words, and the pairs of CJK
+/// characters in it, or a character itself if it has no pair.
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for token in tokenize(query) {
+        match token {
+            Token::Word(word) => terms.push(word),
+            Token::Cjk(run) if run.len() == 1 => terms.push(run[0].to_string()),
+            Token::Cjk(run) => terms.extend(run.windows(2).map(|pair| pair.iter().collect())),
+        }
+    }
+    terms
+}
+
+fn search_index_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap()
+        .join(DATA_ROOT_DIR)
+        .join(SEARCH_INDEX_FILENAME)
+}
+
+/// An unreadable index is built again from the library, so its backups are
+/// not tried.
+pub fn read_search_index_from_disk() -> SearchIndex {
+    persist::read_json(&search_index_path()).unwrap_or_default()
+}
+
+/// Without backups, as the index may be large.
+pub fn write_search_index_to_disk(search_index: &SearchIndex) -> io::Result<()> {
+    let contents = serde_json::to_vec(search_index)?;
+    persist::write_atomically_without_backups(&search_index_path(), &contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut terms: Vec<String>) -> Vec<String> {
+        terms.sort();
+        terms
+    }
+
+    #[test]
+    fn test_term_counts() {
+        let counts = term_counts("他推开门。Hello, ＷＯＲＬＤ! hello");
+        assert_eq!(counts["他推"], 1);
+        assert_eq!(counts["门"], 1);
+        assert_eq!(counts["hello"], 2);
+        assert_eq!(counts["world"], 1);
+        assert!(!counts.contains_key("门h"));
+        assert_eq!(counts.len(), 4 + 3 + 2);
+    }
+
+    #[test]
+    fn test_query_terms() {
+        assert_eq!(sorted(query_terms("推开门")), ["开门", "推开"]);
+        assert_eq!(sorted(query_terms("门 Door")), ["door", "门"]);
+        assert_eq!(sorted(query_terms("ひらがな")), ["がな", "ひら", "らが"]);
+        assert!(query_terms("，。!").is_empty());
+    }
+
+    #[test]
+    fn test_search() {
+        let mut index = SearchIndex::default();
+        index.insert_book("a", term_counts("他推开门，外面在下雨。雨很大。"));
+        index.insert_book("b", term_counts("雨停了。他推开窗。"));
+        index.insert_book("c", term_counts("The rain stopped."));
+
+        let ids = |hits: Vec<(String, f64)>| -> Vec<String> {
+            hits.into_iter().map(|(id, _)| id).collect()
+        };
+        assert_eq!(ids(index.search("雨")), ["a", "b"]);
+        assert_eq!(ids(index.search("推开")), ["b", "a"]);
+        assert_eq!(ids(index.search("推开门")), ["a"]);
+        assert_eq!(ids(index.search("RAIN")), ["c"]);
+        assert!(index.search("雪").is_empty());
+        assert!(index.search("").is_empty());
+
+        assert!(index.remove_book("a"));
+        assert!(!index.remove_book("a"));
+        assert_eq!(ids(index.search("雨")), ["b"]);
+        assert!(!index.postings.contains_key("开门"));
+
+        index.insert_book("b", term_counts("晴天"));
+        assert!(index.search("雨").is_empty());
+        assert_eq!(ids(index.search("晴")), ["b"]);
+    }
+}
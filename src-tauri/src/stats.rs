@@ -0,0 +1,712 @@
+//! Reading statistics, from sessions that last while the reader is shown.
+//!
+//! Characters read are counted from how far the progress of a book moves
+//! forward, in UTF-16 code units like the progress itself, which are single
+//! characters for all but rare ones.
+
+use crate::{chapters, library::Book, persist, DATA_ROOT_DIR};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub const SESSIONS_FILENAME: &str = "reading_sessions.json";
+
+/// A session stops counting this long after the last page turn, for when the
+/// reader is left shown.
+const IDLE_SECONDS: u64 = 5 * 60;
+
+/// More than a page holds even in a large reader. A move forward farther
+/// than this at once was not read page by page, so it isn't counted.
+const MAX_PAGE_CHARACTERS: usize = 5000;
+
+/// How many days of sessions the reading speed is measured over.
+const SPEED_DAYS: u64 = 30;
+
+/// How many days `ReadingStats::days` covers, today included.
+const RECENT_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Session {
+    pub book_id: String,
+    /// In seconds since the Unix epoch.
+    pub start: u64,
+    pub end: u64,
+    pub characters: usize,
+}
+
+impl Session {
+    pub fn seconds(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CurrentSession {
+    session: Session,
+    last_activity: u64,
+    /// How the session was last saved while it went on, if it was.
+    saved: Option<Session>,
+}
+
+/// Records sessions as the reader is shown and hidden, and pages are turned.
+/// Times are in seconds since the Unix epoch.
+#[derive(Debug, Default)]
+pub struct Recorder {
+    sessions: Vec<Session>,
+    current: Option<CurrentSession>,
+}
+
+impl Recorder {
+    pub fn new(sessions: Vec<Session>) -> Self {
+        Self {
+            sessions,
+            current: None,
+        }
+    }
+
+    pub fn sessions(&self) -> &[Session] {
+        &self.sessions
+    }
+
+    pub fn show(&mut self, book_id: &str, now: u64) {
+        self.hide(now);
+        self.current = Some(CurrentSession {
+            session: Session {
+                book_id: book_id.to_string(),
+                start: now,
+                end: now,
+                characters: 0,
+            },
+            last_activity: now,
+            saved: None,
+        });
+    }
+
+    /// Count how far a book was read, if the reader is shown. Moving back
+    /// doesn't count, nor does it take away from what was read, and neither
+    /// does moving farther than a page. Jumps and going back set the progress
+    /// without reporting it here, and the reader reports only the pages turned
+    /// from there.
+    pub fn record_progress(&mut self, book_id: &str, from: usize, to: usize, now: u64) {
+        let Some(current) = &self.current else {
+            return;
+        };
+        if current.session.book_id != book_id {
+            self.show(book_id, now);
+        }
+        if let Some(current) = &mut self.current {
+            let characters = to.saturating_sub(from);
+            if characters <= MAX_PAGE_CHARACTERS {
+                current.session.characters += characters;
+            }
+            current.last_activity = now;
+        }
+    }
+
+    /// End the current session, if any. Returns whether one was recorded.
+    pub fn hide(&mut self, now: u64) -> bool {
+        if let Some(current) = &mut self.current {
+            current.session.end = current_end(current, now);
+        }
+        self.end_current()
+    }
+
+    /// The recorded sessions followed by the current one as far as it has
+    /// gone, if that changed since this was last called, to be saved in case
+    /// the app quits before the reader is hidden. The current session is
+    /// still recorded as one when it ends.
+    pub fn unsaved_sessions(&mut self, now: u64) -> Option<Vec<Session>> {
+        let current = self.current.as_mut()?;
+        let mut session = current.session.clone();
+        session.end = current_end(current, now);
+        if (session.seconds() == 0 && session.characters == 0)
+            || current.saved.as_ref() == Some(&session)
+        {
+            return None;
+        }
+        current.saved = Some(session.clone());
+
+        let mut sessions = self.sessions.clone();
+        sessions.push(session);
+        Some(sessions)
+    }
+
+    fn end_current(&mut self) -> bool {
+        let Some(CurrentSession { session, .. }) = self.current.take() else {
+            return false;
+        };
+        if session.seconds() == 0 && session.characters == 0 {
+            return false;
+        }
+        self.sessions.push(session);
+        true
+    }
+}
+
+fn current_end(current: &CurrentSession, now: u64) -> u64 {
+    now.min(current.last_activity + IDLE_SECONDS)
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Default, PartialEq)]
+pub struct ReadingTotals {
+    pub seconds: u64,
+    pub characters: usize,
+}
+
+impl ReadingTotals {
+    fn add(&mut self, session: &Session) {
+        self.seconds += session.seconds();
+        self.characters += session.characters;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DailyTotals {
+    /// `YYYY-MM-DD`, in local time.
+    pub date: String,
+    #[serde(flatten)]
+    pub totals: ReadingTotals,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ReadingStats {
+    pub today: ReadingTotals,
+    /// Since Monday.
+    pub this_week: ReadingTotals,
+    /// The last days, oldest first.
+    pub days: Vec<DailyTotals>,
+    /// In characters per minute, if enough was read lately to tell.
+    pub speed: Option<f64>,
+}
+
+/// The local date a session is counted on, which is when it started.
+pub fn session_date<Tz: TimeZone>(session: &Session, tz: &Tz) -> NaiveDate {
+    date_of(session.start, tz)
+}
+
+pub fn date_of<Tz: TimeZone>(time: u64, tz: &Tz) -> NaiveDate {
+    let time = DateTime::from_timestamp(time as i64, 0).unwrap_or_default();
+    time.with_timezone(tz).date_naive()
+}
+
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date.week(Weekday::Mon).first_day()
+}
+
+/// What was read on each date from `first` to `last`, both included.
+pub fn totals_by_date<Tz: TimeZone>(
+    sessions: &[Session],
+    tz: &Tz,
+    first: NaiveDate,
+    last: NaiveDate,
+) -> Vec<(NaiveDate, ReadingTotals)> {
+    let mut totals: Vec<(NaiveDate, ReadingTotals)> = first
+        .iter_days()
+        .take_while(|date| *date <= last)
+        .map(|date| (date, ReadingTotals::default()))
+        .collect();
+    for session in sessions {
+        let date = session_date(session, tz);
+        if let Some(i) = date
+            .signed_duration_since(first)
+            .num_days()
+            .try_into()
+            .ok()
+            .filter(|&i: &usize| i < totals.len())
+        {
+            totals[i].1.add(session);
+        }
+    }
+    totals
+}
+
+/// Characters per minute over the sessions since `since`, counting only those
+/// in which pages were turned.
+pub fn reading_speed(sessions: &[Session], since: u64) -> Option<f64> {
+    let mut totals = ReadingTotals::default();
+    for session in sessions
+        .iter()
+        .filter(|session| session.start >= since && session.characters > 0)
+    {
+        totals.add(session);
+    }
+    // Less than a minute of reading tells little.
+    (totals.seconds >= 60).then(|| totals.characters as f64 * 60.0 / totals.seconds as f64)
+}
+
+/// The reading speed over the last days, as used for estimates.
+pub fn recent_reading_speed(sessions: &[Session], now: u64) -> Option<f64> {
+    reading_speed(sessions, now.saturating_sub(SPEED_DAYS * 24 * 60 * 60))
+}
+
+pub fn reading_stats<Tz: TimeZone>(sessions: &[Session], tz: &Tz, now: u64) -> ReadingStats {
+    let today = date_of(now, tz);
+    let first = (today - Duration::days(RECENT_DAYS - 1)).min(week_start(today));
+    let totals = totals_by_date(sessions, tz, first, today);
+
+    let sum = |since: NaiveDate| {
+        let mut sum = ReadingTotals::default();
+        for (_, totals) in totals.iter().filter(|(date, _)| *date >= since) {
+            sum.seconds += totals.seconds;
+            sum.characters += totals.characters;
+        }
+        sum
+    };
+    let days = totals
+        .iter()
+        .filter(|(date, _)| *date > today - Duration::days(RECENT_DAYS))
+        .map(|(date, totals)| DailyTotals {
+            date: date.format("%Y-%m-%d").to_string(),
+            totals: *totals,
+        })
+        .collect();
+
+    ReadingStats {
+        today: sum(today),
+        this_week: sum(week_start(today)),
+        days,
+        speed: recent_reading_speed(sessions, now),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum GoalUnit {
+    #[default]
+    Minutes,
+    Characters,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Goal {
+    pub unit: GoalUnit,
+    pub amount: u64,
+}
+
+impl Goal {
+    /// How much of the goal was read, in its unit.
+    pub fn done(&self, totals: &ReadingTotals) -> u64 {
+        match self.unit {
+            GoalUnit::Minutes => totals.seconds / 60,
+            GoalUnit::Characters => totals.characters as u64,
+        }
+    }
+
+    pub fn is_met(&self, totals: &ReadingTotals) -> bool {
+        self.done(totals) >= self.amount
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ReadingGoals {
+    pub daily: Option<Goal>,
+    /// From Monday to Sunday.
+    pub weekly: Option<Goal>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GoalProgress {
+    pub goal: Goal,
+    pub done: u64,
+    pub is_met: bool,
+}
+
+impl GoalProgress {
+    fn new(goal: Goal, totals: &ReadingTotals) -> Self {
+        Self {
+            goal,
+            done: goal.done(totals),
+            is_met: goal.is_met(totals),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GoalsSummary {
+    pub daily: Option<GoalProgress>,
+    pub weekly: Option<GoalProgress>,
+    /// See `streak`.
+    pub streak: usize,
+}
+
+/// How many days in a row the daily goal was met, or anything was read if
+/// there is no daily goal. A streak not continued today yet still counts
+/// until the day is over.
+pub fn streak<Tz: TimeZone>(
+    sessions: &[Session],
+    tz: &Tz,
+    today: NaiveDate,
+    daily: Option<&Goal>,
+) -> usize {
+    let mut totals: HashMap<NaiveDate, ReadingTotals> = HashMap::new();
+    for session in sessions {
+        totals
+            .entry(session_date(session, tz))
+            .or_default()
+            .add(session);
+    }
+    let is_met = |date: &NaiveDate| {
+        totals.get(date).is_some_and(|totals| match daily {
+            Some(goal) => goal.is_met(totals),
+            None => totals.seconds > 0 || totals.characters > 0,
+        })
+    };
+
+    let mut date = today;
+    if !is_met(&date) {
+        date -= Duration::days(1);
+    }
+    let mut streak = 0;
+    while is_met(&date) {
+        streak += 1;
+        date -= Duration::days(1);
+    }
+    streak
+}
+
+pub fn goals_summary<Tz: TimeZone>(
+    sessions: &[Session],
+    goals: &ReadingGoals,
+    tz: &Tz,
+    now: u64,
+) -> GoalsSummary {
+    let stats = reading_stats(sessions, tz, now);
+    GoalsSummary {
+        daily: goals
+            .daily
+            .map(|goal| GoalProgress::new(goal, &stats.today)),
+        weekly: goals
+            .weekly
+            .map(|goal| GoalProgress::new(goal, &stats.this_week)),
+        streak: streak(sessions, tz, date_of(now, tz), goals.daily.as_ref()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct TimeLeft {
+    /// `None` if the progress is not in a chapter.
+    pub chapter_seconds: Option<u64>,
+    pub book_seconds: u64,
+}
+
+/// How long it takes to finish the current chapter and the book, reading
+/// `speed` characters per minute. The length of the book is counted in
+/// characters, which only differs from progress for rare characters.
+pub fn time_left(book: &Book, speed: f64) -> TimeLeft {
+    let total = book.total_character_count.max(book.progress);
+    let seconds = |characters: usize| (characters as f64 * 60.0 / speed).round() as u64;
+    let chapter = book
+        .chapter_starts
+        .as_deref()
+        .and_then(|starts| chapters::chapter_at(starts, total, book.progress));
+    TimeLeft {
+        chapter_seconds: chapter.map(|(_, end)| seconds(end - book.progress)),
+        book_seconds: seconds(total - book.progress),
+    }
+}
+
+fn sessions_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap()
+        .join(DATA_ROOT_DIR)
+        .join(SESSIONS_FILENAME)
+}
+
+/// Merge the sessions that are too old to count for the reading speed into
+/// one per book and local date, so that the log doesn't grow with every
+/// session. The totals of every date stay the same.
+pub fn compact_sessions<Tz: TimeZone>(sessions: Vec<Session>, tz: &Tz, now: u64) -> Vec<Session> {
+    let since = now.saturating_sub(SPEED_DAYS * 24 * 60 * 60);
+    let (old, recent): (Vec<Session>, Vec<Session>) = sessions
+        .into_iter()
+        .partition(|session| session.start < since);
+
+    let mut merged: BTreeMap<(NaiveDate, String), Session> = BTreeMap::new();
+    for session in old {
+        let key = (session_date(&session, tz), session.book_id.clone());
+        match merged.get_mut(&key) {
+            Some(day) => {
+                let seconds = day.seconds() + session.seconds();
+                day.start = day.start.min(session.start);
+                day.end = day.start + seconds;
+                day.characters += session.characters;
+            }
+            None => {
+                merged.insert(key, session);
+            }
+        }
+    }
+
+    let mut sessions: Vec<Session> = merged.into_values().collect();
+    sessions.sort_by_key(|session| session.start);
+    sessions.extend(recent);
+    sessions
+}
+
+/// An unreadable session log is started over, as it only holds statistics.
+pub fn read_sessions_from_disk() -> Vec<Session> {
+    persist::read_json_with_backups(&sessions_path()).unwrap_or_default()
+}
+
+/// The whole log is written every time, which is fine as it is compacted
+/// when read.
+pub fn write_sessions_to_disk(sessions: &[Session]) -> io::Result<()> {
+    persist::write_json_atomically(&sessions_path(), &sessions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, Utc};
+
+    fn session(start: u64, seconds: u64, characters: usize) -> Session {
+        Session {
+            book_id: String::from("a"),
+            start,
+            end: start + seconds,
+            characters,
+        }
+    }
+
+    #[test]
+    fn test_recorder() {
+        let mut recorder = Recorder::default();
+        // Not counted while the reader is hidden.
+        recorder.record_progress("a", 0, 100, 10);
+        assert!(!recorder.hide(20));
+
+        recorder.show("a", 100);
+        recorder.record_progress("a", 0, 300, 130);
+        recorder.record_progress("a", 300, 200, 140);
+        recorder.record_progress("a", 200, 200 + MAX_PAGE_CHARACTERS + 1, 150);
+        recorder.record_progress("b", 0, 50, 160);
+        assert!(recorder.hide(170));
+        assert_eq!(
+            recorder.sessions(),
+            [
+                Session {
+                    book_id: String::from("a"),
+                    start: 100,
+                    end: 160,
+                    characters: 300,
+                },
+                Session {
+                    book_id: String::from("b"),
+                    start: 160,
+                    end: 170,
+                    characters: 50,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recorder_stops_counting_when_idle() {
+        let mut recorder = Recorder::default();
+        recorder.show("a", 1000);
+        recorder.record_progress("a", 0, 10, 1010);
+        recorder.hide(1010 + IDLE_SECONDS + 3600);
+        assert_eq!(recorder.sessions()[0].seconds(), 10 + IDLE_SECONDS);
+
+        // Nothing happened at all.
+        recorder.show("a", 5000);
+        assert!(!recorder.hide(5000));
+    }
+
+    #[test]
+    fn test_unsaved_sessions() {
+        let mut recorder = Recorder::new(vec![session(0, 60, 100)]);
+        assert!(recorder.unsaved_sessions(50).is_none());
+
+        recorder.show("a", 100);
+        recorder.record_progress("a", 0, 300, 130);
+        let sessions = recorder.unsaved_sessions(140).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[1].end, 140);
+        assert_eq!(sessions[1].characters, 300);
+        // It stops changing once idle.
+        assert!(recorder.unsaved_sessions(130 + IDLE_SECONDS + 10).is_some());
+        assert!(recorder.unsaved_sessions(130 + IDLE_SECONDS + 40).is_none());
+
+        // Still recorded once when it ends.
+        assert!(recorder.hide(200));
+        assert_eq!(recorder.sessions().len(), 2);
+    }
+
+    #[test]
+    fn test_compact_sessions() {
+        // Monday 2026-10-19 12:00:00 UTC
+        let now = 1_792_411_200;
+        let day = 24 * 3600;
+        let old = now - 40 * day;
+        let mut sessions = vec![
+            session(old, 600, 1000),
+            session(old + 3600, 300, 500),
+            session(old - day, 60, 10),
+            session(now - day, 1200, 2000),
+        ];
+        sessions[1].book_id = String::from("b");
+        sessions.push(session(old + 7200, 120, 200));
+
+        let first = date_of(old - day, &Utc);
+        let today = date_of(now, &Utc);
+        let totals = totals_by_date(&sessions, &Utc, first, today);
+        let compacted = compact_sessions(sessions.clone(), &Utc, now);
+        assert_eq!(compacted.len(), 4);
+        assert_eq!(compacted[1], session(old, 720, 1200));
+        assert_eq!(compacted[3], sessions[3]);
+        assert_eq!(totals_by_date(&compacted, &Utc, first, today), totals);
+        assert_eq!(compact_sessions(compacted.clone(), &Utc, now), compacted);
+    }
+
+    #[test]
+    fn test_session_date_is_local() {
+        // 2026-10-18 23:30:00 UTC
+        let session = session(1_792_366_200, 60, 0);
+        let utc_plus_8 = FixedOffset::east_opt(8 * 3600).unwrap();
+        assert_eq!(
+            session_date(&session, &Utc),
+            NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+        );
+        assert_eq!(
+            session_date(&session, &utc_plus_8),
+            NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reading_stats() {
+        // Monday 2026-10-19 12:00:00 UTC
+        let now = 1_792_411_200;
+        let day = 24 * 3600;
+        let sessions = [
+            session(now - 3600, 600, 3000),
+            session(now - 7200, 300, 1500),
+            // Sunday, last week.
+            session(now - day, 1200, 0),
+            session(now - 10 * day, 600, 600),
+        ];
+
+        let stats = reading_stats(&sessions, &Utc, now);
+        assert_eq!(
+            stats.today,
+            ReadingTotals {
+                seconds: 900,
+                characters: 4500,
+            }
+        );
+        assert_eq!(stats.this_week, stats.today);
+        assert_eq!(stats.days.len(), 7);
+        assert_eq!(stats.days[5].date, "2026-10-18");
+        assert_eq!(stats.days[5].totals.seconds, 1200);
+        assert_eq!(stats.days[6].date, "2026-10-19");
+        // The session without any page turned doesn't count.
+        assert_eq!(stats.speed, Some(5100.0 * 60.0 / 1500.0));
+    }
+
+    #[test]
+    fn test_streak() {
+        // Monday 2026-10-19 12:00:00 UTC
+        let now = 1_792_411_200;
+        let today = date_of(now, &Utc);
+        let day = 24 * 3600;
+        let sessions = [
+            session(now - day, 1200, 100),
+            session(now - 2 * day, 300, 100),
+            session(now - 3 * day, 1200, 100),
+            session(now - 5 * day, 1200, 100),
+        ];
+        let goal = Goal {
+            unit: GoalUnit::Minutes,
+            amount: 10,
+        };
+
+        // Not read yet today, which doesn't break the streak.
+        assert_eq!(streak(&sessions, &Utc, today, None), 3);
+        assert_eq!(streak(&sessions, &Utc, today, Some(&goal)), 1);
+
+        let mut sessions = sessions.to_vec();
+        sessions.push(session(now - 60, 600, 100));
+        assert_eq!(streak(&sessions, &Utc, today, None), 4);
+        assert_eq!(streak(&sessions, &Utc, today, Some(&goal)), 2);
+        assert_eq!(streak(&sessions, &Utc, today + Duration::days(2), None), 0);
+    }
+
+    #[test]
+    fn test_goals_summary() {
+        // Monday 2026-10-19 12:00:00 UTC
+        let now = 1_792_411_200;
+        let sessions = [session(now - 3600, 900, 6000)];
+        let goals = ReadingGoals {
+            daily: Some(Goal {
+                unit: GoalUnit::Minutes,
+                amount: 20,
+            }),
+            weekly: Some(Goal {
+                unit: GoalUnit::Characters,
+                amount: 5000,
+            }),
+        };
+
+        let summary = goals_summary(&sessions, &goals, &Utc, now);
+        let daily = summary.daily.unwrap();
+        assert_eq!((daily.done, daily.is_met), (15, false));
+        let weekly = summary.weekly.unwrap();
+        assert_eq!((weekly.done, weekly.is_met), (6000, true));
+        assert_eq!(summary.streak, 0);
+
+        let summary = goals_summary(&sessions, &ReadingGoals::default(), &Utc, now);
+        assert!(summary.daily.is_none());
+        assert_eq!(summary.streak, 1);
+    }
+
+    #[test]
+    fn test_time_left() {
+        let book = Book {
+            id: String::new(),
+            title: String::new(),
+            summary: String::new(),
+            total_character_count: 10_000,
+            progress: 2_000,
+            last_read_time: 0,
+            chinese_conversion: Default::default(),
+            settings: Default::default(),
+            chapter_starts: Some(vec![0, 3_000, 6_000]),
+            bookmarks: Vec::new(),
+            highlights: Vec::new(),
+            history: Default::default(),
+        };
+        assert_eq!(
+            time_left(&book, 500.0),
+            TimeLeft {
+                chapter_seconds: Some(120),
+                book_seconds: 960,
+            }
+        );
+
+        let book = Book {
+            chapter_starts: Some(vec![]),
+            ..book
+        };
+        assert_eq!(time_left(&book, 500.0).chapter_seconds, None);
+    }
+
+    #[test]
+    fn test_reading_speed_needs_a_minute() {
+        assert_eq!(reading_speed(&[session(0, 59, 100)], 0), None);
+        assert_eq!(reading_speed(&[session(0, 120, 100)], 0), Some(50.0));
+        assert_eq!(reading_speed(&[session(0, 120, 100)], 1), None);
+    }
+}
@@ -0,0 +1,293 @@
+//! The tray icon and its menu.
+
+use crate::{
+    command,
+    config::Config,
+    library,
+    stats::{GoalProgress, GoalUnit, GoalsSummary},
+};
+use std::sync::Mutex;
+use tauri::{
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Manager, Wry,
+};
+
+const TRAY_ID: &str = "main";
+/// Followed by the name of the profile, in the ID of its menu item.
+const PROFILE_MENU_ID_PREFIX: &str = "profile:";
+
+/// The languages the tray is shown in, which are those the settings window
+/// is translated into. Traditional Chinese falls back to Simplified Chinese
+/// there as well.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    Chinese,
+}
+
+impl Language {
+    /// From a BCP 47 language tag, e.g. `navigator.language` of a webview.
+    pub fn from_tag(tag: &str) -> Self {
+        let primary = tag.split(['-', '_']).next().unwrap_or_default();
+        if primary.eq_ignore_ascii_case("zh") {
+            Language::Chinese
+        } else {
+            Language::English
+        }
+    }
+}
+
+struct Strings {
+    open_settings: &'static str,
+    profiles: &'static str,
+    no_profiles: &'static str,
+    reader_to_primary: &'static str,
+    quit: &'static str,
+    today: &'static str,
+    this_week: &'static str,
+    streak: &'static str,
+    minutes: &'static str,
+    characters: &'static str,
+    day: &'static str,
+    days: &'static str,
+}
+
+const ENGLISH: Strings = Strings {
+    open_settings: "Open settings",
+    profiles: "Profiles",
+    no_profiles: "No profiles",
+    reader_to_primary: "Bring reader back to primary monitor",
+    quit: "Quit",
+    today: "Today",
+    this_week: "This week",
+    streak: "Streak",
+    minutes: "min",
+    characters: "characters",
+    day: "day",
+    days: "days",
+};
+
+const CHINESE: Strings = Strings {
+    open_settings: "打开设置",
+    profiles: "配置方案",
+    no_profiles: "没有配置方案",
+    reader_to_primary: "将阅读窗口移回主显示器",
+    quit: "退出",
+    today: "今天",
+    this_week: "本周",
+    streak: "连续天数",
+    minutes: "分钟",
+    characters: "字",
+    day: "天",
+    days: "天",
+};
+
+fn strings(app: &AppHandle) -> &'static Strings {
+    let language = app.state::<Mutex<Language>>();
+    let language = *language.lock().unwrap();
+    match language {
+        Language::English => &ENGLISH,
+        Language::Chinese => &CHINESE,
+    }
+}
+
+pub fn create_tray(app: &AppHandle, config: &Config) -> tauri::Result<()> {
+    let menu = build_menu(app, config)?;
+
+    let mut tray_builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .icon(tauri::include_image!("icons/32x32.png"))
+        .on_menu_event(on_menu_event)
+        .on_tray_icon_event(move |tray, event| {
+            if let TrayIconEvent::Click {
+                id: _,
+                position: _,
+                rect: _,
+                button,
+                button_state,
+            } = event
+            {
+                if button == MouseButton::Left && button_state == MouseButtonState::Up {
+                    crate::open_or_create_settings_window(tray.app_handle());
+                }
+            }
+        });
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        tray_builder = tray_builder.show_menu_on_left_click(false)
+    }
+
+    tray_builder.build(app)?;
+    Ok(())
+}
+
+/// Rebuild the menu, after the profiles or the active one changed.
+pub fn refresh_menu(app: &AppHandle, config: &Config) -> tauri::Result<()> {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        tray.set_menu(Some(build_menu(app, config)?))?;
+    }
+    Ok(())
+}
+
+/// Show how far the reading goals are reached, when the tray icon is hovered.
+pub fn refresh_tooltip(app: &AppHandle, summary: &GoalsSummary) -> tauri::Result<()> {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        tray.set_tooltip(Some(tooltip_of(summary, strings(app))))?;
+    }
+    Ok(())
+}
+
+fn tooltip_of(summary: &GoalsSummary, strings: &Strings) -> String {
+    let mut lines = vec![String::from("Sneaky Reader")];
+    let progress = |name: &str, progress: &GoalProgress| {
+        let unit = match progress.goal.unit {
+            GoalUnit::Minutes => strings.minutes,
+            GoalUnit::Characters => strings.characters,
+        };
+        format!(
+            "{name}: {}/{} {unit}{}",
+            progress.done,
+            progress.goal.amount,
+            if progress.is_met { " ✓" } else { "" }
+        )
+    };
+    if let Some(daily) = &summary.daily {
+        lines.push(progress(strings.today, daily));
+    }
+    if let Some(weekly) = &summary.weekly {
+        lines.push(progress(strings.this_week, weekly));
+    }
+    if summary.streak > 0 {
+        lines.push(format!(
+            "{}: {} {}",
+            strings.streak,
+            summary.streak,
+            if summary.streak == 1 {
+                strings.day
+            } else {
+                strings.days
+            }
+        ));
+    }
+    lines.join("\n")
+}
+
+fn build_menu(app: &AppHandle, config: &Config) -> tauri::Result<Menu<Wry>> {
+    let strings = strings(app);
+    let menu_item_settings =
+        MenuItem::with_id(app, "settings", strings.open_settings, true, None::<&str>)?;
+
+    let submenu_profiles = Submenu::with_id(app, "profiles", strings.profiles, true)?;
+    if config.profiles.is_empty() {
+        let menu_item_none =
+            MenuItem::with_id(app, "no-profiles", strings.no_profiles, false, None::<&str>)?;
+        submenu_profiles.append(&menu_item_none)?;
+    }
+    for profile in &config.profiles {
+        let menu_item_profile = CheckMenuItem::with_id(
+            app,
+            format!("{PROFILE_MENU_ID_PREFIX}{}", profile.name),
+            &profile.name,
+            true,
+            config.active_profile.as_ref() == Some(&profile.name),
+            None::<&str>,
+        )?;
+        submenu_profiles.append(&menu_item_profile)?;
+    }
+
+    let menu_item_reader_to_primary = MenuItem::with_id(
+        app,
+        "reader-to-primary",
+        strings.reader_to_primary,
+        true,
+        None::<&str>,
+    )?;
+    let menu_item_quit = MenuItem::with_id(app, "quit", strings.quit, true, None::<&str>)?;
+    Menu::with_items(
+        app,
+        &[
+            &menu_item_settings,
+            &submenu_profiles,
+            &menu_item_reader_to_primary,
+            &menu_item_quit,
+        ],
+    )
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id.as_ref() {
+        "settings" => {
+            crate::open_or_create_settings_window(app);
+        }
+        "reader-to-primary" => {
+            if let Err(e) = command::bring_reader_to_primary_monitor_aux(app) {
+                eprintln!("Cannot move reader: {e}");
+            }
+        }
+        "quit" => {
+            // Quit anyway, as there is no window to report errors to.
+            if let Err(e) = command::persist_appearance_aux(app) {
+                eprintln!("Cannot persist appearance: {e}");
+            }
+            if let Err(e) = command::record_reader_shown_aux(app, false) {
+                eprintln!("Cannot record reading session: {e}");
+            }
+
+            let books_aux = app.state::<Mutex<library::BooksAux>>();
+            let books_aux = books_aux.lock().unwrap();
+            if let Err(e) = library::write_books_to_disk(&books_aux.books) {
+                eprintln!("Cannot write metadata file: {e}");
+            }
+
+            app.exit(0);
+        }
+        id => {
+            if let Some(name) = id.strip_prefix(PROFILE_MENU_ID_PREFIX) {
+                if let Err(e) = command::apply_profile_aux(app, name) {
+                    eprintln!("Cannot apply profile {name}: {e}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::Goal;
+
+    #[test]
+    fn test_language_from_tag() {
+        assert_eq!(Language::from_tag("zh-CN"), Language::Chinese);
+        assert_eq!(Language::from_tag("zh_TW"), Language::Chinese);
+        assert_eq!(Language::from_tag("en-US"), Language::English);
+        assert_eq!(Language::from_tag(""), Language::English);
+    }
+
+    #[test]
+    fn test_tooltip_of() {
+        let summary = GoalsSummary {
+            daily: Some(GoalProgress {
+                goal: Goal {
+                    unit: GoalUnit::Minutes,
+                    amount: 30,
+                },
+                done: 30,
+                is_met: true,
+            }),
+            weekly: None,
+            streak: 2,
+        };
+        assert_eq!(
+            tooltip_of(&summary, &ENGLISH),
+            "Sneaky Reader\nToday: 30/30 min ✓\nStreak: 2 days"
+        );
+        assert_eq!(
+            tooltip_of(&summary, &CHINESE),
+            "Sneaky Reader\n今天: 30/30 分钟 ✓\n连续天数: 2 天"
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! Conversion between Simplified and Traditional Chinese, using OpenCC-style
+//! dictionary tables bundled in `dicts/`.
+//!
+//! Each line of a table is a key, a tab, and space-separated candidates, of
+//! which only the first one is used. The Traditional-to-Simplified characters
+//! are derived by reversing `STCharacters.txt`, with `TSCharacters.txt` only
+//! holding the extra variants.
+//!
+//! Only entries that keep the number of characters are used, so that reading
+//! progress stays valid whether the text is converted or not.
+//!
+//! The tables are a subset of those of OpenCC, see `dicts/README.md`. They
+//! cover the common characters and only a few phrases, so rarer characters
+//! are left as they are, and a character that converts differently within a
+//! phrase may be converted as if on its own.
+
+use serde::{Deserialize, Serialize};
+use std::{cmp::Reverse, collections::HashMap, sync::OnceLock};
+
+const ST_CHARACTERS: &str = include_str!("dicts/STCharacters.txt");
+const ST_PHRASES: &str = include_str!("dicts/STPhrases.txt");
+const TS_CHARACTERS: &str = include_str!("dicts/TSCharacters.txt");
+const TS_PHRASES: &str = include_str!("dicts/TSPhrases.txt");
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ChineseConversion {
+    #[default]
+    None,
+    SimplifiedToTraditional,
+    TraditionalToSimplified,
+}
+
+/// A phrase and its conversion, both as characters.
+type Phrase = (Vec<char>, Vec<char>);
+
+#[derive(Debug, Default)]
+struct Dictionary {
+    characters: HashMap<char, char>,
+    /// Phrases grouped by their first character, longest first.
+    phrases: HashMap<char, Vec<Phrase>>,
+}
+
+impl Dictionary {
+    fn insert_character(&mut self, from: char, to: char) {
+        if from != to {
+            self.characters.entry(from).or_insert(to);
+        }
+    }
+
+    fn insert_phrase(&mut self, from: &str, to: &str) {
+        let from: Vec<char> = from.chars().collect();
+        let to: Vec<char> = to.chars().collect();
+        if from.len() < 2 || from.len() != to.len() {
+            return;
+        }
+        self.phrases.entry(from[0]).or_default().push((from, to));
+    }
+
+    fn sort_phrases(&mut self) {
+        for phrases in self.phrases.values_mut() {
+            phrases.sort_by_key(|(from, _)| Reverse(from.len()));
+        }
+    }
+
+    fn convert(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut ret = String::with_capacity(text.len());
+
+        let mut i = 0;
+        'outer: while i < chars.len() {
+            if let Some(phrases) = self.phrases.get(&chars[i]) {
+                for (from, to) in phrases {
+                    if chars[i..].starts_with(from) {
+                        ret.extend(to);
+                        i += from.len();
+                        continue 'outer;
+                    }
+                }
+            }
+            ret.push(*self.characters.get(&chars[i]).unwrap_or(&chars[i]));
+            i += 1;
+        }
+
+        ret
+    }
+}
+
+/// Parse a table into `(key, first candidate)` pairs.
+fn parse_table(table: &str) -> impl Iterator<Item = (&str, &str)> {
+    table.lines().filter_map(|line| {
+        let (key, candidates) = line.split_once('\t')?;
+        let first = candidates.split(' ').next()?;
+        Some((key, first))
+    })
+}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    let c = chars.next()?;
+    chars.next().is_none().then_some(c)
+}
+
+fn simplified_to_traditional() -> &'static Dictionary {
+    static DICTIONARY: OnceLock<Dictionary> = OnceLock::new();
+    DICTIONARY.get_or_init(|| {
+        let mut dictionary = Dictionary::default();
+        for (from, to) in parse_table(ST_CHARACTERS) {
+            if let (Some(from), Some(to)) = (single_char(from), single_char(to)) {
+                dictionary.insert_character(from, to);
+            }
+        }
+        for (from, to) in parse_table(ST_PHRASES) {
+            dictionary.insert_phrase(from, to);
+        }
+        dictionary.sort_phrases();
+        dictionary
+    })
+}
+
+fn traditional_to_simplified() -> &'static Dictionary {
+    static DICTIONARY: OnceLock<Dictionary> = OnceLock::new();
+    DICTIONARY.get_or_init(|| {
+        let mut dictionary = Dictionary::default();
+        for (from, to) in parse_table(TS_CHARACTERS) {
+            if let (Some(from), Some(to)) = (single_char(from), single_char(to)) {
+                dictionary.insert_character(from, to);
+            }
+        }
+        for line in ST_CHARACTERS.lines() {
+            let Some((simplified, candidates)) = line.split_once('\t') else {
+                continue;
+            };
+            let Some(simplified) = single_char(simplified) else {
+                continue;
+            };
+            for traditional in candidates.split(' ').filter_map(single_char) {
+                dictionary.insert_character(traditional, simplified);
+            }
+        }
+        for (from, to) in parse_table(TS_PHRASES) {
+            dictionary.insert_phrase(from, to);
+        }
+        dictionary.sort_phrases();
+        dictionary
+    })
+}
+
+pub fn convert(text: &str, conversion: ChineseConversion) -> String {
+    match conversion {
+        ChineseConversion::None => text.to_string(),
+        ChineseConversion::SimplifiedToTraditional => simplified_to_traditional().convert(text),
+        ChineseConversion::TraditionalToSimplified => traditional_to_simplified().convert(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simplified_to_traditional() {
+        assert_eq!(
+            convert(
+                "皇后以后剪了头发，吃了面条。",
+                ChineseConversion::SimplifiedToTraditional
+            ),
+            "皇后以後剪了頭髮，吃了麵條。"
+        );
+    }
+
+    #[test]
+    fn test_traditional_to_simplified() {
+        assert_eq!(
+            convert(
+                "乾隆皇帝的頭髮乾淨了。",
+                ChineseConversion::TraditionalToSimplified
+            ),
+            "乾隆皇帝的头发干净了。"
+        );
+    }
+
+    #[test]
+    fn test_non_chinese_text_unchanged() {
+        let text = "Lorem ipsum, dolor sit amet.\n";
+        assert_eq!(
+            convert(text, ChineseConversion::SimplifiedToTraditional),
+            text
+        );
+        assert_eq!(
+            convert(text, ChineseConversion::TraditionalToSimplified),
+            text
+        );
+    }
+
+    #[test]
+    fn test_conversion_keeps_length() {
+        let text = include_str!("texts/sample_chinese.txt");
+        for conversion in [
+            ChineseConversion::SimplifiedToTraditional,
+            ChineseConversion::TraditionalToSimplified,
+        ] {
+            let converted = convert(text, conversion);
+            assert_eq!(converted.chars().count(), text.chars().count());
+            assert_eq!(
+                converted.encode_utf16().count(),
+                text.encode_utf16().count()
+            );
+        }
+    }
+}
@@ -0,0 +1,99 @@
+import { Camouflage } from "../util";
+
+export interface CamouflageTheme {
+  /** Added to `body`, and styled in `styles.css`. */
+  className: string;
+  /**
+   * Text shown before a paragraph, given its number in the book. A paragraph
+   * continued from the previous page gets the prefix of a wrapped line.
+   */
+  prefix?: (paragraphNumber: number, isContinued: boolean) => string;
+}
+
+const CHAT_NAMES = ["Alex", "Sam"];
+
+/** Fake times go forward from when the reader was opened. */
+const startTime = Date.now();
+
+/**
+ * A time for the paragraph that stays the same across pages, a few seconds
+ * after the previous paragraph.
+ */
+function fakeTime(paragraphNumber: number): Date {
+  // Cheap deterministic jitter, so that the gaps don't look regular.
+  const jitter = (paragraphNumber * 2654435761) % 7;
+  return new Date(startTime + paragraphNumber * 4000 + jitter * 1000);
+}
+
+function pad(n: number, length = 2): string {
+  return String(n).padStart(length, "0");
+}
+
+const themes: Record<Camouflage, CamouflageTheme> = {
+  None: { className: "" },
+  Terminal: {
+    className: "camouflage-terminal",
+    prefix: (paragraphNumber, isContinued) => {
+      if (isContinued) {
+        return "";
+      }
+      const time = fakeTime(paragraphNumber);
+      return `[${pad(time.getHours())}:${pad(time.getMinutes())}:${pad(
+        time.getSeconds()
+      )}]`;
+    },
+  },
+  CodeEditor: {
+    className: "camouflage-code-editor",
+    prefix: (paragraphNumber, isContinued) =>
+      isContinued ? "" : String(paragraphNumber + 1),
+  },
+  Spreadsheet: {
+    className: "camouflage-spreadsheet",
+    prefix: (paragraphNumber) => String(paragraphNumber + 1),
+  },
+  Chat: {
+    className: "camouflage-chat",
+    prefix: (paragraphNumber, isContinued) => {
+      if (isContinued) {
+        return "";
+      }
+      const time = fakeTime(paragraphNumber);
+      const name = CHAT_NAMES[paragraphNumber % CHAT_NAMES.length];
+      return `${name} ${pad(time.getHours())}:${pad(time.getMinutes())}`;
+    },
+  },
+};
+
+export function getCamouflageTheme(camouflage: Camouflage): CamouflageTheme {
+  return themes[camouflage] ?? themes.None;
+}
+
+/**
+ * Offsets of the line breaks in a text, for finding the number of the
+ * paragraph at any offset.
+ */
+export function lineBreakOffsets(content: string): number[] {
+  const offsets = [];
+  let i = content.indexOf("\n");
+  while (i !== -1) {
+    offsets.push(i);
+    i = content.indexOf("\n", i + 1);
+  }
+  return offsets;
+}
+
+/** The number of line breaks before `index`. */
+export function paragraphNumberAt(offsets: number[], index: number): number {
+  let left = 0;
+  let right = offsets.length;
+  while (left < right) {
+    const mid = (left + right) >> 1;
+    if (offsets[mid] < index) {
+      left = mid + 1;
+    } else {
+      right = mid;
+    }
+  }
+  return left;
+}
@@ -1,6 +1,18 @@
-import { listen } from "@tauri-apps/api/event";
-import { Config, invokeCommand, ReaderBookInfo } from "../util";
+import { emit, listen } from "@tauri-apps/api/event";
+import {
+  Appearance,
+  BookSettings,
+  Config,
+  invokeCommand,
+  ReaderBookInfo,
+  ReaderSelection,
+} from "../util";
 import { Pager } from "./pager";
+import {
+  getCamouflageTheme,
+  lineBreakOffsets,
+  paragraphNumberAt,
+} from "./camouflage";
 
 const BINARY_SEARCH_START_LENGTH = 512;
 
@@ -10,12 +22,27 @@ let contentReal: HTMLDivElement | null = null;
 let contentDryRun: HTMLDivElement | null = null;
 
 let bookInfo: ReaderBookInfo | null = null;
+/** Offsets of the line breaks in the content of `bookInfo`. */
+let lineBreaks: number[] = [];
+let appearance: Appearance | null = null;
 let pager: Pager | null = null;
+/** Whether text can be selected to be highlighted, see `isHighlighting`. */
+let isHighlighting = false;
 
 document.addEventListener("keydown", (event) => event.preventDefault());
 document.addEventListener("keyup", (event) => event.preventDefault());
-document.addEventListener("mousedown", (event) => event.preventDefault());
-document.addEventListener("mouseup", (event) => event.preventDefault());
+document.addEventListener("mousedown", (event) => {
+  if (!isHighlighting) {
+    event.preventDefault();
+  }
+});
+document.addEventListener("mouseup", (event) => {
+  if (!isHighlighting) {
+    event.preventDefault();
+    return;
+  }
+  emit("reader-selection-changed", getSelection());
+});
 document.addEventListener("wheel", (event) => event.preventDefault());
 document.addEventListener("contextmenu", (event) => event.preventDefault());
 
@@ -29,6 +56,8 @@ window.addEventListener("DOMContentLoaded", async () => {
   contentReal = document.getElementById("content-real") as HTMLDivElement;
   contentDryRun = document.getElementById("content-dry-run") as HTMLDivElement;
 
+  invokeCommand("set_tray_language", { language: navigator.language });
+
   const temBookInfo = await invokeCommand<ReaderBookInfo | null>(
     "get_first_reader_book_info"
   );
@@ -37,6 +66,7 @@ window.addEventListener("DOMContentLoaded", async () => {
     return;
   }
   bookInfo = temBookInfo;
+  lineBreaks = bookInfo ? lineBreakOffsets(bookInfo.content) : [];
 
   const config = await invokeCommand<Config>("get_config");
   if (typeof config === "undefined") {
@@ -44,10 +74,8 @@ window.addEventListener("DOMContentLoaded", async () => {
     return;
   }
 
-  contentReal.style.fontSize = `${config.appearance.text_size}px`;
-  contentDryRun.style.fontSize = `${config.appearance.text_size}px`;
-  contentReal.style.color = config.appearance.text_color;
-  contentDryRun.style.color = config.appearance.text_color;
+  appearance = config.appearance;
+  applyTextStyles();
 
   refreshContent();
 });
@@ -70,8 +98,70 @@ listen("end-changing-styles", () => {
   const style = contentContainerReal.style;
   style.outlineWidth = "0px";
   style.visibility = "hidden";
+  setHighlighting(false);
+});
+
+listen<boolean>("highlight-mode-changed", (event) => {
+  setHighlighting(event.payload);
 });
 
+/**
+ * While highlighting, the window can't be dragged, and text is selected
+ * instead. Only possible while styles are changed, when the reader gets
+ * cursor events at all.
+ */
+function setHighlighting(highlighting: boolean): void {
+  isHighlighting = highlighting;
+  document.body.classList.toggle("highlighting", highlighting);
+  if (!highlighting) {
+    window.getSelection()?.removeAllRanges();
+  }
+}
+
+/** What is selected in the content, in offsets into it. */
+function getSelection(): ReaderSelection | null {
+  const selection = window.getSelection();
+  if (!bookInfo || !selection || selection.rangeCount === 0) {
+    return null;
+  }
+  const range = selection.getRangeAt(0);
+  const start = contentOffsetOf(range.startContainer, range.startOffset);
+  const end = contentOffsetOf(range.endContainer, range.endOffset);
+  if (start === null || end === null || start >= end) {
+    return null;
+  }
+  return {
+    bookId: bookInfo.id,
+    start,
+    end,
+    text: bookInfo.content.substring(start, end),
+  };
+}
+
+/**
+ * The offset into the content of a point in the page, from the offset of its
+ * paragraph. Points in the prefix of a camouflage theme are at the start.
+ */
+function contentOffsetOf(node: Node, offset: number): number | null {
+  const element = node instanceof Element ? node : node.parentElement;
+  const paragraph = element?.closest<HTMLParagraphElement>("#content-real > p");
+  if (!paragraph || typeof paragraph.dataset.offset === "undefined") {
+    return null;
+  }
+  const paragraphStart = Number(paragraph.dataset.offset);
+  if (element!.closest(".prefix")) {
+    return paragraphStart;
+  }
+  if (node.nodeType === Node.TEXT_NODE) {
+    return paragraphStart + offset;
+  }
+  // Between the children of an element.
+  const text = paragraph.querySelector(".text") ?? paragraph;
+  return offset < node.childNodes.length
+    ? paragraphStart
+    : paragraphStart + (text.textContent?.length ?? 0);
+}
+
 listen("show", () => {
   if (!contentContainerReal) {
     console.warn("DOM content not loaded");
@@ -103,10 +193,8 @@ listen("next-page", () => {
   }
   showContentInParagraphs(
     contentReal,
-    bookInfo.content.substring(
-      newPageContent.startIndex,
-      newPageContent.startIndex + newPageContent.contentLength
-    )
+    newPageContent.startIndex,
+    newPageContent.contentLength
   );
 
   bookInfo.progress = newPageContent.startIndex;
@@ -128,10 +216,8 @@ listen("prev-page", () => {
   }
   showContentInParagraphs(
     contentReal,
-    bookInfo.content.substring(
-      newPageContent.startIndex,
-      newPageContent.startIndex + newPageContent.contentLength
-    )
+    newPageContent.startIndex,
+    newPageContent.contentLength
   );
 
   bookInfo.progress = newPageContent!.startIndex;
@@ -140,36 +226,152 @@ listen("prev-page", () => {
 
 listen<ReaderBookInfo>("book-changed", (event) => {
   bookInfo = event.payload;
+  lineBreaks = lineBreakOffsets(bookInfo.content);
+  applyTextStyles();
+  refreshContent();
+});
+
+listen<BookSettings>("book-settings-changed", (event) => {
+  if (!bookInfo) {
+    console.warn("Book content not initialized");
+    return;
+  }
+  bookInfo.settings = event.payload;
+  applyTextStyles();
+  refreshContent();
+});
+
+listen<Appearance>("appearance-changed", (event) => {
+  appearance = event.payload;
+  applyTextStyles();
   refreshContent();
 });
 
 listen("refresh-content", refreshContent);
 
 listen<number>("text-size-changed", (event) => {
-  if (!contentReal || !contentDryRun) {
-    console.warn("DOM content not loaded");
+  if (!appearance) {
+    console.warn("Config not loaded");
     return;
   }
-  const textSize = `${event.payload}px`;
-  contentReal.style.fontSize = textSize;
-  contentDryRun.style.fontSize = textSize;
+  appearance.text_size = event.payload;
+  applyTextStyles();
   refreshContent();
 });
 
 listen<string>("text-color-changed", (event) => {
-  if (!contentReal || !contentDryRun) {
-    console.warn("DOM content not loaded");
+  if (!appearance) {
+    console.warn("Config not loaded");
     return;
   }
-  const textColor = event.payload;
-  contentReal.style.color = textColor;
-  contentDryRun.style.color = textColor;
+  appearance.text_color = event.payload;
+  applyTextStyles();
 });
 
+listenAppearanceChanged("font-family-changed", "font_family");
+listenAppearanceChanged("line-height-changed", "line_height");
+listenAppearanceChanged("letter-spacing-changed", "letter_spacing");
+listenAppearanceChanged("paragraph-spacing-changed", "paragraph_spacing");
+listenAppearanceChanged("first-line-indent-changed", "first_line_indent");
+listenAppearanceChanged("camouflage-changed", "camouflage");
+
+// These don't change the layout, so the content is kept as it is.
+for (const [eventName, key] of [
+  ["text-stroke-changed", "text_stroke"],
+  ["text-shadow-changed", "text_shadow"],
+  ["background-plate-changed", "background_plate"],
+] as const) {
+  listen<Appearance[typeof key]>(eventName, (event) => {
+    if (!appearance) {
+      console.warn("Config not loaded");
+      return;
+    }
+    Object.assign(appearance, { [key]: event.payload });
+    applyTextStyles();
+  });
+}
+
+/** Preview a change to a setting that affects how much text fits on a page. */
+function listenAppearanceChanged<K extends keyof Appearance>(
+  eventName: string,
+  key: K
+): void {
+  listen<Appearance[K]>(eventName, (event) => {
+    if (!appearance) {
+      console.warn("Config not loaded");
+      return;
+    }
+    appearance[key] = event.payload;
+    applyTextStyles();
+    refreshContent();
+  });
+}
+
+/**
+ * Apply the settings of the current book, falling back to the global ones for
+ * those it doesn't override. A camouflage theme replaces the font, colors and
+ * decorations.
+ */
+function applyTextStyles(): void {
+  if (
+    !appearance ||
+    !contentContainerReal ||
+    !contentReal ||
+    !contentDryRun
+  ) {
+    console.warn("DOM content or config not loaded");
+    return;
+  }
+  const settings = bookInfo?.settings;
+  const textSize = settings?.text_size ?? appearance.text_size;
+  const textColor = settings?.text_color ?? appearance.text_color;
+  const lineHeight = settings?.line_height ?? appearance.line_height;
+  const fontFamily = settings?.font_family ?? appearance.font_family;
+
+  const theme = getCamouflageTheme(appearance.camouflage);
+  const isCamouflaged = theme.className !== "";
+  document.body.className = [
+    theme.className,
+    bookInfo?.is_log_stream ? "log-stream" : "",
+  ]
+    .filter((className) => className !== "")
+    .join(" ");
+
+  for (const div of [contentReal, contentDryRun]) {
+    div.style.fontSize = `${textSize}px`;
+    div.style.color = isCamouflaged ? "" : textColor;
+    div.style.lineHeight = String(lineHeight);
+    div.style.fontFamily = isCamouflaged ? "" : fontFamily;
+    div.style.letterSpacing = `${appearance.letter_spacing}em`;
+    div.style.setProperty(
+      "--paragraph-spacing",
+      `${appearance.paragraph_spacing}em`
+    );
+    div.style.setProperty(
+      "--first-line-indent",
+      `${appearance.first_line_indent}em`
+    );
+  }
+
+  const { text_stroke: stroke, text_shadow: shadow } = appearance;
+  contentReal.style.webkitTextStroke =
+    !isCamouflaged && stroke.enabled ? `${stroke.width}px ${stroke.color}` : "";
+  contentReal.style.textShadow =
+    !isCamouflaged && shadow.enabled
+      ? `${shadow.offset_x}px ${shadow.offset_y}px ${shadow.blur}px ${shadow.color}`
+      : "";
+
+  const plate = appearance.background_plate;
+  contentContainerReal.style.backgroundColor =
+    !isCamouflaged && plate.enabled ? plate.color : "";
+  contentContainerReal.style.borderRadius = isCamouflaged
+    ? ""
+    : `${plate.corner_radius}px`;
+}
+
 function refreshContent(): void {
   const bestLength = binarySearchBestLength(
-    (len) =>
-      bookInfo!.content.substring(bookInfo!.progress, bookInfo!.progress + len),
+    () => bookInfo!.progress,
     bookInfo!.content.length - bookInfo!.progress
   );
 
@@ -177,24 +379,16 @@ function refreshContent(): void {
     bookInfo!.progress,
     bestLength,
     (pageContent) => {
-      if (
-        pageContent.startIndex + pageContent.contentLength >=
-        bookInfo!.content.length
-      ) {
+      const startIndex = pageContent.startIndex + pageContent.contentLength;
+      if (startIndex >= bookInfo!.content.length) {
         return null;
       }
       const nextBestLength = binarySearchBestLength(
-        (len) =>
-          bookInfo!.content.substring(
-            pageContent.startIndex + pageContent.contentLength,
-            pageContent.startIndex + pageContent.contentLength + len
-          ),
-        bookInfo!.content.length -
-          pageContent.startIndex -
-          pageContent.contentLength
+        () => startIndex,
+        bookInfo!.content.length - startIndex
       );
       return {
-        startIndex: pageContent.startIndex + pageContent.contentLength,
+        startIndex,
         contentLength: nextBestLength,
       };
     },
@@ -203,11 +397,7 @@ function refreshContent(): void {
         return null;
       }
       const prevBestLength = binarySearchBestLength(
-        (len) =>
-          bookInfo!.content.substring(
-            pageContent.startIndex - len,
-            pageContent.startIndex
-          ),
+        (len) => pageContent.startIndex - len,
         pageContent.startIndex
       );
       return {
@@ -217,13 +407,7 @@ function refreshContent(): void {
     }
   );
 
-  showContentInParagraphs(
-    contentReal!,
-    bookInfo!.content.substring(
-      bookInfo!.progress,
-      bookInfo!.progress + bestLength
-    )
-  );
+  showContentInParagraphs(contentReal!, bookInfo!.progress, bestLength);
 }
 
 async function reportProgress(): Promise<void> {
@@ -232,13 +416,17 @@ async function reportProgress(): Promise<void> {
     return;
   }
   await invokeCommand("update_progress", {
-    title: bookInfo.title,
+    id: bookInfo.id,
     progress: bookInfo.progress,
   });
 }
 
+/**
+ * Find the longest part of the book that fits in the reader window, where
+ * `lenToStart` gives where a part of the given length starts.
+ */
 function binarySearchBestLength(
-  lenToStr: (len: number) => string,
+  lenToStart: (len: number) => number,
   maxLen: number
 ): number {
   if (maxLen <= 0) {
@@ -248,7 +436,7 @@ function binarySearchBestLength(
   let left = 0;
   let right = Math.min(BINARY_SEARCH_START_LENGTH, maxLen);
 
-  while (canFit(lenToStr(right))) {
+  while (canFit(lenToStart(right), right)) {
     if (right === maxLen) {
       return maxLen;
     }
@@ -259,7 +447,7 @@ function binarySearchBestLength(
   let mid = 0;
   while (left !== right) {
     mid = ~~((left + right + 1) / 2);
-    if (canFit(lenToStr(mid))) {
+    if (canFit(lenToStart(mid), mid)) {
       left = mid;
     } else {
       right = mid - 1;
@@ -269,19 +457,56 @@ function binarySearchBestLength(
   return left;
 }
 
-function canFit(content: string): boolean {
-  showContentInParagraphs(contentDryRun!, content);
+function canFit(startIndex: number, length: number): boolean {
+  showContentInParagraphs(contentDryRun!, startIndex, length);
   return (
     contentContainerDryRun!.scrollHeight <= contentContainerDryRun!.clientHeight
   );
 }
 
-function showContentInParagraphs(div: HTMLDivElement, content: string): void {
-  const paragraphContents = content.split("\n").filter((s) => s.length > 0);
-  const paragraphs = paragraphContents.map((content) => {
+function showContentInParagraphs(
+  div: HTMLDivElement,
+  startIndex: number,
+  length: number
+): void {
+  const bookContent = bookInfo!.content;
+  const content = bookContent.substring(startIndex, startIndex + length);
+  // Whether the page starts in the middle of a paragraph, which then shouldn't
+  // be indented.
+  const isContinued = startIndex > 0 && bookContent[startIndex - 1] !== "\n";
+
+  const prefix = getCamouflageTheme(appearance!.camouflage).prefix;
+  const firstParagraphNumber = paragraphNumberAt(lineBreaks, startIndex);
+
+  const paragraphs: HTMLParagraphElement[] = [];
+  let paragraphStart = startIndex;
+  content.split("\n").forEach((paragraphContent, i) => {
+    const offset = paragraphStart;
+    paragraphStart += paragraphContent.length + 1;
+    if (paragraphContent.length === 0) {
+      return;
+    }
     const paragraph = document.createElement("p");
-    paragraph.textContent = content;
-    return paragraph;
+    paragraph.dataset.offset = String(offset);
+    const isParagraphContinued = i === 0 && isContinued;
+    if (isParagraphContinued) {
+      paragraph.classList.add("continued");
+    }
+    if (prefix) {
+      const prefixSpan = document.createElement("span");
+      prefixSpan.className = "prefix";
+      prefixSpan.textContent = prefix(
+        firstParagraphNumber + i,
+        isParagraphContinued
+      );
+      const textSpan = document.createElement("span");
+      textSpan.className = "text";
+      textSpan.textContent = paragraphContent;
+      paragraph.replaceChildren(prefixSpan, textSpan);
+    } else {
+      paragraph.textContent = paragraphContent;
+    }
+    paragraphs.push(paragraph);
   });
   div.replaceChildren(...paragraphs);
 }
@@ -1,4 +1,5 @@
 import {
+  Alert,
   Box,
   CssBaseline,
   Drawer,
@@ -7,21 +8,33 @@ import {
   ListItemButton,
   ListItemIcon,
   ListItemText,
+  Snackbar,
 } from "@mui/material";
 import { useEffect, useMemo, useState } from "react";
 import Appearance from "./Appearance";
 import Control from "./Control";
 import {
+  FileDownloadOutlined,
   FormatColorTextOutlined,
   InfoOutlined,
+  InsightsOutlined,
   KeyboardAltOutlined,
   LibraryBooksOutlined,
 } from "@mui/icons-material";
 import Library from "./Library";
+import Import from "./Import";
 import { useTranslation } from "react-i18next";
 import About from "./About";
-import { invokeCommand } from "../util";
+import Statistics from "./Statistics";
+import {
+  COMMAND_ERROR_EVENT,
+  CommandError,
+  ConfigRecovery,
+  invokeCommand,
+  LibraryRecovery,
+} from "../util";
 import { getVersion } from "@tauri-apps/api/app";
+import { listen } from "@tauri-apps/api/event";
 
 interface DrawerItem {
   name: string;
@@ -33,6 +46,10 @@ const drawerItems: DrawerItem[] = [
     name: "library",
     icon: <LibraryBooksOutlined />,
   },
+  {
+    name: "statistics",
+    icon: <InsightsOutlined />,
+  },
   {
     name: "appearance",
     icon: <FormatColorTextOutlined />,
@@ -41,6 +58,10 @@ const drawerItems: DrawerItem[] = [
     name: "control",
     icon: <KeyboardAltOutlined />,
   },
+  {
+    name: "import",
+    icon: <FileDownloadOutlined />,
+  },
   {
     name: "about",
     icon: <InfoOutlined />,
@@ -55,21 +76,33 @@ export default function App() {
   const [isTrialVersion, setIsTrialVersion] = useState(false);
   const [version, setVersion] = useState("");
   const [selectedItem, setSelectedItem] = useState("library");
+  const [configRecovery, setConfigRecovery] = useState<ConfigRecovery | null>(
+    null
+  );
+  const [libraryRecovery, setLibraryRecovery] =
+    useState<LibraryRecovery | null>(null);
+  const [commandError, setCommandError] = useState<CommandError | null>(null);
+  // Bumped when a profile replaces the appearance, to reload its page.
+  const [appearanceKey, setAppearanceKey] = useState(0);
 
   const mainComponent = useMemo(() => {
     switch (selectedItem) {
       case "appearance":
-        return <Appearance />;
+        return <Appearance key={appearanceKey} />;
       case "control":
         return <Control isTrialVersion={isTrialVersion} />;
       case "library":
         return <Library />;
+      case "statistics":
+        return <Statistics />;
+      case "import":
+        return <Import />;
       case "about":
         return <About isTrialVersion={isTrialVersion} version={version} />;
       default:
         return null;
     }
-  }, [selectedItem]);
+  }, [selectedItem, appearanceKey]);
 
   useEffect(() => {
     const init = async () => {
@@ -77,10 +110,19 @@ export default function App() {
         "get_is_trial_version"
       );
       const promiseGetVersion = getVersion();
-      const [isTrialVersion, version] = await Promise.all([
-        promiseGetIsTrialVersion,
-        promiseGetVersion,
-      ]);
+      const promiseGetConfigRecovery = invokeCommand<ConfigRecovery | null>(
+        "get_config_recovery"
+      );
+      const promiseGetLibraryRecovery = invokeCommand<LibraryRecovery | null>(
+        "get_library_recovery"
+      );
+      const [isTrialVersion, version, configRecovery, libraryRecovery] =
+        await Promise.all([
+          promiseGetIsTrialVersion,
+          promiseGetVersion,
+          promiseGetConfigRecovery,
+          promiseGetLibraryRecovery,
+        ]);
 
       if (typeof isTrialVersion === "undefined") {
         console.error(
@@ -90,12 +132,91 @@ export default function App() {
       }
       setIsTrialVersion(isTrialVersion);
       setVersion(version);
+      setConfigRecovery(configRecovery ?? null);
+      setLibraryRecovery(libraryRecovery ?? null);
       setReady(true);
     };
 
     init();
   }, []);
 
+  useEffect(() => {
+    const onCommandError = (event: Event) => {
+      setCommandError((event as CustomEvent<CommandError>).detail);
+    };
+    window.addEventListener(COMMAND_ERROR_EVENT, onCommandError);
+    return () => {
+      window.removeEventListener(COMMAND_ERROR_EVENT, onCommandError);
+    };
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen("appearance-changed", () => {
+      setAppearanceKey((key) => key + 1);
+    });
+    return () => {
+      unlisten.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  const onConfigRecoveryClose = () => {
+    setConfigRecovery(null);
+    invokeCommand("dismiss_config_recovery");
+  };
+
+  const configRecoveryMessage = (configRecovery: ConfigRecovery) => {
+    // A config from a newer version is read fine, it is only not saved.
+    if (
+      configRecovery.is_from_newer_version &&
+      configRecovery.bad_file === null &&
+      !configRecovery.is_from_backup
+    ) {
+      return t("configFromNewerVersion");
+    }
+    const messages = [
+      configRecovery.bad_file === null
+        ? t("configMissing")
+        : t("configCorrupt", { badFile: configRecovery.bad_file }),
+    ];
+    if (configRecovery.is_from_backup) {
+      messages.push(t("configRestoredFromBackup"));
+    } else if (configRecovery.is_reset_to_defaults) {
+      messages.push(t("configResetToDefaults"));
+    } else if (configRecovery.reset_fields.length > 0) {
+      messages.push(
+        t("configFieldsReset", {
+          fields: configRecovery.reset_fields.join(", "),
+        })
+      );
+    }
+    if (configRecovery.write_error !== null) {
+      messages.push(
+        t("configNotSaved", { message: configRecovery.write_error })
+      );
+    } else if (configRecovery.is_from_newer_version) {
+      messages.push(t("configFromNewerVersion"));
+    }
+    return messages.join(" ");
+  };
+
+  const onLibraryRecoveryClose = () => {
+    setLibraryRecovery(null);
+    invokeCommand("dismiss_library_recovery");
+  };
+
+  const libraryRecoveryMessage = (libraryRecovery: LibraryRecovery) => {
+    const messages = [
+      t("libraryCorrupt", { badFile: libraryRecovery.bad_file }),
+      t("libraryBooksRecovered", { count: libraryRecovery.recovered_count }),
+    ];
+    if (libraryRecovery.write_error !== null) {
+      messages.push(
+        t("libraryNotSaved", { message: libraryRecovery.write_error })
+      );
+    }
+    return messages.join(" ");
+  };
+
   if (!ready) {
     return <></>;
   }
@@ -125,6 +246,51 @@ export default function App() {
       <Box component="main" sx={{ width: "100%" }}>
         {mainComponent}
       </Box>
+
+      <Snackbar
+        open={configRecovery !== null}
+        anchorOrigin={{ vertical: "top", horizontal: "center" }}
+      >
+        <Alert
+          onClose={onConfigRecoveryClose}
+          severity="warning"
+          sx={{ width: "100%", userSelect: "text" }}
+        >
+          {configRecovery !== null && configRecoveryMessage(configRecovery)}
+        </Alert>
+      </Snackbar>
+
+      <Snackbar
+        open={libraryRecovery !== null && configRecovery === null}
+        anchorOrigin={{ vertical: "top", horizontal: "center" }}
+      >
+        <Alert
+          onClose={onLibraryRecoveryClose}
+          severity="warning"
+          sx={{ width: "100%", userSelect: "text" }}
+        >
+          {libraryRecovery !== null &&
+            libraryRecoveryMessage(libraryRecovery)}
+        </Alert>
+      </Snackbar>
+
+      <Snackbar
+        open={commandError !== null}
+        autoHideDuration={6000}
+        onClose={() => setCommandError(null)}
+        anchorOrigin={{ vertical: "bottom", horizontal: "center" }}
+      >
+        <Alert
+          onClose={() => setCommandError(null)}
+          severity="error"
+          sx={{ width: "100%", userSelect: "text" }}
+        >
+          {commandError !== null &&
+            t([`commandError_${commandError.kind}`, "commandError"], {
+              message: commandError.message,
+            })}
+        </Alert>
+      </Snackbar>
     </Box>
   );
 }
@@ -1,17 +1,36 @@
 import {
+  Autocomplete,
   Box,
-  Input,
   List,
   ListItem,
-  Slider,
+  ListSubheader,
+  MenuItem,
+  Select,
   SxProps,
+  TextField,
   Theme,
 } from "@mui/material";
-import { useEffect, useRef, useState } from "react";
-import { Config, invokeCommand } from "../util";
+import { useEffect, useState } from "react";
+import {
+  Anchor,
+  anchors,
+  BackgroundPlate,
+  Camouflage,
+  camouflages,
+  Config,
+  invokeCommand,
+  LogStreamOptions,
+  ReaderAnchor,
+  TextShadow,
+  TextStroke,
+} from "../util";
 import { useTranslation } from "react-i18next";
-import { HexAlphaColorPicker } from "react-colorful";
 import { emit } from "@tauri-apps/api/event";
+import SettingSlider from "./components/SettingSlider";
+import SettingColor from "./components/SettingColor";
+import SettingSwitch from "./components/SettingSwitch";
+import Profiles from "./Profiles";
+import Highlighter from "./Highlighter";
 
 const listItemSx: SxProps<Theme> = {
   display: "flex",
@@ -22,30 +41,70 @@ const listItemSx: SxProps<Theme> = {
 const TEXT_SIZE_MIN = 1;
 const TEXT_SIZE_MAX = 50;
 
+interface NumberSetting {
+  key:
+    | "line_height"
+    | "letter_spacing"
+    | "paragraph_spacing"
+    | "first_line_indent";
+  label: string;
+  min: number;
+  max: number;
+  step: number;
+}
+
+const numberSettings: NumberSetting[] = [
+  { key: "line_height", label: "lineHeight", min: 0.8, max: 3, step: 0.1 },
+  {
+    key: "letter_spacing",
+    label: "letterSpacing",
+    min: -0.2,
+    max: 1,
+    step: 0.01,
+  },
+  {
+    key: "paragraph_spacing",
+    label: "paragraphSpacing",
+    min: 0,
+    max: 3,
+    step: 0.1,
+  },
+  {
+    key: "first_line_indent",
+    label: "firstLineIndent",
+    min: 0,
+    max: 4,
+    step: 0.5,
+  },
+];
+
+/** Turn `line_height` into `line-height-changed` and `update_line_height`. */
+const toEventName = (key: string) => `${key.replace(/_/g, "-")}-changed`;
+const toCommandName = (key: string) => `update_${key}`;
+const toCamelCase = (key: string) =>
+  key.replace(/_(\w)/g, (_match, c: string) => c.toUpperCase());
+
+/** Quote a family name so that it is a valid CSS `font-family` value. */
+const toFontFamilyValue = (family: string) =>
+  /^[\w-]+$/.test(family) ? family : `"${family.replace(/"/g, '\\"')}"`;
+
 export default function Appearance() {
   const { t } = useTranslation();
   const [ready, setReady] = useState(false);
-  const [textSize, setTextSize] = useState(16);
-  const [textColor, setTextColor] = useState("#1cb8c3ff");
-  const [paletteOpen, setPaletteOpen] = useState(false);
-  const paletteRef = useRef<HTMLElement>(null);
-  const colorBoxRef = useRef<HTMLElement>(null);
+  const [appearance, setAppearance] = useState<Config["appearance"] | null>(
+    null
+  );
+  const [camouflage, setCamouflage] = useState<Camouflage>("None");
+  const [fontFamily, setFontFamily] = useState("");
+  const [systemFontFamilies, setSystemFontFamilies] = useState<string[]>([]);
+  const [textStroke, setTextStroke] = useState<TextStroke | null>(null);
+  const [textShadow, setTextShadow] = useState<TextShadow | null>(null);
+  const [backgroundPlate, setBackgroundPlate] =
+    useState<BackgroundPlate | null>(null);
+  const [logStream, setLogStream] = useState<LogStreamOptions | null>(null);
+  const [readerAnchor, setReaderAnchor] = useState<ReaderAnchor | null>(null);
 
   useEffect(() => {
-    const handleClickPaletteOutside = (event: MouseEvent) => {
-      if (!(event.target instanceof HTMLElement)) {
-        console.error("event.target is not HTMLElement");
-        return;
-      }
-      if (
-        paletteRef.current?.contains(event.target) ||
-        colorBoxRef.current?.contains(event.target)
-      ) {
-        return;
-      }
-      setPaletteOpen(false);
-    };
-
     invokeCommand<Config>("get_config").then((config) => {
       if (typeof config === "undefined") {
         console.error(
@@ -54,143 +113,358 @@ export default function Appearance() {
         return;
       }
       const appearance = config.appearance;
-      setTextSize(appearance.text_size);
-      setTextColor(appearance.text_color);
-      document.addEventListener("mouseup", handleClickPaletteOutside);
+      setAppearance(appearance);
+      setCamouflage(appearance.camouflage);
+      setFontFamily(appearance.font_family);
+      setTextStroke(appearance.text_stroke);
+      setTextShadow(appearance.text_shadow);
+      setBackgroundPlate(appearance.background_plate);
+      setLogStream(appearance.log_stream);
+      setReaderAnchor(appearance.reader_anchor);
       invokeCommand("start_changing_styles");
       setReady(true);
     });
 
+    invokeCommand<string[]>("get_system_font_families").then((families) => {
+      setSystemFontFamilies(families ?? []);
+    });
+
     return () => {
-      document.removeEventListener("mouseup", handleClickPaletteOutside);
       invokeCommand("end_changing_styles");
       invokeCommand("persist_appearance");
     };
   }, []);
 
-  const onTextSizeSliderChange = (_event: Event, value: number | number[]) => {
-    setTextSize(value as number);
-    emit("text-size-changed", value as number);
+  const callCommandToUpdateTextSize = (textSize: number) => {
+    invokeCommand("update_text_size", { textSize });
   };
 
-  const onTextSizeInputChange = (
-    event: React.ChangeEvent<HTMLInputElement>
-  ) => {
-    const newTextSize = Number(event.target.value) || 0;
-    setTextSize(newTextSize);
-    emit("text-size-changed", newTextSize);
+  const callCommandToUpdateTextColor = (textColor: string) => {
+    invokeCommand("update_text_color", { textColor });
   };
 
-  const onTextSizeInputBlur = () => {
-    let newTextSize = textSize;
-    if (textSize < TEXT_SIZE_MIN) {
-      newTextSize = TEXT_SIZE_MIN;
-    } else if (textSize > TEXT_SIZE_MAX) {
-      newTextSize = TEXT_SIZE_MAX;
+  const onCamouflageChange = (camouflage: Camouflage) => {
+    setCamouflage(camouflage);
+    emit("camouflage-changed", camouflage);
+    invokeCommand("update_camouflage", { camouflage });
+  };
+
+  const onFontFamilyChange = (fontFamily: string) => {
+    setFontFamily(fontFamily);
+    emit("font-family-changed", fontFamily);
+    invokeCommand("update_font_family", { fontFamily });
+  };
+
+  // Each of these previews every change, but is only persisted once the
+  // change is settled.
+  const changeTextStroke = (change: Partial<TextStroke>, commit: boolean) => {
+    const newTextStroke = { ...textStroke!, ...change };
+    setTextStroke(newTextStroke);
+    emit("text-stroke-changed", newTextStroke);
+    if (commit) {
+      invokeCommand("update_text_stroke", { textStroke: newTextStroke });
     }
-    setTextSize(newTextSize);
-    emit("text-size-changed", newTextSize);
-    callCommandToUpdateTextSize(newTextSize);
   };
 
-  const onTextColorBoxClick = () => {
-    setPaletteOpen((open) => !open);
+  const changeTextShadow = (change: Partial<TextShadow>, commit: boolean) => {
+    const newTextShadow = { ...textShadow!, ...change };
+    setTextShadow(newTextShadow);
+    emit("text-shadow-changed", newTextShadow);
+    if (commit) {
+      invokeCommand("update_text_shadow", { textShadow: newTextShadow });
+    }
   };
 
-  const onColorSliderChange = (textColor: string) => {
-    setTextColor(textColor);
-    emit("text-color-changed", textColor);
+  const changeBackgroundPlate = (
+    change: Partial<BackgroundPlate>,
+    commit: boolean
+  ) => {
+    const newBackgroundPlate = { ...backgroundPlate!, ...change };
+    setBackgroundPlate(newBackgroundPlate);
+    emit("background-plate-changed", newBackgroundPlate);
+    if (commit) {
+      invokeCommand("update_background_plate", {
+        backgroundPlate: newBackgroundPlate,
+      });
+    }
   };
 
-  const callCommandToUpdateTextSize = (textSize: number) => {
-    invokeCommand("update_text_size", { textSize });
+  // The reader is moved by the backend, so there is no preview either.
+  const changeReaderAnchor = (change: Partial<ReaderAnchor>) => {
+    const newReaderAnchor = { ...readerAnchor!, ...change };
+    setReaderAnchor(newReaderAnchor);
+    invokeCommand("update_reader_anchor", { readerAnchor: newReaderAnchor });
   };
 
-  const callCommandToUpdateTextColor = (textColor: string) => {
-    invokeCommand("update_text_color", { textColor });
+  // The content is rebuilt by the backend, so there is no preview.
+  const changeLogStream = (change: Partial<LogStreamOptions>) => {
+    const newLogStream = { ...logStream!, ...change };
+    setLogStream(newLogStream);
+    invokeCommand("update_log_stream", { logStream: newLogStream });
   };
 
-  if (!ready) {
+  if (
+    !ready ||
+    appearance === null ||
+    textStroke === null ||
+    textShadow === null ||
+    backgroundPlate === null ||
+    logStream === null ||
+    readerAnchor === null
+  ) {
     return <></>;
   }
 
   return (
     <Box>
+      <Profiles />
+
+      <Highlighter />
+
       <List>
+        <ListSubheader>{t("currentAppearance")}</ListSubheader>
         <ListItem sx={listItemSx}>
-          <Box>{t("textSize")}</Box>
-          <Box sx={{ display: "flex" }}>
-            <Slider
-              value={textSize}
-              onChange={onTextSizeSliderChange}
-              min={TEXT_SIZE_MIN}
-              max={TEXT_SIZE_MAX}
-              step={1}
-              shiftStep={4}
-              sx={{ width: "150px" }}
-              onChangeCommitted={(_, value) => {
-                callCommandToUpdateTextSize(value as number);
-              }}
-            />
-
-            <Input
-              value={textSize}
-              size="small"
-              inputProps={{
-                step: 1,
-                min: TEXT_SIZE_MIN,
-                max: TEXT_SIZE_MAX,
-                type: "number",
-              }}
-              onChange={onTextSizeInputChange}
-              onBlur={onTextSizeInputBlur}
-              sx={{ ml: "30px" }}
-            />
-          </Box>
+          <Box>{t("camouflage")}</Box>
+          <Select
+            value={camouflage}
+            size="small"
+            onChange={(event) =>
+              onCamouflageChange(event.target.value as Camouflage)
+            }
+          >
+            {camouflages.map((value) => (
+              <MenuItem key={value} value={value}>
+                {t(`camouflage${value}`)}
+              </MenuItem>
+            ))}
+          </Select>
         </ListItem>
 
+        <SettingSlider
+          defaultValue={appearance.text_size}
+          min={TEXT_SIZE_MIN}
+          max={TEXT_SIZE_MAX}
+          step={1}
+          onChange={(value) => emit("text-size-changed", value)}
+          onCommit={callCommandToUpdateTextSize}
+        >
+          {t("textSize")}
+        </SettingSlider>
+
+        <SettingColor
+          defaultValue={appearance.text_color}
+          onChange={(textColor) => emit("text-color-changed", textColor)}
+          onCommit={callCommandToUpdateTextColor}
+        >
+          {t("textColorAndTransparency")}
+        </SettingColor>
+
         <ListItem sx={listItemSx}>
-          <Box>{t("textColorAndTransparency")}</Box>
-          <Box
-            sx={{
-              padding: "8px",
-              border: "2px dashed black",
-              borderRadius: "5px",
-              width: "75px",
-              height: "50px",
-              position: "relative",
+          <Box>{t("fontFamily")}</Box>
+          <Autocomplete
+            freeSolo
+            options={systemFontFamilies}
+            inputValue={fontFamily}
+            onInputChange={(_event, value, reason) => {
+              if (reason === "input" || reason === "clear") {
+                setFontFamily(value);
+              }
             }}
-          >
-            <Box
-              onClick={onTextColorBoxClick}
-              ref={colorBoxRef}
-              sx={{
-                width: "100%",
-                height: "100%",
-                bgcolor: textColor,
-                borderRadius: "5px",
-                cursor: "pointer",
-              }}
-            />
-            {paletteOpen && (
-              <Box
-                ref={paletteRef}
-                sx={{
-                  position: "absolute",
-                  top: "60px",
-                  left: "50px",
-                  transform: "translateX(-100%)",
-                }}
-              >
-                <HexAlphaColorPicker
-                  color={textColor}
-                  onChange={onColorSliderChange}
-                  onMouseUp={() => callCommandToUpdateTextColor(textColor)}
-                />
-              </Box>
+            onChange={(_event, value) =>
+              onFontFamilyChange(value ? toFontFamilyValue(value) : "")
+            }
+            onBlur={() => onFontFamilyChange(fontFamily)}
+            renderOption={(props, family) => (
+              <li {...props} key={family}>
+                <span style={{ fontFamily: toFontFamilyValue(family) }}>
+                  {family}
+                </span>
+              </li>
+            )}
+            renderInput={(params) => (
+              <TextField
+                {...params}
+                size="small"
+                placeholder={t("defaultFont")}
+              />
             )}
-          </Box>
+            sx={{ width: "300px" }}
+          />
         </ListItem>
+
+        {numberSettings.map((setting) => (
+          <SettingSlider
+            key={setting.key}
+            defaultValue={appearance[setting.key]}
+            min={setting.min}
+            max={setting.max}
+            step={setting.step}
+            onChange={(value) => emit(toEventName(setting.key), value)}
+            onCommit={(value) =>
+              invokeCommand(toCommandName(setting.key), {
+                [toCamelCase(setting.key)]: value,
+              })
+            }
+          >
+            {t(setting.label)}
+          </SettingSlider>
+        ))}
+
+        <ListSubheader>{t("textStroke")}</ListSubheader>
+        <SettingSwitch
+          defaultChecked={textStroke.enabled}
+          onChange={(enabled) => changeTextStroke({ enabled }, true)}
+        >
+          {t("enabled")}
+        </SettingSwitch>
+        <SettingSlider
+          defaultValue={textStroke.width}
+          min={0.5}
+          max={5}
+          step={0.5}
+          onChange={(width) => changeTextStroke({ width }, false)}
+          onCommit={(width) => changeTextStroke({ width }, true)}
+        >
+          {t("widthPx")}
+        </SettingSlider>
+        <SettingColor
+          defaultValue={textStroke.color}
+          onChange={(color) => changeTextStroke({ color }, false)}
+          onCommit={(color) => changeTextStroke({ color }, true)}
+        >
+          {t("colorAndTransparency")}
+        </SettingColor>
+
+        <ListSubheader>{t("textShadow")}</ListSubheader>
+        <SettingSwitch
+          defaultChecked={textShadow.enabled}
+          onChange={(enabled) => changeTextShadow({ enabled }, true)}
+        >
+          {t("enabled")}
+        </SettingSwitch>
+        <SettingSlider
+          defaultValue={textShadow.offset_x}
+          min={-10}
+          max={10}
+          step={0.5}
+          onChange={(offset_x) => changeTextShadow({ offset_x }, false)}
+          onCommit={(offset_x) => changeTextShadow({ offset_x }, true)}
+        >
+          {t("offsetXPx")}
+        </SettingSlider>
+        <SettingSlider
+          defaultValue={textShadow.offset_y}
+          min={-10}
+          max={10}
+          step={0.5}
+          onChange={(offset_y) => changeTextShadow({ offset_y }, false)}
+          onCommit={(offset_y) => changeTextShadow({ offset_y }, true)}
+        >
+          {t("offsetYPx")}
+        </SettingSlider>
+        <SettingSlider
+          defaultValue={textShadow.blur}
+          min={0}
+          max={20}
+          step={0.5}
+          onChange={(blur) => changeTextShadow({ blur }, false)}
+          onCommit={(blur) => changeTextShadow({ blur }, true)}
+        >
+          {t("blurPx")}
+        </SettingSlider>
+        <SettingColor
+          defaultValue={textShadow.color}
+          onChange={(color) => changeTextShadow({ color }, false)}
+          onCommit={(color) => changeTextShadow({ color }, true)}
+        >
+          {t("colorAndTransparency")}
+        </SettingColor>
+
+        <ListSubheader>{t("backgroundPlate")}</ListSubheader>
+        <SettingSwitch
+          defaultChecked={backgroundPlate.enabled}
+          onChange={(enabled) => changeBackgroundPlate({ enabled }, true)}
+        >
+          {t("enabled")}
+        </SettingSwitch>
+        <SettingColor
+          defaultValue={backgroundPlate.color}
+          onChange={(color) => changeBackgroundPlate({ color }, false)}
+          onCommit={(color) => changeBackgroundPlate({ color }, true)}
+        >
+          {t("colorAndTransparency")}
+        </SettingColor>
+        <SettingSlider
+          defaultValue={backgroundPlate.corner_radius}
+          min={0}
+          max={30}
+          step={1}
+          onChange={(corner_radius) =>
+            changeBackgroundPlate({ corner_radius }, false)
+          }
+          onCommit={(corner_radius) =>
+            changeBackgroundPlate({ corner_radius }, true)
+          }
+        >
+          {t("cornerRadiusPx")}
+        </SettingSlider>
+
+        <ListSubheader>{t("readerPosition")}</ListSubheader>
+        <ListItem sx={listItemSx}>
+          <Box>{t("anchor")}</Box>
+          <Select
+            value={readerAnchor.anchor}
+            size="small"
+            onChange={(event) =>
+              changeReaderAnchor({ anchor: event.target.value as Anchor })
+            }
+          >
+            {anchors.map((value) => (
+              <MenuItem key={value} value={value}>
+                {t(`anchor${value}`)}
+              </MenuItem>
+            ))}
+          </Select>
+        </ListItem>
+        {readerAnchor.anchor !== "None" && (
+          <>
+            <SettingSlider
+              defaultValue={readerAnchor.margin_x}
+              min={-300}
+              max={300}
+              step={1}
+              onCommit={(margin_x) => changeReaderAnchor({ margin_x })}
+            >
+              {t("marginXPx")}
+            </SettingSlider>
+            <SettingSlider
+              defaultValue={readerAnchor.margin_y}
+              min={-300}
+              max={300}
+              step={1}
+              onCommit={(margin_y) => changeReaderAnchor({ margin_y })}
+            >
+              {t("marginYPx")}
+            </SettingSlider>
+          </>
+        )}
+
+        <ListSubheader>{t("logStream")}</ListSubheader>
+        <SettingSwitch
+          defaultChecked={logStream.enabled}
+          onChange={(enabled) => changeLogStream({ enabled })}
+        >
+          {t("logStreamExplain")}
+        </SettingSwitch>
+        <SettingSlider
+          defaultValue={logStream.max_line_length}
+          min={20}
+          max={200}
+          step={10}
+          onCommit={(max_line_length) => changeLogStream({ max_line_length })}
+        >
+          {t("maxLogLineLength")}
+        </SettingSlider>
       </List>
     </Box>
   );
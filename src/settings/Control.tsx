@@ -56,6 +56,9 @@ export default function Control({ isTrialVersion }: Props) {
   const [kbShowHide, setKbShowHide] = useState<KeyButton>("");
   const [kbNextPage, setKbNextPage] = useState<KeyButton>("");
   const [kbPrevPage, setKbPrevPage] = useState<KeyButton>("");
+  const [kbNextProfile, setKbNextProfile] = useState<KeyButton | null>(null);
+  const [kbAddBookmark, setKbAddBookmark] = useState<KeyButton | null>(null);
+  const [kbGoBack, setKbGoBack] = useState<KeyButton | null>(null);
 
   useEffect(() => {
     invokeCommand<Config>("get_config").then((config) => {
@@ -70,6 +73,9 @@ export default function Control({ isTrialVersion }: Props) {
       setKbShowHide(basicConfig.show_hide);
       setKbNextPage(basicConfig.next_page);
       setKbPrevPage(basicConfig.prev_page);
+      setKbNextProfile(basicConfig.next_profile);
+      setKbAddBookmark(basicConfig.add_bookmark);
+      setKbGoBack(basicConfig.go_back);
       setReady(true);
     });
   }, []);
@@ -91,6 +97,16 @@ export default function Control({ isTrialVersion }: Props) {
     };
   };
 
+  const createOnClearKeyButton = (
+    name: string,
+    setter: (keyButton: KeyButton | null) => void
+  ) => {
+    return () => {
+      setter(null);
+      invokeCommand("unbind_basic_control_key_button", { name });
+    };
+  };
+
   if (!ready) {
     return <></>;
   }
@@ -134,6 +150,42 @@ export default function Control({ isTrialVersion }: Props) {
         >
           {t("prevPage")}
         </SettingShortcutSingleKey>
+        <SettingShortcutSingleKey
+          name="next_profile"
+          keyButton={kbNextProfile}
+          onChangeKeyButton={createOnChangeCode(
+            "next_profile",
+            setKbNextProfile
+          )}
+          onClearKeyButton={createOnClearKeyButton(
+            "next_profile",
+            setKbNextProfile
+          )}
+        >
+          {t("nextProfile")}
+        </SettingShortcutSingleKey>
+        <SettingShortcutSingleKey
+          name="add_bookmark"
+          keyButton={kbAddBookmark}
+          onChangeKeyButton={createOnChangeCode(
+            "add_bookmark",
+            setKbAddBookmark
+          )}
+          onClearKeyButton={createOnClearKeyButton(
+            "add_bookmark",
+            setKbAddBookmark
+          )}
+        >
+          {t("addBookmark")}
+        </SettingShortcutSingleKey>
+        <SettingShortcutSingleKey
+          name="go_back"
+          keyButton={kbGoBack}
+          onChangeKeyButton={createOnChangeCode("go_back", setKbGoBack)}
+          onClearKeyButton={createOnClearKeyButton("go_back", setKbGoBack)}
+        >
+          {t("goBack")}
+        </SettingShortcutSingleKey>
       </List>
 
       {isTrialVersion && (
@@ -0,0 +1,168 @@
+import {
+  Box,
+  Button,
+  Dialog,
+  DialogContent,
+  IconButton,
+  List,
+  ListItem,
+  TextField,
+  Tooltip,
+  Typography,
+} from "@mui/material";
+import { DeleteOutlined, LoginOutlined } from "@mui/icons-material";
+import { listen } from "@tauri-apps/api/event";
+import { Dispatch, SetStateAction, useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { Bookmark, invokeCommand, LibraryBook } from "../util";
+
+interface Props {
+  book?: LibraryBook;
+  onClose: () => void;
+  setBooks: Dispatch<SetStateAction<LibraryBook[]>>;
+}
+
+export default function DialogBookmarks({ book, onClose, setBooks }: Props) {
+  const { t } = useTranslation();
+  const [bookmarks, setBookmarks] = useState<Bookmark[]>([]);
+  const [newNote, setNewNote] = useState("");
+
+  const bookId = book?.id;
+
+  useEffect(() => {
+    if (typeof bookId === "undefined") {
+      return;
+    }
+    const refresh = () => {
+      invokeCommand<Bookmark[]>("get_bookmarks", { id: bookId }).then(
+        (bookmarks) => setBookmarks(bookmarks ?? [])
+      );
+    };
+    refresh();
+    setNewNote("");
+
+    // Bookmarks are also added with a hotkey while reading.
+    const unlisten = listen<string>("bookmarks-changed", (event) => {
+      if (event.payload === bookId) {
+        refresh();
+      }
+    });
+    return () => {
+      unlisten.then((unlisten) => unlisten());
+    };
+  }, [bookId]);
+
+  if (typeof book === "undefined") {
+    return <></>;
+  }
+
+  const onAdd = async () => {
+    const bookmark = await invokeCommand<Bookmark>("add_bookmark", {
+      id: book.id,
+      note: newNote,
+    });
+    if (typeof bookmark !== "undefined") {
+      setNewNote("");
+    }
+  };
+
+  const onNoteChange = (bookmark: Bookmark, note: string) => {
+    invokeCommand("update_bookmark_note", {
+      id: book.id,
+      bookmarkId: bookmark.id,
+      note,
+    });
+  };
+
+  const onJump = async (bookmark: Bookmark) => {
+    const books = await invokeCommand<LibraryBook[]>("jump_to_bookmark", {
+      id: book.id,
+      bookmarkId: bookmark.id,
+    });
+    if (typeof books === "undefined") {
+      return;
+    }
+    setBooks(books);
+    onClose();
+  };
+
+  const onRemove = async (bookmark: Bookmark) => {
+    const result = await invokeCommand("remove_bookmark", {
+      id: book.id,
+      bookmarkId: bookmark.id,
+    });
+    if (result !== undefined) {
+      setBookmarks((bookmarks) =>
+        bookmarks.filter(({ id }) => id !== bookmark.id)
+      );
+    }
+  };
+
+  const percentage = (offset: number) =>
+    book.total_character_count === 0
+      ? 0
+      : Math.min(100, (offset / book.total_character_count) * 100);
+
+  return (
+    <Dialog open onClose={onClose} fullWidth>
+      <DialogContent>
+        <Box sx={{ display: "flex", alignItems: "center" }}>
+          <TextField
+            value={newNote}
+            size="small"
+            autoComplete="off"
+            placeholder={t("bookmarkNote")}
+            onChange={(event) => setNewNote(event.target.value)}
+            sx={{ flex: 1 }}
+          />
+          <Button variant="outlined" onClick={onAdd} sx={{ ml: "10px" }}>
+            {t("addBookmarkHere")}
+          </Button>
+        </Box>
+
+        {bookmarks.length === 0 && (
+          <Typography variant="body2" sx={{ mt: "20px" }}>
+            {t("noBookmarks")}
+          </Typography>
+        )}
+        <List>
+          {bookmarks.map((bookmark) => (
+            <ListItem
+              key={bookmark.id}
+              disableGutters
+              sx={{ alignItems: "flex-start", gap: "10px" }}
+            >
+              <Box sx={{ flex: 1, minWidth: 0 }}>
+                <Typography variant="caption">
+                  {percentage(bookmark.offset).toFixed(1)}%
+                  {" · "}
+                  {new Date(bookmark.created_time * 1000).toLocaleString()}
+                </Typography>
+                <Typography variant="body2" noWrap>
+                  {bookmark.snippet}
+                </Typography>
+                <TextField
+                  defaultValue={bookmark.note ?? ""}
+                  size="small"
+                  variant="standard"
+                  autoComplete="off"
+                  placeholder={t("bookmarkNote")}
+                  onBlur={(event) => onNoteChange(bookmark, event.target.value)}
+                  fullWidth
+                />
+              </Box>
+              <Tooltip title={t("jumpToBookmark")}>
+                <IconButton onClick={() => onJump(bookmark)}>
+                  <LoginOutlined />
+                </IconButton>
+              </Tooltip>
+              <IconButton onClick={() => onRemove(bookmark)}>
+                <DeleteOutlined />
+              </IconButton>
+            </ListItem>
+          ))}
+        </List>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,158 @@
+import {
+  Box,
+  Button,
+  Dialog,
+  DialogActions,
+  DialogContent,
+  IconButton,
+  List,
+  ListItem,
+  TextField,
+  Typography,
+} from "@mui/material";
+import { DeleteOutlined } from "@mui/icons-material";
+import { listen } from "@tauri-apps/api/event";
+import { save } from "@tauri-apps/plugin-dialog";
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { ExportFormat, Highlight, invokeCommand, LibraryBook } from "../util";
+
+interface Props {
+  book?: LibraryBook;
+  onClose: () => void;
+}
+
+const exportExtensions: Record<ExportFormat, string> = {
+  Markdown: "md",
+  Json: "json",
+};
+
+export default function DialogHighlights({ book, onClose }: Props) {
+  const { t } = useTranslation();
+  const [highlights, setHighlights] = useState<Highlight[]>([]);
+
+  const bookId = book?.id;
+
+  useEffect(() => {
+    if (typeof bookId === "undefined") {
+      return;
+    }
+    const refresh = () => {
+      invokeCommand<Highlight[]>("get_highlights", { id: bookId }).then(
+        (highlights) => setHighlights(highlights ?? [])
+      );
+    };
+    refresh();
+
+    // Highlights are added in the appearance page while the dialog is open.
+    const unlisten = listen<string>("highlights-changed", (event) => {
+      if (event.payload === bookId) {
+        refresh();
+      }
+    });
+    return () => {
+      unlisten.then((unlisten) => unlisten());
+    };
+  }, [bookId]);
+
+  if (typeof book === "undefined") {
+    return <></>;
+  }
+
+  const onNoteChange = (highlight: Highlight, note: string) => {
+    invokeCommand("update_highlight_note", {
+      id: book.id,
+      highlightId: highlight.id,
+      note,
+    });
+  };
+
+  const onRemove = async (highlight: Highlight) => {
+    const result = await invokeCommand("remove_highlight", {
+      id: book.id,
+      highlightId: highlight.id,
+    });
+    if (result !== undefined) {
+      setHighlights((highlights) =>
+        highlights.filter(({ id }) => id !== highlight.id)
+      );
+    }
+  };
+
+  const onExport = async (format: ExportFormat) => {
+    const extension = exportExtensions[format];
+    const path = await save({
+      title: t("exportAnnotations"),
+      defaultPath: `${book.title}.${extension}`,
+      filters: [{ name: format, extensions: [extension] }],
+    });
+    if (path === null) {
+      return;
+    }
+    invokeCommand("export_annotations", { id: book.id, format, path });
+  };
+
+  const percentage = (offset: number) =>
+    book.total_character_count === 0
+      ? 0
+      : Math.min(100, (offset / book.total_character_count) * 100);
+
+  return (
+    <Dialog open onClose={onClose} fullWidth>
+      <DialogContent>
+        {highlights.length === 0 && (
+          <Typography variant="body2">{t("noHighlights")}</Typography>
+        )}
+        <List>
+          {highlights.map((highlight) => (
+            <ListItem
+              key={highlight.id}
+              disableGutters
+              sx={{ alignItems: "flex-start", gap: "10px" }}
+            >
+              <Box sx={{ flex: 1, minWidth: 0 }}>
+                <Typography variant="caption">
+                  {percentage(highlight.start).toFixed(1)}%
+                  {" · "}
+                  {new Date(highlight.created_time * 1000).toLocaleString()}
+                </Typography>
+                <Typography
+                  variant="body2"
+                  sx={{
+                    borderLeft: 3,
+                    borderColor: "primary.main",
+                    pl: "10px",
+                    whiteSpace: "pre-wrap",
+                    wordBreak: "break-word",
+                  }}
+                >
+                  {highlight.text}
+                </Typography>
+                <TextField
+                  defaultValue={highlight.note ?? ""}
+                  size="small"
+                  variant="standard"
+                  autoComplete="off"
+                  placeholder={t("highlightNote")}
+                  onBlur={(event) =>
+                    onNoteChange(highlight, event.target.value)
+                  }
+                  fullWidth
+                />
+              </Box>
+              <IconButton onClick={() => onRemove(highlight)}>
+                <DeleteOutlined />
+              </IconButton>
+            </ListItem>
+          ))}
+        </List>
+      </DialogContent>
+      <DialogActions>
+        <Button onClick={() => onExport("Markdown")}>
+          {t("exportMarkdown")}
+        </Button>
+        <Button onClick={() => onExport("Json")}>{t("exportJson")}</Button>
+      </DialogActions>
+    </Dialog>
+  );
+}
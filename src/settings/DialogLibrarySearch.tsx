@@ -0,0 +1,108 @@
+import {
+  Box,
+  Button,
+  Dialog,
+  DialogContent,
+  List,
+  ListItem,
+  ListItemButton,
+  ListSubheader,
+  TextField,
+  Typography,
+} from "@mui/material";
+import { Dispatch, SetStateAction, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { invokeCommand, LibraryBook, LibrarySearchHit } from "../util";
+
+interface Props {
+  open: boolean;
+  onClose: () => void;
+  setBooks: Dispatch<SetStateAction<LibraryBook[]>>;
+}
+
+export default function DialogLibrarySearch({
+  open,
+  onClose,
+  setBooks,
+}: Props) {
+  const { t } = useTranslation();
+  const [query, setQuery] = useState("");
+  const [hits, setHits] = useState<LibrarySearchHit[] | null>(null);
+
+  const onSearch = async () => {
+    const hits = await invokeCommand<LibrarySearchHit[]>("search_library", {
+      query,
+    });
+    setHits(hits ?? null);
+  };
+
+  const onJump = async (bookId: string, offset: number) => {
+    const books = await invokeCommand<LibraryBook[]>("jump_to_offset", {
+      id: bookId,
+      offset,
+    });
+    if (typeof books === "undefined") {
+      return;
+    }
+    setBooks(books);
+    onClose();
+  };
+
+  return (
+    <Dialog open={open} onClose={onClose} fullWidth>
+      <DialogContent>
+        <Box sx={{ display: "flex", alignItems: "center" }}>
+          <TextField
+            value={query}
+            size="small"
+            autoComplete="off"
+            autoFocus
+            placeholder={t("searchLibrary")}
+            onChange={(event) => setQuery(event.target.value)}
+            onKeyDown={(event) => {
+              if (event.key === "Enter") {
+                onSearch();
+              }
+            }}
+            sx={{ flex: 1 }}
+          />
+          <Button variant="outlined" onClick={onSearch} sx={{ ml: "10px" }}>
+            {t("search")}
+          </Button>
+        </Box>
+
+        {hits !== null && hits.length === 0 && (
+          <Typography variant="body2" sx={{ mt: "20px" }}>
+            {t("noSearchHits")}
+          </Typography>
+        )}
+        <List>
+          {hits?.map((hit) => (
+            <Box key={hit.book_id}>
+              <ListSubheader disableGutters>
+                {hit.title}
+                {" · "}
+                {hit.is_truncated
+                  ? t("searchMatchesAtLeast", { count: hit.match_count })
+                  : t("searchMatches", { count: hit.match_count })}
+              </ListSubheader>
+              {hit.matches.map((match) => (
+                <ListItem key={match.start} disablePadding>
+                  <ListItemButton
+                    onClick={() => onJump(hit.book_id, match.start)}
+                  >
+                    <Typography variant="body2" noWrap>
+                      {match.before}
+                      <Box component="mark">{match.text}</Box>
+                      {match.after}
+                    </Typography>
+                  </ListItemButton>
+                </ListItem>
+              ))}
+            </Box>
+          ))}
+        </List>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,145 @@
+import {
+  Box,
+  Button,
+  Checkbox,
+  Dialog,
+  DialogContent,
+  FormControlLabel,
+  List,
+  ListItem,
+  ListItemButton,
+  TextField,
+  Typography,
+} from "@mui/material";
+import { Dispatch, SetStateAction, useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import {
+  invokeCommand,
+  LibraryBook,
+  SearchOptions,
+  SearchResult,
+} from "../util";
+
+interface Props {
+  book?: LibraryBook;
+  onClose: () => void;
+  setBooks: Dispatch<SetStateAction<LibraryBook[]>>;
+}
+
+const searchOptionNames: (keyof SearchOptions)[] = [
+  "ignore_case",
+  "ignore_width",
+  "regex",
+];
+
+const searchOptionKeys: Record<keyof SearchOptions, string> = {
+  ignore_case: "searchIgnoreCase",
+  ignore_width: "searchIgnoreWidth",
+  regex: "searchRegex",
+};
+
+export default function DialogSearch({ book, onClose, setBooks }: Props) {
+  const { t } = useTranslation();
+  const [query, setQuery] = useState("");
+  const [options, setOptions] = useState<SearchOptions>({
+    ignore_case: true,
+    ignore_width: true,
+    regex: false,
+  });
+  const [result, setResult] = useState<SearchResult | null>(null);
+
+  const bookId = book?.id;
+
+  useEffect(() => {
+    setResult(null);
+  }, [bookId]);
+
+  if (typeof book === "undefined") {
+    return <></>;
+  }
+
+  const onSearch = async () => {
+    const result = await invokeCommand<SearchResult>("search_book", {
+      id: book.id,
+      query,
+      options,
+    });
+    setResult(result ?? null);
+  };
+
+  const onJump = async (offset: number) => {
+    const books = await invokeCommand<LibraryBook[]>("jump_to_offset", {
+      id: book.id,
+      offset,
+    });
+    if (typeof books === "undefined") {
+      return;
+    }
+    setBooks(books);
+    onClose();
+  };
+
+  return (
+    <Dialog open onClose={onClose} fullWidth>
+      <DialogContent>
+        <Box sx={{ display: "flex", alignItems: "center" }}>
+          <TextField
+            value={query}
+            size="small"
+            autoComplete="off"
+            autoFocus
+            placeholder={t("searchInBook")}
+            onChange={(event) => setQuery(event.target.value)}
+            onKeyDown={(event) => {
+              if (event.key === "Enter") {
+                onSearch();
+              }
+            }}
+            sx={{ flex: 1 }}
+          />
+          <Button variant="outlined" onClick={onSearch} sx={{ ml: "10px" }}>
+            {t("search")}
+          </Button>
+        </Box>
+        <Box>
+          {searchOptionNames.map((name) => (
+            <FormControlLabel
+              key={name}
+              control={
+                <Checkbox
+                  size="small"
+                  checked={options[name]}
+                  onChange={(event) =>
+                    setOptions({ ...options, [name]: event.target.checked })
+                  }
+                />
+              }
+              label={t(searchOptionKeys[name])}
+            />
+          ))}
+        </Box>
+
+        {result !== null && (
+          <Typography variant="body2" sx={{ mt: "10px" }}>
+            {result.is_truncated
+              ? t("searchMatchesTruncated", { count: result.matches.length })
+              : t("searchMatches", { count: result.matches.length })}
+          </Typography>
+        )}
+        <List>
+          {result?.matches.map((match) => (
+            <ListItem key={match.start} disablePadding>
+              <ListItemButton onClick={() => onJump(match.start)}>
+                <Typography variant="body2" noWrap>
+                  {match.before}
+                  <Box component="mark">{match.text}</Box>
+                  {match.after}
+                </Typography>
+              </ListItemButton>
+            </ListItem>
+          ))}
+        </List>
+      </DialogContent>
+    </Dialog>
+  );
+}
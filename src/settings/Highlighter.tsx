@@ -0,0 +1,99 @@
+import {
+  Box,
+  Button,
+  List,
+  ListItem,
+  ListSubheader,
+  TextField,
+  Typography,
+} from "@mui/material";
+import { emit, listen } from "@tauri-apps/api/event";
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { Highlight, invokeCommand, ReaderSelection } from "../util";
+import SettingSwitch from "./components/SettingSwitch";
+
+/**
+ * Highlight text selected in the reader, which only gets cursor events while
+ * its appearance is being changed on this page.
+ */
+export default function Highlighter() {
+  const { t } = useTranslation();
+  const [selection, setSelection] = useState<ReaderSelection | null>(null);
+  const [note, setNote] = useState("");
+
+  useEffect(() => {
+    const unlisten = listen<ReaderSelection | null>(
+      "reader-selection-changed",
+      (event) => setSelection(event.payload)
+    );
+    return () => {
+      unlisten.then((unlisten) => unlisten());
+      emit("highlight-mode-changed", false);
+    };
+  }, []);
+
+  const onHighlightModeChange = (highlighting: boolean) => {
+    emit("highlight-mode-changed", highlighting);
+    if (!highlighting) {
+      setSelection(null);
+    }
+  };
+
+  const onSave = async () => {
+    if (selection === null) {
+      return;
+    }
+    const highlight = await invokeCommand<Highlight>("add_highlight", {
+      id: selection.bookId,
+      start: selection.start,
+      end: selection.end,
+      note,
+    });
+    if (typeof highlight !== "undefined") {
+      setSelection(null);
+      setNote("");
+    }
+  };
+
+  return (
+    <List>
+      <ListSubheader>{t("highlights")}</ListSubheader>
+      <SettingSwitch onChange={onHighlightModeChange}>
+        {t("highlightModeExplain")}
+      </SettingSwitch>
+      {selection !== null && (
+        <ListItem sx={{ display: "block" }}>
+          <Typography
+            variant="body2"
+            sx={{
+              borderLeft: 3,
+              borderColor: "primary.main",
+              pl: "10px",
+              display: "-webkit-box",
+              WebkitBoxOrient: "vertical",
+              WebkitLineClamp: 3,
+              overflow: "hidden",
+              whiteSpace: "pre-wrap",
+            }}
+          >
+            {selection.text}
+          </Typography>
+          <Box sx={{ display: "flex", alignItems: "center", mt: "10px" }}>
+            <TextField
+              value={note}
+              size="small"
+              autoComplete="off"
+              placeholder={t("highlightNote")}
+              onChange={(event) => setNote(event.target.value)}
+              sx={{ flex: 1 }}
+            />
+            <Button variant="outlined" onClick={onSave} sx={{ ml: "10px" }}>
+              {t("saveHighlight")}
+            </Button>
+          </Box>
+        </ListItem>
+      )}
+    </List>
+  );
+}
@@ -0,0 +1,338 @@
+import {
+  Alert,
+  Box,
+  Button,
+  IconButton,
+  List,
+  ListItem,
+  MenuItem,
+  Select,
+  SxProps,
+  Table,
+  TableBody,
+  TableCell,
+  TableHead,
+  TableRow,
+  TextField,
+  Theme,
+  Typography,
+} from "@mui/material";
+import { DeleteOutlined } from "@mui/icons-material";
+import { open } from "@tauri-apps/plugin-dialog";
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import {
+  ChineseConversion,
+  chineseConversions,
+  CleanupPreviewLine,
+  CleanupRule,
+  Config,
+  invokeCommand,
+  invokeCommandResult,
+  NormalizeOptions,
+  readTextFile,
+} from "../util";
+import SettingSwitch from "./components/SettingSwitch";
+
+const normalizeOptionKeys: (keyof NormalizeOptions)[] = [
+  "full_width_to_half_width",
+  "unify_quotes",
+  "unify_ellipses",
+  "collapse_repeated_punctuation",
+  "collapse_spaces",
+  "nfkc",
+];
+
+const listItemSx: SxProps<Theme> = {
+  display: "flex",
+  justifyContent: "space-between",
+  alignItems: "center",
+};
+
+export default function Import() {
+  const { t } = useTranslation();
+  const [ready, setReady] = useState(false);
+  const [normalization, setNormalization] = useState<NormalizeOptions | null>(
+    null
+  );
+  const [rules, setRules] = useState<CleanupRule[]>([]);
+  const [chineseConversion, setChineseConversion] =
+    useState<ChineseConversion>("None");
+  const [previewPath, setPreviewPath] = useState("");
+  const [previewLines, setPreviewLines] = useState<
+    CleanupPreviewLine[] | null
+  >(null);
+  const [errorMessage, setErrorMessage] = useState("");
+  const [saved, setSaved] = useState(true);
+
+  useEffect(() => {
+    const init = async () => {
+      const config = await invokeCommand<Config>("get_config");
+      if (typeof config === "undefined") {
+        console.error("Not received the config");
+        return;
+      }
+      setNormalization(config.import.normalization);
+      setRules(config.import.cleanup_rules);
+      setChineseConversion(config.import.chinese_conversion);
+      setReady(true);
+    };
+
+    init();
+  }, []);
+
+  const onNormalizationChange = (
+    key: keyof NormalizeOptions,
+    checked: boolean
+  ) => {
+    if (normalization === null) {
+      return;
+    }
+    const newNormalization = { ...normalization, [key]: checked };
+    setNormalization(newNormalization);
+    invokeCommand("update_import_normalization", {
+      normalization: newNormalization,
+    });
+  };
+
+  const onChineseConversionChange = (value: ChineseConversion) => {
+    setChineseConversion(value);
+    invokeCommand("update_import_chinese_conversion", {
+      chineseConversion: value,
+    });
+  };
+
+  const updateRule = (index: number, rule: CleanupRule) => {
+    setRules((rules) => rules.map((r, i) => (i === index ? rule : r)));
+    setSaved(false);
+  };
+
+  const onRuleKindChange = (index: number, kind: string) => {
+    const pattern = rules[index].pattern;
+    updateRule(
+      index,
+      kind === "Replace"
+        ? { kind: "Replace", pattern, replacement: "" }
+        : { kind: "DeleteLine", pattern }
+    );
+  };
+
+  const onAddRule = () => {
+    setRules((rules) => [...rules, { kind: "DeleteLine", pattern: "" }]);
+    setSaved(false);
+  };
+
+  const onRemoveRule = (index: number) => {
+    setRules((rules) => rules.filter((_, i) => i !== index));
+    setSaved(false);
+  };
+
+  const onSave = async () => {
+    const result = await invokeCommandResult("update_cleanup_rules", {
+      cleanupRules: rules,
+    });
+    if (!result.ok) {
+      setErrorMessage(result.error.message);
+      return;
+    }
+    setErrorMessage("");
+    setSaved(true);
+  };
+
+  const onSelectPreviewFile = async () => {
+    const selectedPath = await open({
+      title: t("previewFile"),
+      multiple: false,
+      directory: false,
+      filters: [
+        {
+          name: t("plainTextFiles"),
+          extensions: ["txt"],
+        },
+      ],
+    });
+    if (selectedPath !== null) {
+      setPreviewPath(selectedPath);
+    }
+  };
+
+  const onPreview = async () => {
+    const content = await readTextFile(previewPath);
+    if (content === null) {
+      setErrorMessage(t("cannotReadPreviewFile"));
+      setPreviewLines(null);
+      return;
+    }
+    const result = await invokeCommandResult<CleanupPreviewLine[]>(
+      "preview_cleanup_rules",
+      { content, cleanupRules: rules }
+    );
+    if (!result.ok) {
+      setErrorMessage(result.error.message);
+      setPreviewLines(null);
+      return;
+    }
+    setErrorMessage("");
+    setPreviewLines(result.value);
+  };
+
+  if (!ready || normalization === null) {
+    return <></>;
+  }
+
+  return (
+    <Box sx={{ px: "20px", mt: "10px" }}>
+      <Typography variant="h6">{t("normalization")}</Typography>
+      <List>
+        {normalizeOptionKeys.map((key) => (
+          <SettingSwitch
+            key={key}
+            defaultChecked={normalization[key]}
+            onChange={(checked) => onNormalizationChange(key, checked)}
+          >
+            {t(`normalization_${key}`)}
+          </SettingSwitch>
+        ))}
+      </List>
+
+      <List>
+        <ListItem sx={listItemSx} disableGutters>
+          <Box>{t("chineseConversionOnImport")}</Box>
+          <Select
+            value={chineseConversion}
+            size="small"
+            onChange={(event) =>
+              onChineseConversionChange(
+                event.target.value as ChineseConversion
+              )
+            }
+          >
+            {chineseConversions.map((value) => (
+              <MenuItem key={value} value={value}>
+                {t(`chineseConversion${value}`)}
+              </MenuItem>
+            ))}
+          </Select>
+        </ListItem>
+      </List>
+
+      <Typography variant="h6">{t("cleanupRules")}</Typography>
+      <Typography variant="body2">{t("cleanupRulesExplain")}</Typography>
+
+      <List>
+        {rules.map((rule, index) => (
+          <ListItem key={index} sx={listItemSx} disableGutters>
+            <Select
+              value={rule.kind}
+              size="small"
+              onChange={(event) => onRuleKindChange(index, event.target.value)}
+              sx={{ width: "150px" }}
+            >
+              <MenuItem value="DeleteLine">{t("deleteLine")}</MenuItem>
+              <MenuItem value="Replace">{t("replace")}</MenuItem>
+            </Select>
+            <TextField
+              label={t("pattern")}
+              value={rule.pattern}
+              size="small"
+              autoComplete="off"
+              onChange={(event) =>
+                updateRule(index, { ...rule, pattern: event.target.value })
+              }
+              sx={{ flex: 1, ml: "10px" }}
+            />
+            {rule.kind === "Replace" && (
+              <TextField
+                label={t("replacement")}
+                value={rule.replacement}
+                size="small"
+                autoComplete="off"
+                onChange={(event) =>
+                  updateRule(index, {
+                    ...rule,
+                    replacement: event.target.value,
+                  })
+                }
+                sx={{ flex: 1, ml: "10px" }}
+              />
+            )}
+            <IconButton onClick={() => onRemoveRule(index)}>
+              <DeleteOutlined />
+            </IconButton>
+          </ListItem>
+        ))}
+      </List>
+
+      <Box sx={{ display: "flex", justifyContent: "right" }}>
+        <Button variant="outlined" onClick={onAddRule}>
+          {t("addRule")}
+        </Button>
+        <Button
+          variant="contained"
+          onClick={onSave}
+          disabled={saved}
+          sx={{ ml: "10px" }}
+        >
+          {t("save")}
+        </Button>
+      </Box>
+
+      {errorMessage !== "" && (
+        <Alert severity="error" sx={{ mt: "10px" }}>
+          {errorMessage}
+        </Alert>
+      )}
+
+      <Box sx={{ display: "flex", alignItems: "center", mt: "20px" }}>
+        <TextField
+          value={previewPath}
+          size="small"
+          placeholder={t("previewFile")}
+          slotProps={{ input: { readOnly: true } }}
+          onClick={onSelectPreviewFile}
+          sx={{ flex: 1 }}
+        />
+        <Button
+          variant="outlined"
+          onClick={onPreview}
+          disabled={previewPath === ""}
+          sx={{ ml: "10px" }}
+        >
+          {t("preview")}
+        </Button>
+      </Box>
+
+      {previewLines !== null && (
+        <Table size="small" sx={{ mt: "10px", userSelect: "text" }}>
+          <TableHead>
+            <TableRow>
+              <TableCell>#</TableCell>
+              <TableCell>{t("before")}</TableCell>
+              <TableCell>{t("after")}</TableCell>
+            </TableRow>
+          </TableHead>
+          <TableBody>
+            {previewLines.length === 0 && (
+              <TableRow>
+                <TableCell colSpan={3}>{t("nothingChanged")}</TableCell>
+              </TableRow>
+            )}
+            {previewLines.map((line) => (
+              <TableRow key={line.line_number}>
+                <TableCell>{line.line_number}</TableCell>
+                <TableCell>{line.before}</TableCell>
+                <TableCell
+                  sx={{
+                    textDecoration: line.after === null ? "line-through" : "",
+                  }}
+                >
+                  {line.after ?? line.before}
+                </TableCell>
+              </TableRow>
+            ))}
+          </TableBody>
+        </Table>
+      )}
+    </Box>
+  );
+}
@@ -7,17 +7,31 @@ import {
   Dialog,
   DialogContent,
   Grid2,
+  MenuItem,
+  Select,
   Snackbar,
   TextField,
 } from "@mui/material";
 import { OverridableStringUnion } from "@mui/types";
 import { open } from "@tauri-apps/plugin-dialog";
-import { Book, invokeCommand, NewBooksResult, ReaderBookInfo } from "../util";
-import { Dispatch, SetStateAction, useEffect, useMemo, useState } from "react";
+import {
+  Book,
+  BookSettings,
+  ChineseConversion,
+  chineseConversions,
+  invokeCommand,
+  LibraryBook,
+  NewBookInfo,
+  NewBooksResult,
+  readTextFile,
+} from "../util";
+import { Dispatch, SetStateAction, useEffect, useState } from "react";
 import { useTranslation } from "react-i18next";
 import BookCard from "./components/BookCard";
-import { readFile } from "@tauri-apps/plugin-fs";
-import * as jschardet from "jschardet";
+import DialogBookmarks from "./DialogBookmarks";
+import DialogHighlights from "./DialogHighlights";
+import DialogSearch from "./DialogSearch";
+import DialogLibrarySearch from "./DialogLibrarySearch";
 
 type Severity = OverridableStringUnion<AlertColor, AlertPropsColorOverrides>;
 
@@ -27,11 +41,16 @@ interface SnackbarInfo {
   severity?: Severity;
 }
 
+/** A newly imported book, whose estimate comes with the next `get_books`. */
+function toLibraryBook(book: Book): LibraryBook {
+  return { ...book, time_left: null };
+}
+
 export default function Library() {
   const { t } = useTranslation();
   const [ready, setReady] = useState(false);
-  const [books, setBooks] = useState([] as Book[]);
-  const [showingContextMenuBookTitle, setShowingContextMenuBookTitle] =
+  const [books, setBooks] = useState([] as LibraryBook[]);
+  const [showingContextMenuBookId, setShowingContextMenuBookId] =
     useState("");
   const [snackbarInfo, setSnackbarInfo] = useState<SnackbarInfo>({
     open: false,
@@ -40,11 +59,19 @@ export default function Library() {
   });
   const [dialogNewBookOpen, setDialogNewBookOpen] = useState(false);
   const [dialogRenameBookOpen, setDialogRenameBookOpen] = useState(false);
+  const [renameBookId, setRenameBookId] = useState("");
   const [originalTitle, setOriginalTitle] = useState("");
   const [renameTitle, setRenameTitle] = useState("");
+  const [dialogChineseConversionBookId, setDialogChineseConversionBookId] =
+    useState("");
+  const [dialogSettingsBookId, setDialogSettingsBookId] = useState("");
+  const [dialogBookmarksBookId, setDialogBookmarksBookId] = useState("");
+  const [dialogHighlightsBookId, setDialogHighlightsBookId] = useState("");
+  const [dialogSearchBookId, setDialogSearchBookId] = useState("");
+  const [dialogLibrarySearchOpen, setDialogLibrarySearchOpen] = useState(false);
 
   useEffect(() => {
-    invokeCommand<Book[]>("get_books").then((books) => {
+    invokeCommand<LibraryBook[]>("get_books").then((books) => {
       if (typeof books === "undefined") {
         console.error("Not received books after calling 'get_books'");
         return;
@@ -54,14 +81,12 @@ export default function Library() {
     });
   }, []);
 
-  const bookTitles = useMemo(() => new Set(books.map((b) => b.title)), [books]);
-
   const onNewBook = async () => {
     setDialogNewBookOpen(true);
   };
 
   const onSelectFiles = async () => {
-    setShowingContextMenuBookTitle("");
+    setShowingContextMenuBookId("");
 
     const selectedBookPaths = await open({
       title: t("importTxtBooks"),
@@ -80,24 +105,11 @@ export default function Library() {
     }
 
     const failedPaths: string[] = [];
-    const readerBookInfos: ReaderBookInfo[] = [];
+    const newBookInfos: NewBookInfo[] = [];
 
     for (const selectedPath of selectedBookPaths) {
-      if (bookTitles.has(selectedPath)) {
-        failedPaths.push(selectedPath);
-        continue;
-      }
-
-      const rawContentBytes = await readFile(selectedPath);
-      const binaryString = Array.from(rawContentBytes)
-        .map((b) => String.fromCharCode(b))
-        .join("");
-      const detectResult = jschardet.detect(binaryString);
-      const decoder = new TextDecoder(detectResult.encoding);
-      let content;
-      try {
-        content = decoder.decode(rawContentBytes);
-      } catch {
+      const content = await readTextFile(selectedPath);
+      if (content === null) {
         failedPaths.push(selectedPath);
         continue;
       }
@@ -107,15 +119,14 @@ export default function Library() {
         selectedPath.lastIndexOf("/", lastDot - 1),
         selectedPath.lastIndexOf("\\", lastDot - 1)
       );
-      readerBookInfos.push({
+      newBookInfos.push({
         title: selectedPath.substring(lastSlash + 1, lastDot),
         content,
-        progress: 0,
       });
     }
 
     const newBooksResult = await invokeCommand<NewBooksResult>("new_books", {
-      bookInfos: readerBookInfos,
+      bookInfos: newBookInfos,
     });
     if (typeof newBooksResult === "undefined") {
       console.error("Not received import books result");
@@ -126,10 +137,11 @@ export default function Library() {
       if (newBooksResult.successful.length === 0) {
         return books;
       }
+      const newBooks = newBooksResult.successful.map(toLibraryBook);
       if (books.length === 0) {
-        return newBooksResult.successful;
+        return newBooks;
       }
-      return [books[0], ...newBooksResult.successful, ...books.slice(1)];
+      return [books[0], ...newBooks, ...books.slice(1)];
     });
 
     const counts = {
@@ -155,29 +167,84 @@ export default function Library() {
     });
   };
 
-  const createOnBookSelect = (bookTitle: string) => async () => {
-    const books = await invokeCommand<Book[]>("change_book", {
-      title: bookTitle,
+  const createOnBookSelect = (bookId: string) => async () => {
+    const books = await invokeCommand<LibraryBook[]>("change_book", {
+      id: bookId,
     });
     if (typeof books === "undefined") {
       console.error("Not received books after calling 'change_book'");
       return;
     }
     setBooks(books);
-    setShowingContextMenuBookTitle("");
+    setShowingContextMenuBookId("");
   };
 
-  const createOnBookContextMenu = (bookTitle: string) => () => {
-    setShowingContextMenuBookTitle(bookTitle);
+  const createOnBookContextMenu = (bookId: string) => () => {
+    setShowingContextMenuBookId(bookId);
   };
 
-  const createOnBookRename = (bookTitle: string) => async () => {
-    setOriginalTitle(bookTitle);
-    setRenameTitle(bookTitle);
+  const createOnBookRename = (book: Book) => async () => {
+    setRenameBookId(book.id);
+    setOriginalTitle(book.title);
+    setRenameTitle(book.title);
     setDialogRenameBookOpen(true);
   };
 
-  const createOnBookRemove = (bookTitle: string) => async () => {
+  const createOnBookChineseConversion = (bookId: string) => () => {
+    setDialogChineseConversionBookId(bookId);
+  };
+
+  const onChineseConversionChange = async (
+    chineseConversion: ChineseConversion
+  ) => {
+    const id = dialogChineseConversionBookId;
+    setDialogChineseConversionBookId("");
+    const result = await invokeCommand("update_book_chinese_conversion", {
+      id,
+      chineseConversion,
+    });
+    if (result === undefined) {
+      return;
+    }
+    setBooks((books) =>
+      books.map((book) =>
+        book.id === id
+          ? { ...book, chinese_conversion: chineseConversion }
+          : book
+      )
+    );
+  };
+
+  const createOnBookGoBack = (bookId: string) => async () => {
+    setShowingContextMenuBookId("");
+    const books = await invokeCommand<LibraryBook[]>("go_back", {
+      id: bookId,
+    });
+    if (typeof books !== "undefined") {
+      setBooks(books);
+    }
+  };
+
+  const createOnBookBookmarks = (bookId: string) => () => {
+    setDialogBookmarksBookId(bookId);
+    setShowingContextMenuBookId("");
+  };
+
+  const createOnBookHighlights = (bookId: string) => () => {
+    setDialogHighlightsBookId(bookId);
+    setShowingContextMenuBookId("");
+  };
+
+  const createOnBookSearch = (bookId: string) => () => {
+    setDialogSearchBookId(bookId);
+    setShowingContextMenuBookId("");
+  };
+
+  const createOnBookSettings = (bookId: string) => () => {
+    setDialogSettingsBookId(bookId);
+  };
+
+  const createOnBookRemove = (bookId: string) => async () => {
     if (books.length === 1) {
       setSnackbarInfo({
         open: true,
@@ -186,9 +253,12 @@ export default function Library() {
       });
       return;
     }
-    await invokeCommand("remove_book", { title: bookTitle });
+    const result = await invokeCommand("remove_book", { id: bookId });
+    if (result === undefined) {
+      return;
+    }
     for (let i = 0; i < books.length; i++) {
-      if (books[i].title === bookTitle) {
+      if (books[i].id === bookId) {
         setBooks((books) => books.slice(0, i).concat(books.slice(i + 1)));
         break;
       }
@@ -205,7 +275,7 @@ export default function Library() {
 
   return (
     <Box
-      onClick={() => setShowingContextMenuBookTitle("")}
+      onClick={() => setShowingContextMenuBookId("")}
       sx={{ width: "100%", height: "95vh" }}
     >
       <Box
@@ -216,7 +286,13 @@ export default function Library() {
           px: "20px",
         }}
       >
-        <Button variant="contained" onClick={onNewBook}>
+        <Button
+          variant="outlined"
+          onClick={() => setDialogLibrarySearchOpen(true)}
+        >
+          {t("searchLibrary")}
+        </Button>
+        <Button variant="contained" onClick={onNewBook} sx={{ ml: "10px" }}>
           {t("new")}
         </Button>
         <Button variant="contained" onClick={onSelectFiles} sx={{ ml: "10px" }}>
@@ -230,15 +306,22 @@ export default function Library() {
         sx={{ px: "20px", mt: "20px" }}
       >
         {books.map((book) => (
-          <Grid2 key={book.title} size={{ xs: 2, sm: 4, md: 4 }}>
+          <Grid2 key={book.id} size={{ xs: 2, sm: 4, md: 4 }}>
             <BookCard
               title={book.title}
               summary={book.summary}
-              showContextMenu={showingContextMenuBookTitle === book.title}
-              onSelect={createOnBookSelect(book.title)}
-              onContextMenu={createOnBookContextMenu(book.title)}
-              onRename={createOnBookRename(book.title)}
-              onRemove={createOnBookRemove(book.title)}
+              timeLeft={book.time_left}
+              showContextMenu={showingContextMenuBookId === book.id}
+              onSelect={createOnBookSelect(book.id)}
+              onContextMenu={createOnBookContextMenu(book.id)}
+              onRename={createOnBookRename(book)}
+              onChineseConversion={createOnBookChineseConversion(book.id)}
+              onSettings={createOnBookSettings(book.id)}
+              onBookmarks={createOnBookBookmarks(book.id)}
+              onHighlights={createOnBookHighlights(book.id)}
+              onSearch={createOnBookSearch(book.id)}
+              onGoBack={createOnBookGoBack(book.id)}
+              onRemove={createOnBookRemove(book.id)}
             />
           </Grid2>
         ))}
@@ -248,7 +331,6 @@ export default function Library() {
         open={dialogNewBookOpen}
         setOpen={setDialogNewBookOpen}
         setSnackbarInfo={setSnackbarInfo}
-        bookTitles={bookTitles}
         setBooks={setBooks}
       />
 
@@ -256,13 +338,68 @@ export default function Library() {
         open={dialogRenameBookOpen}
         setOpen={setDialogRenameBookOpen}
         setSnackbarInfo={setSnackbarInfo}
-        bookTitles={bookTitles}
         setBooks={setBooks}
+        bookId={renameBookId}
         originalTitle={originalTitle}
         renameTitle={renameTitle}
         setRenameTitle={setRenameTitle}
       />
 
+      <Dialog
+        open={dialogChineseConversionBookId !== ""}
+        onClose={() => setDialogChineseConversionBookId("")}
+      >
+        <DialogContent>
+          <Select
+            value={
+              books.find((book) => book.id === dialogChineseConversionBookId)
+                ?.chinese_conversion ?? "None"
+            }
+            size="small"
+            onChange={(event) =>
+              onChineseConversionChange(
+                event.target.value as ChineseConversion
+              )
+            }
+          >
+            {chineseConversions.map((value) => (
+              <MenuItem key={value} value={value}>
+                {t(`chineseConversion${value}`)}
+              </MenuItem>
+            ))}
+          </Select>
+        </DialogContent>
+      </Dialog>
+
+      <DialogBookmarks
+        book={books.find((book) => book.id === dialogBookmarksBookId)}
+        onClose={() => setDialogBookmarksBookId("")}
+        setBooks={setBooks}
+      />
+
+      <DialogHighlights
+        book={books.find((book) => book.id === dialogHighlightsBookId)}
+        onClose={() => setDialogHighlightsBookId("")}
+      />
+
+      <DialogLibrarySearch
+        open={dialogLibrarySearchOpen}
+        onClose={() => setDialogLibrarySearchOpen(false)}
+        setBooks={setBooks}
+      />
+
+      <DialogSearch
+        book={books.find((book) => book.id === dialogSearchBookId)}
+        onClose={() => setDialogSearchBookId("")}
+        setBooks={setBooks}
+      />
+
+      <DialogBookSettings
+        book={books.find((book) => book.id === dialogSettingsBookId)}
+        onClose={() => setDialogSettingsBookId("")}
+        setBooks={setBooks}
+      />
+
       <Snackbar
         open={snackbarInfo.open}
         autoHideDuration={3000}
@@ -284,15 +421,13 @@ interface DialogProps {
   open: boolean;
   setOpen: Dispatch<SetStateAction<boolean>>;
   setSnackbarInfo: Dispatch<SetStateAction<SnackbarInfo>>;
-  bookTitles: Set<string>;
-  setBooks: Dispatch<SetStateAction<Book[]>>;
+  setBooks: Dispatch<SetStateAction<LibraryBook[]>>;
 }
 
 function DialogNewBook({
   open,
   setOpen,
   setSnackbarInfo,
-  bookTitles,
   setBooks,
 }: DialogProps) {
   const { t } = useTranslation();
@@ -315,16 +450,7 @@ function DialogNewBook({
       return;
     }
 
-    if (bookTitles.has(title)) {
-      setSnackbarInfo({
-        open: true,
-        message: t("bookWithSameTitleExists"),
-        severity: "error",
-      });
-      return;
-    }
-
-    const bookInfos: ReaderBookInfo[] = [{ title, content, progress: 0 }];
+    const bookInfos: NewBookInfo[] = [{ title, content }];
     const newBooksResult = await invokeCommand<NewBooksResult>("new_books", {
       bookInfos,
     });
@@ -339,7 +465,7 @@ function DialogNewBook({
       });
       return;
     }
-    const book = newBooksResult.successful[0];
+    const book = toLibraryBook(newBooksResult.successful[0]);
     setBooks((books) =>
       books.length === 0 ? [book] : [books[0], book, ...books.slice(1)]
     );
@@ -362,8 +488,6 @@ function DialogNewBook({
           fullWidth
           variant="outlined"
           autoComplete="off"
-          error={bookTitles.has(title)}
-          helperText={bookTitles.has(title) ? t("bookWithSameTitleExists") : ""}
           onChange={(event) => setTitle(event.target.value)}
         />
 
@@ -384,6 +508,7 @@ function DialogNewBook({
 }
 
 interface DialogRenameBookProps extends DialogProps {
+  bookId: string;
   originalTitle: string;
   renameTitle: string;
   setRenameTitle: Dispatch<SetStateAction<string>>;
@@ -393,8 +518,8 @@ function DialogRenameBook({
   open,
   setOpen,
   setSnackbarInfo,
-  bookTitles,
   setBooks,
+  bookId,
   originalTitle,
   renameTitle,
   setRenameTitle,
@@ -417,20 +542,11 @@ function DialogRenameBook({
       return;
     }
 
-    if (bookTitles.has(renameTitle)) {
-      setSnackbarInfo({
-        open: true,
-        message: t("bookWithSameTitleExists"),
-        severity: "error",
-      });
-      return;
-    }
-
-    const res = await invokeCommand<null>("rename_book", {
-      originalTitle,
+    const newTitle = await invokeCommand<string>("rename_book", {
+      id: bookId,
       newTitle: renameTitle,
     });
-    if (typeof res === "undefined") {
+    if (typeof newTitle === "undefined") {
       setSnackbarInfo({
         open: true,
         message: t("titleNotChanged"),
@@ -439,14 +555,11 @@ function DialogRenameBook({
       return;
     }
 
-    setBooks((books) => {
-      const newBooks = [...books];
-      const originalIndex = newBooks.findIndex(
-        (book) => book.title === originalTitle
-      );
-      newBooks[originalIndex].title = renameTitle;
-      return newBooks;
-    });
+    setBooks((books) =>
+      books.map((book) =>
+        book.id === bookId ? { ...book, title: newTitle } : book
+      )
+    );
     setSnackbarInfo({
       open: true,
       message: t("titleChanged"),
@@ -454,9 +567,6 @@ function DialogRenameBook({
     });
   };
 
-  const isErrorShown =
-    bookTitles.has(renameTitle) && renameTitle !== originalTitle;
-
   return (
     <Dialog open={open} onClose={onClose} fullWidth>
       <DialogContent sx={{ display: "flex", flexDirection: "column" }}>
@@ -467,11 +577,106 @@ function DialogRenameBook({
           fullWidth
           variant="outlined"
           autoComplete="off"
-          error={isErrorShown}
-          helperText={isErrorShown ? t("bookWithSameTitleExists") : ""}
           onChange={(event) => setRenameTitle(event.target.value)}
         />
       </DialogContent>
     </Dialog>
   );
 }
+
+interface DialogBookSettingsProps {
+  book?: Book;
+  onClose: () => void;
+  setBooks: Dispatch<SetStateAction<LibraryBook[]>>;
+}
+
+/** Text fields left empty fall back to the global appearance settings. */
+function DialogBookSettings({
+  book,
+  onClose,
+  setBooks,
+}: DialogBookSettingsProps) {
+  const { t } = useTranslation();
+  const [textSize, setTextSize] = useState("");
+  const [textColor, setTextColor] = useState("");
+  const [lineHeight, setLineHeight] = useState("");
+  const [fontFamily, setFontFamily] = useState("");
+
+  useEffect(() => {
+    const settings = book?.settings;
+    setTextSize(settings?.text_size?.toString() ?? "");
+    setTextColor(settings?.text_color ?? "");
+    setLineHeight(settings?.line_height?.toString() ?? "");
+    setFontFamily(settings?.font_family ?? "");
+  }, [book?.id]);
+
+  const onDialogClose = async () => {
+    onClose();
+    if (!book) {
+      return;
+    }
+
+    const toNumber = (value: string) =>
+      value.trim() === "" ? null : Number(value) || null;
+    const toString = (value: string) =>
+      value.trim() === "" ? null : value.trim();
+    const settings = await invokeCommand<BookSettings>("update_book_settings", {
+      id: book.id,
+      settings: {
+        text_size: toNumber(textSize),
+        text_color: toString(textColor),
+        line_height: toNumber(lineHeight),
+        font_family: toString(fontFamily),
+      },
+    });
+    if (typeof settings === "undefined") {
+      return;
+    }
+    setBooks((books) =>
+      books.map((b) => (b.id === book.id ? { ...b, settings } : b))
+    );
+  };
+
+  return (
+    <Dialog open={book !== undefined} onClose={onDialogClose} fullWidth>
+      <DialogContent
+        sx={{ display: "flex", flexDirection: "column", gap: "20px" }}
+      >
+        <TextField
+          label={t("textSize")}
+          value={textSize}
+          placeholder={t("useGlobalSetting")}
+          size="small"
+          type="number"
+          inputProps={{ min: 1, step: 1 }}
+          onChange={(event) => setTextSize(event.target.value)}
+        />
+        <TextField
+          label={t("textColor")}
+          value={textColor}
+          placeholder={t("useGlobalSetting")}
+          size="small"
+          autoComplete="off"
+          onChange={(event) => setTextColor(event.target.value)}
+        />
+        <TextField
+          label={t("lineHeight")}
+          value={lineHeight}
+          placeholder={t("useGlobalSetting")}
+          size="small"
+          type="number"
+          inputProps={{ min: 0.5, step: 0.1 }}
+          onChange={(event) => setLineHeight(event.target.value)}
+        />
+        <TextField
+          label={t("fontFamily")}
+          value={fontFamily}
+          placeholder={t("useGlobalSetting")}
+          size="small"
+          autoComplete="off"
+          onChange={(event) => setFontFamily(event.target.value)}
+        />
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,155 @@
+import {
+  Box,
+  Button,
+  IconButton,
+  List,
+  ListItem,
+  ListSubheader,
+  Switch,
+  SxProps,
+  TextField,
+  Theme,
+  Tooltip,
+} from "@mui/material";
+import {
+  CheckOutlined,
+  DeleteOutlined,
+  SaveOutlined,
+} from "@mui/icons-material";
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { AppearanceProfile, Config, invokeCommand } from "../util";
+
+const listItemSx: SxProps<Theme> = {
+  display: "flex",
+  justifyContent: "space-between",
+  alignItems: "center",
+};
+
+/**
+ * Named copies of the appearance. Applying one replaces the whole appearance,
+ * so the page showing it is reloaded by the app on "appearance-changed".
+ */
+export default function Profiles() {
+  const { t } = useTranslation();
+  const [profiles, setProfiles] = useState<AppearanceProfile[]>([]);
+  const [activeProfile, setActiveProfile] = useState<string | null>(null);
+  const [monitorLayout, setMonitorLayout] = useState("");
+  const [newName, setNewName] = useState("");
+
+  useEffect(() => {
+    invokeCommand<Config>("get_config").then((config) => {
+      if (typeof config === "undefined") {
+        console.error(
+          "Not received the config after invoking command 'get_config'"
+        );
+        return;
+      }
+      setProfiles(config.profiles);
+      setActiveProfile(config.active_profile);
+    });
+
+    invokeCommand<string>("get_monitor_layout").then((monitorLayout) => {
+      setMonitorLayout(monitorLayout ?? "");
+    });
+  }, []);
+
+  const onSave = async (name: string) => {
+    const newProfiles = await invokeCommand<AppearanceProfile[]>(
+      "save_profile",
+      { name }
+    );
+    if (typeof newProfiles === "undefined") {
+      return;
+    }
+    setProfiles(newProfiles);
+    setActiveProfile(name);
+    setNewName("");
+  };
+
+  const onApply = (name: string) => {
+    invokeCommand("apply_profile", { name });
+  };
+
+  const onRemove = async (name: string) => {
+    const newProfiles = await invokeCommand<AppearanceProfile[]>(
+      "remove_profile",
+      { name }
+    );
+    if (typeof newProfiles === "undefined") {
+      return;
+    }
+    setProfiles(newProfiles);
+    if (activeProfile === name) {
+      setActiveProfile(null);
+    }
+  };
+
+  const onAutoChange = async (name: string, isAuto: boolean) => {
+    const newProfiles = await invokeCommand<AppearanceProfile[]>(
+      "update_profile_monitor_layout",
+      { name, isAuto }
+    );
+    if (typeof newProfiles !== "undefined") {
+      setProfiles(newProfiles);
+    }
+  };
+
+  const trimmedNewName = newName.trim();
+
+  return (
+    <List>
+      <ListSubheader>{t("profiles")}</ListSubheader>
+      {profiles.map((profile) => (
+        <ListItem key={profile.name} sx={listItemSx}>
+          <Box sx={{ flex: 1 }}>
+            {profile.name}
+            {profile.name === activeProfile && ` (${t("activeProfile")})`}
+          </Box>
+          <Tooltip title={t("applyForTheseMonitors")}>
+            <Switch
+              checked={
+                monitorLayout !== "" &&
+                profile.monitor_layout === monitorLayout
+              }
+              onChange={(event) =>
+                onAutoChange(profile.name, event.target.checked)
+              }
+            />
+          </Tooltip>
+          <Tooltip title={t("applyProfile")}>
+            <IconButton onClick={() => onApply(profile.name)}>
+              <CheckOutlined />
+            </IconButton>
+          </Tooltip>
+          <Tooltip title={t("overwriteProfile")}>
+            <IconButton onClick={() => onSave(profile.name)}>
+              <SaveOutlined />
+            </IconButton>
+          </Tooltip>
+          <IconButton onClick={() => onRemove(profile.name)}>
+            <DeleteOutlined />
+          </IconButton>
+        </ListItem>
+      ))}
+      <ListItem sx={listItemSx}>
+        <TextField
+          value={newName}
+          size="small"
+          autoComplete="off"
+          placeholder={t("profileName")}
+          onChange={(event) => setNewName(event.target.value)}
+          sx={{ flex: 1 }}
+        />
+        <Button
+          variant="outlined"
+          disabled={trimmedNewName === ""}
+          onClick={() => onSave(trimmedNewName)}
+          sx={{ ml: "10px" }}
+        >
+          {t("saveAsProfile")}
+        </Button>
+      </ListItem>
+    </List>
+  );
+}
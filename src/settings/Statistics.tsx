@@ -0,0 +1,190 @@
+import {
+  Box,
+  LinearProgress,
+  List,
+  ListItem,
+  ListSubheader,
+  MenuItem,
+  Select,
+  SxProps,
+  TextField,
+  Theme,
+} from "@mui/material";
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import {
+  Config,
+  Goal,
+  GoalProgress,
+  GoalsSummary,
+  GoalUnit,
+  goalUnits,
+  ReadingGoals,
+  ReadingStats,
+  ReadingTotals,
+  invokeCommand,
+} from "../util";
+
+const listItemSx: SxProps<Theme> = {
+  display: "flex",
+  justifyContent: "space-between",
+  alignItems: "center",
+};
+
+const defaultGoalAmounts: Record<GoalUnit, number> = {
+  Minutes: 30,
+  Characters: 10000,
+};
+
+export default function Statistics() {
+  const { t } = useTranslation();
+  const [stats, setStats] = useState<ReadingStats | null>(null);
+  const [goals, setGoals] = useState<ReadingGoals | null>(null);
+  const [goalsSummary, setGoalsSummary] = useState<GoalsSummary | null>(null);
+
+  useEffect(() => {
+    invokeCommand<ReadingStats>("get_reading_stats").then((stats) => {
+      if (typeof stats === "undefined") {
+        console.error(
+          "Not received reading stats after invoking command 'get_reading_stats'"
+        );
+        return;
+      }
+      setStats(stats);
+    });
+
+    invokeCommand<Config>("get_config").then((config) => {
+      if (typeof config !== "undefined") {
+        setGoals(config.goals);
+      }
+    });
+
+    invokeCommand<GoalsSummary>("get_goals_summary").then((goalsSummary) => {
+      setGoalsSummary(goalsSummary ?? null);
+    });
+  }, []);
+
+  if (stats === null || goals === null || goalsSummary === null) {
+    return <></>;
+  }
+
+  const onGoalsChange = async (newGoals: ReadingGoals) => {
+    setGoals(newGoals);
+    const goalsSummary = await invokeCommand<GoalsSummary>(
+      "update_reading_goals",
+      { goals: newGoals }
+    );
+    if (typeof goalsSummary !== "undefined") {
+      setGoalsSummary(goalsSummary);
+    }
+  };
+
+  const totals = (totals: ReadingTotals) => (
+    <Box>
+      {t("readingTime", { minutes: Math.round(totals.seconds / 60) })}
+      {" · "}
+      {t("charactersRead", { count: totals.characters })}
+    </Box>
+  );
+
+  const goalItem = (
+    name: "daily" | "weekly",
+    goal: Goal | null,
+    progress: GoalProgress | null
+  ) => (
+    <ListItem key={name} sx={{ display: "block" }}>
+      <Box sx={listItemSx}>
+        <Box sx={{ flex: 1 }}>{t(`${name}Goal`)}</Box>
+        <Select
+          value={goal?.unit ?? "None"}
+          size="small"
+          onChange={(event) => {
+            const unit = event.target.value as GoalUnit | "None";
+            onGoalsChange({
+              ...goals,
+              [name]:
+                unit === "None"
+                  ? null
+                  : { unit, amount: defaultGoalAmounts[unit] },
+            });
+          }}
+        >
+          <MenuItem value="None">{t("noGoal")}</MenuItem>
+          {goalUnits.map((unit) => (
+            <MenuItem key={unit} value={unit}>
+              {t(`goalUnit${unit}`)}
+            </MenuItem>
+          ))}
+        </Select>
+        {goal !== null && (
+          <TextField
+            key={goal.unit}
+            type="number"
+            size="small"
+            defaultValue={goal.amount}
+            onChange={(event) => {
+              const amount = Math.floor(Number(event.target.value));
+              if (amount > 0) {
+                onGoalsChange({ ...goals, [name]: { ...goal, amount } });
+              }
+            }}
+            sx={{ width: "120px", ml: "10px" }}
+          />
+        )}
+      </Box>
+      {progress !== null && (
+        <Box sx={{ mt: "10px" }}>
+          <LinearProgress
+            variant="determinate"
+            value={Math.min(100, (progress.done / progress.goal.amount) * 100)}
+          />
+          <Box sx={{ mt: "5px", textAlign: "right" }}>
+            {t(`goalProgress${progress.goal.unit}`, {
+              done: progress.done,
+              amount: progress.goal.amount,
+            })}
+            {progress.is_met && ` · ${t("goalMet")}`}
+          </Box>
+        </Box>
+      )}
+    </ListItem>
+  );
+
+  return (
+    <List>
+      <ListSubheader>{t("goals")}</ListSubheader>
+      {goalItem("daily", goals.daily, goalsSummary.daily)}
+      {goalItem("weekly", goals.weekly, goalsSummary.weekly)}
+      <ListItem sx={listItemSx}>
+        <Box>{t("streak")}</Box>
+        <Box>{t("streakDays", { count: goalsSummary.streak })}</Box>
+      </ListItem>
+
+      <ListSubheader>{t("statistics")}</ListSubheader>
+      <ListItem sx={listItemSx}>
+        <Box>{t("today")}</Box>
+        {totals(stats.today)}
+      </ListItem>
+      <ListItem sx={listItemSx}>
+        <Box>{t("thisWeek")}</Box>
+        {totals(stats.this_week)}
+      </ListItem>
+      <ListItem sx={listItemSx}>
+        <Box>{t("readingSpeed")}</Box>
+        <Box>
+          {stats.speed === null
+            ? t("readingSpeedUnknown")
+            : t("charactersPerMinute", { count: Math.round(stats.speed) })}
+        </Box>
+      </ListItem>
+
+      <ListSubheader>{t("last7Days")}</ListSubheader>
+      {[...stats.days].reverse().map((day) => (
+        <ListItem key={day.date} sx={listItemSx}>
+          <Box>{day.date}</Box>
+          {totals(day)}
+        </ListItem>
+      ))}
+    </List>
+  );
+}
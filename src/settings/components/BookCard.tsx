@@ -11,24 +11,39 @@ import {
   Typography,
 } from "@mui/material";
 import { t } from "i18next";
+import { TimeLeft } from "../../util";
 
 interface Props {
   title: string;
   summary: string;
+  timeLeft?: TimeLeft | null;
   showContextMenu?: boolean;
   onSelect?: () => void;
   onContextMenu?: () => void;
   onRename?: () => void;
+  onChineseConversion?: () => void;
+  onSettings?: () => void;
+  onBookmarks?: () => void;
+  onHighlights?: () => void;
+  onSearch?: () => void;
+  onGoBack?: () => void;
   onRemove?: () => void;
 }
 
 export default function BookCard({
   title,
   summary,
+  timeLeft,
   showContextMenu,
   onSelect,
   onContextMenu,
   onRename,
+  onChineseConversion,
+  onSettings,
+  onBookmarks,
+  onHighlights,
+  onSearch,
+  onGoBack,
   onRemove,
 }: Props) {
   return (
@@ -55,6 +70,17 @@ export default function BookCard({
             >
               {summary}
             </Typography>
+            {timeLeft && (
+              <Typography variant="caption" color="text.secondary">
+                {timeLeft.chapter_seconds !== null &&
+                  `${t("timeLeftInChapter", {
+                    time: formatDuration(timeLeft.chapter_seconds),
+                  })} · `}
+                {t("timeLeftInBook", {
+                  time: formatDuration(timeLeft.book_seconds),
+                })}
+              </Typography>
+            )}
             {/* TODO: Add a bottom tool bar */}
           </CardContent>
         </CardActionArea>
@@ -87,6 +113,36 @@ export default function BookCard({
               <ListItemText primary={t("rename")} />
             </ListItemButton>
           </ListItem>
+          <ListItem onClick={onChineseConversion} disablePadding>
+            <ListItemButton>
+              <ListItemText primary={t("chineseConversion")} />
+            </ListItemButton>
+          </ListItem>
+          <ListItem onClick={onSettings} disablePadding>
+            <ListItemButton>
+              <ListItemText primary={t("bookSettings")} />
+            </ListItemButton>
+          </ListItem>
+          <ListItem onClick={onBookmarks} disablePadding>
+            <ListItemButton>
+              <ListItemText primary={t("bookmarks")} />
+            </ListItemButton>
+          </ListItem>
+          <ListItem onClick={onHighlights} disablePadding>
+            <ListItemButton>
+              <ListItemText primary={t("highlights")} />
+            </ListItemButton>
+          </ListItem>
+          <ListItem onClick={onSearch} disablePadding>
+            <ListItemButton>
+              <ListItemText primary={t("searchInBook")} />
+            </ListItemButton>
+          </ListItem>
+          <ListItem onClick={onGoBack} disablePadding>
+            <ListItemButton>
+              <ListItemText primary={t("goBack")} />
+            </ListItemButton>
+          </ListItem>
           <ListItem onClick={onRemove} disablePadding>
             <ListItemButton>
               <ListItemText primary={t("remove")} />
@@ -97,3 +153,14 @@ export default function BookCard({
     </Box>
   );
 }
+
+function formatDuration(seconds: number) {
+  const minutes = Math.max(1, Math.round(seconds / 60));
+  if (minutes < 60) {
+    return t("durationMinutes", { minutes });
+  }
+  return t("durationHoursMinutes", {
+    hours: Math.floor(minutes / 60),
+    minutes: minutes % 60,
+  });
+}
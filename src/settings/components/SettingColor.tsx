@@ -0,0 +1,103 @@
+import { Box, ListItem, SxProps, Theme } from "@mui/material";
+import { useEffect, useRef, useState } from "react";
+import { HexAlphaColorPicker } from "react-colorful";
+
+interface Props {
+  defaultValue: string;
+  /** Called on every change, to preview the color. */
+  onChange?: (color: string) => void;
+  /** Called once the color is picked, to persist it. */
+  onCommit?: (color: string) => void;
+
+  children: React.ReactNode;
+}
+
+const listItemSx: SxProps<Theme> = {
+  display: "flex",
+  justifyContent: "space-between",
+  alignItems: "center",
+};
+
+export default function SettingColor({
+  defaultValue,
+  onChange,
+  onCommit,
+  children,
+}: Props) {
+  const [color, setColor] = useState(defaultValue);
+  const [paletteOpen, setPaletteOpen] = useState(false);
+  const paletteRef = useRef<HTMLElement>(null);
+  const colorBoxRef = useRef<HTMLElement>(null);
+
+  useEffect(() => {
+    const handleClickPaletteOutside = (event: MouseEvent) => {
+      if (!(event.target instanceof HTMLElement)) {
+        console.error("event.target is not HTMLElement");
+        return;
+      }
+      if (
+        paletteRef.current?.contains(event.target) ||
+        colorBoxRef.current?.contains(event.target)
+      ) {
+        return;
+      }
+      setPaletteOpen(false);
+    };
+
+    document.addEventListener("mouseup", handleClickPaletteOutside);
+    return () => {
+      document.removeEventListener("mouseup", handleClickPaletteOutside);
+    };
+  }, []);
+
+  const onColorSliderChange = (color: string) => {
+    setColor(color);
+    onChange?.(color);
+  };
+
+  return (
+    <ListItem sx={listItemSx}>
+      <Box>{children}</Box>
+      <Box
+        sx={{
+          padding: "8px",
+          border: "2px dashed black",
+          borderRadius: "5px",
+          width: "75px",
+          height: "50px",
+          position: "relative",
+        }}
+      >
+        <Box
+          onClick={() => setPaletteOpen((open) => !open)}
+          ref={colorBoxRef}
+          sx={{
+            width: "100%",
+            height: "100%",
+            bgcolor: color,
+            borderRadius: "5px",
+            cursor: "pointer",
+          }}
+        />
+        {paletteOpen && (
+          <Box
+            ref={paletteRef}
+            sx={{
+              position: "absolute",
+              top: "60px",
+              left: "50px",
+              transform: "translateX(-100%)",
+              zIndex: 1,
+            }}
+          >
+            <HexAlphaColorPicker
+              color={color}
+              onChange={onColorSliderChange}
+              onMouseUp={() => onCommit?.(color)}
+            />
+          </Box>
+        )}
+      </Box>
+    </ListItem>
+  );
+}
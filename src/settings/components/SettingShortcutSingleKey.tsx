@@ -3,11 +3,13 @@ import {
   Button,
   Dialog,
   DialogContent,
+  IconButton,
   ListItem,
   SxProps,
   Theme,
   useTheme,
 } from "@mui/material";
+import { ClearOutlined } from "@mui/icons-material";
 import { listen } from "@tauri-apps/api/event";
 import { MouseEventHandler, useEffect, useState } from "react";
 import { useTranslation } from "react-i18next";
@@ -23,8 +25,11 @@ import {
 interface Props {
   name: string;
   contentSx?: SxProps<Theme>;
-  keyButton: KeyButton;
+  /** `null` if not bound. */
+  keyButton: KeyButton | null;
   onChangeKeyButton: (code: KeyButton) => void;
+  /** Given for shortcuts that can be unbound. */
+  onClearKeyButton?: () => void;
   allowWheel?: boolean;
   children: React.ReactNode;
 }
@@ -39,6 +44,7 @@ export default function SettingShortcutSingleKey({
   contentSx,
   keyButton,
   onChangeKeyButton,
+  onClearKeyButton,
   allowWheel,
   children,
 }: Props) {
@@ -96,13 +102,20 @@ export default function SettingShortcutSingleKey({
   return (
     <ListItem sx={listItemSx}>
       <Box sx={contentSx}>{children}</Box>
-      <Button
-        variant="text"
-        onClick={onClickButton}
-        style={{ textTransform: "none" }}
-      >
-        {keyButtonToString(keyButton)}
-      </Button>
+      <Box>
+        <Button
+          variant="text"
+          onClick={onClickButton}
+          style={{ textTransform: "none" }}
+        >
+          {keyButton === null ? t("notBound") : keyButtonToString(keyButton)}
+        </Button>
+        {onClearKeyButton && keyButton !== null && (
+          <IconButton size="small" onClick={onClearKeyButton}>
+            <ClearOutlined fontSize="small" />
+          </IconButton>
+        )}
+      </Box>
 
       <Dialog
         sx={{ display: "flex", justifyContent: "center", alignItems: "center" }}
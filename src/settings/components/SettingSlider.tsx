@@ -0,0 +1,71 @@
+import { Box, Input, ListItem, Slider, SxProps, Theme } from "@mui/material";
+import { useState } from "react";
+
+interface Props {
+  defaultValue: number;
+  min: number;
+  max: number;
+  step: number;
+  /** Called on every change, to preview the value. */
+  onChange?: (value: number) => void;
+  /** Called once the value is settled, to persist it. */
+  onCommit?: (value: number) => void;
+
+  children: React.ReactNode;
+}
+
+const listItemSx: SxProps<Theme> = {
+  display: "flex",
+  justifyContent: "space-between",
+  alignItems: "center",
+};
+
+export default function SettingSlider({
+  defaultValue,
+  min,
+  max,
+  step,
+  onChange,
+  onCommit,
+  children,
+}: Props) {
+  const [value, setValue] = useState(defaultValue);
+
+  const change = (newValue: number) => {
+    setValue(newValue);
+    onChange?.(newValue);
+  };
+
+  const onInputBlur = () => {
+    const newValue = Math.min(Math.max(value, min), max);
+    change(newValue);
+    onCommit?.(newValue);
+  };
+
+  return (
+    <ListItem sx={listItemSx}>
+      <Box>{children}</Box>
+      <Box sx={{ display: "flex" }}>
+        <Slider
+          value={value}
+          onChange={(_event, value) => change(value as number)}
+          onChangeCommitted={(_event, value) => onCommit?.(value as number)}
+          min={min}
+          max={max}
+          step={step}
+          shiftStep={step * 4}
+          sx={{ width: "150px" }}
+        />
+
+        <Input
+          value={value}
+          size="small"
+          inputProps={{ step, min, max, type: "number" }}
+          onChange={(event) => change(Number(event.target.value) || 0)}
+          onBlur={onInputBlur}
+          sx={{ ml: "30px" }}
+        />
+      </Box>
+    </ListItem>
+  );
+}
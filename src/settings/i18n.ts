@@ -11,10 +11,85 @@ const resources: Record<string, { translation: Translation }> = {
       adjustReadingWindow: "Adjust reading window",
       textSize: "Text size",
       textColorAndTransparency: "Text color and transparency",
+      textColor: "Text color (#RRGGBBAA)",
+      lineHeight: "Line height",
+      fontFamily: "Font",
+      bookSettings: "Reading settings",
+      letterSpacing: "Letter spacing (em)",
+      paragraphSpacing: "Paragraph spacing (em)",
+      firstLineIndent: "First-line indent (em)",
+      defaultFont: "Default font",
+      textStroke: "Text outline",
+      textShadow: "Text shadow",
+      backgroundPlate: "Background plate",
+      enabled: "Enabled",
+      widthPx: "Width (px)",
+      offsetXPx: "Horizontal offset (px)",
+      offsetYPx: "Vertical offset (px)",
+      blurPx: "Blur (px)",
+      cornerRadiusPx: "Corner radius (px)",
+      colorAndTransparency: "Color and transparency",
+      camouflage: "Camouflage (replaces font, colors and decorations)",
+      camouflageNone: "None",
+      camouflageTerminal: "Terminal",
+      camouflageCodeEditor: "Code editor",
+      camouflageSpreadsheet: "Spreadsheet",
+      camouflageChat: "Chat",
+      readerPosition: "Reader position",
+      anchor: "Keep the reader at",
+      anchorNone: "Where it is put",
+      anchorTopLeft: "Top left",
+      anchorTop: "Top",
+      anchorTopRight: "Top right",
+      anchorLeft: "Left",
+      anchorCenter: "Center",
+      anchorRight: "Right",
+      anchorBottomLeft: "Bottom left",
+      anchorBottom: "Bottom",
+      anchorBottomRight: "Bottom right",
+      anchorAboveTaskbar: "Next to the taskbar",
+      marginXPx: "Horizontal margin (px)",
+      marginYPx: "Vertical margin (px)",
+      logStream: "Log stream",
+      logStreamExplain: "Show the text as application log lines",
+      maxLogLineLength: "Longest log line (characters)",
+      useGlobalSetting: "Same as the global setting",
+      profiles: "Profiles",
+      activeProfile: "active",
+      profileName: "Profile name",
+      saveAsProfile: "Save as profile",
+      applyProfile: "Apply",
+      overwriteProfile: "Overwrite with the current appearance",
+      applyForTheseMonitors:
+        "Apply automatically when the monitors are arranged as they are now",
+      currentAppearance: "Current appearance",
+      nextProfile: "Next profile",
+      notBound: "Not set",
       font: "Font",
       appearance: "Appearance",
       control: "Control",
       library: "Library",
+      statistics: "Statistics",
+      today: "Today",
+      thisWeek: "This week",
+      readingSpeed: "Reading speed",
+      readingSpeedUnknown: "Not enough reading yet",
+      charactersPerMinute: "{{count}} characters/min",
+      readingTime: "{{minutes}} min",
+      charactersRead: "{{count}} characters",
+      last7Days: "Last 7 days",
+      goals: "Goals",
+      dailyGoal: "Daily goal",
+      weeklyGoal: "Weekly goal",
+      noGoal: "None",
+      goalUnitMinutes: "Minutes",
+      goalUnitCharacters: "Characters",
+      goalProgressMinutes: "{{done}} / {{amount}} min",
+      goalProgressCharacters: "{{done}} / {{amount}} characters",
+      goalMet: "Reached",
+      streak: "Streak",
+      streakDays_one: "{{count}} day",
+      streakDays_other: "{{count}} days",
       about: "About",
       verySafeOption: "Very safe",
       verySafeExplain:
@@ -33,7 +108,6 @@ const resources: Record<string, { translation: Translation }> = {
       title: "Title",
       content: "Content",
       titleAndContentRequired: "Title and content cannot be empty",
-      bookWithSameTitleExists: "Book with same title already exists",
       importedAllBooks: "{{countSuccess}} book(s) imported",
       importedSomeBooks:
         "{{countSuccess}} book(s) imported, {{countFail}} book(s) not imported",
@@ -47,11 +121,103 @@ const resources: Record<string, { translation: Translation }> = {
       plainTextFiles: "Plain text files",
       read: "Read",
       rename: "Rename",
+      bookmarks: "Bookmarks",
+      addBookmark: "Add bookmark",
+      addBookmarkHere: "Bookmark where I am",
+      bookmarkNote: "Note",
+      noBookmarks: "No bookmarks yet",
+      jumpToBookmark: "Read from here",
+      commandError_BookmarkNotFound:
+        "The bookmark is no longer there. Please reopen the bookmarks.",
+      highlights: "Highlights",
+      highlightModeExplain:
+        "Select text in the reader to highlight it (the reader can't be moved meanwhile)",
+      highlightNote: "Note",
+      saveHighlight: "Highlight",
+      noHighlights: "No highlights yet. Add them on the appearance page.",
+      exportAnnotations: "Export bookmarks and highlights",
+      exportMarkdown: "Export as Markdown",
+      exportJson: "Export as JSON",
+      commandError_HighlightNotFound:
+        "The highlight is no longer there. Please reopen the highlights.",
+      commandError_EmptyHighlight: "Please select some text to highlight.",
+      searchInBook: "Search in book",
+      search: "Search",
+      searchIgnoreCase: "Ignore case",
+      searchIgnoreWidth: "Ignore fullwidth/halfwidth",
+      searchRegex: "Regular expression",
+      searchMatches_one: "{{count}} match",
+      searchMatches_other: "{{count}} matches",
+      searchMatchesTruncated: "Showing the first {{count}} matches",
+      searchMatchesAtLeast: "{{count}}+ matches",
+      searchLibrary: "Search library",
+      noSearchHits: "Not found in any book",
+      goBack: "Go back to where I was",
+      commandError_HistoryEmpty: "There is no earlier position to go back to.",
+      commandError_InvalidSearchPattern:
+        "The regular expression is invalid: {{message}}",
+      timeLeftInChapter: "{{time}} left in chapter",
+      timeLeftInBook: "{{time}} left in book",
+      durationMinutes: "{{minutes}} min",
+      durationHoursMinutes: "{{hours}} h {{minutes}} min",
       remove: "Remove",
       cannotRemoveLastBook: "Cannot remove last book",
       version: "Version",
       author: "Author",
       email: "Email",
+      normalization: "Normalize text when importing",
+      normalization_full_width_to_half_width:
+        "Convert full-width letters and digits to half-width",
+      normalization_unify_quotes: "Unify quotation marks",
+      normalization_unify_ellipses: "Unify ellipses",
+      normalization_collapse_repeated_punctuation:
+        "Collapse repeated punctuation",
+      normalization_collapse_spaces: "Collapse repeated spaces",
+      normalization_nfkc: "Unicode NFKC normalization",
+      cleanupRules: "Cleanup rules",
+      cleanupRulesExplain:
+        "Rules are applied in order to every line of imported books.",
+      deleteLine: "Delete line",
+      replace: "Replace",
+      pattern: "Pattern (regex)",
+      replacement: "Replacement",
+      addRule: "Add rule",
+      save: "Save",
+      preview: "Preview",
+      previewFile: "Choose a text file to preview the rules on",
+      cannotReadPreviewFile: "The text file could not be read.",
+      before: "Before",
+      after: "After",
+      nothingChanged: "Nothing changed",
+      chineseConversionOnImport: "Convert Chinese when importing",
+      chineseConversion: "Chinese conversion",
+      chineseConversionNone: "No conversion",
+      chineseConversionSimplifiedToTraditional: "Simplified to Traditional",
+      chineseConversionTraditionalToSimplified: "Traditional to Simplified",
+      configCorrupt:
+        "The config file could not be read. The broken file is kept at {{badFile}}.",
+      configMissing: "The config file was missing.",
+      configRestoredFromBackup: "Settings were restored from a backup.",
+      configResetToDefaults:
+        "Nothing could be recovered, so all settings were reset to their defaults.",
+      configFieldsReset:
+        "These settings were reset to their defaults: {{fields}}.",
+      configNotSaved:
+        "The recovered settings could not be saved, and are only used until the app quits: {{message}}",
+      configFromNewerVersion:
+        "The config file is from a newer version of the app, so changes to settings are not saved, and are only used until the app quits.",
+      libraryCorrupt:
+        "The library could not be read. The broken metadata file is kept at {{badFile}}.",
+      libraryBooksRecovered_one:
+        "{{count}} book was found again, titled by its first line and read from the start.",
+      libraryBooksRecovered_other:
+        "{{count}} books were found again, titled by their first lines and read from the start.",
+      libraryNotSaved: "The recovered library could not be saved: {{message}}",
+      commandError: "Something went wrong: {{message}}",
+      commandError_BookNotFound:
+        "The book is no longer in the library. Please reopen the settings window.",
+      commandError_Io: "Cannot read or write a file: {{message}}",
+      commandError_InvalidCleanupRule: "Please fix the cleanup rules. {{message}}",
       trialVersionHint:
         "You are using the trial version, which only supports left CTRL, left ALT, left SHIFT, mouse left button and mouse wheel down. To use any key/button as shortcut, please purchase the full version.",
     },
@@ -63,10 +229,83 @@ const resources: Record<string, { translation: Translation }> = {
       adjustReadingWindow: "调整阅读窗口",
       textSize: "文本大小",
       textColorAndTransparency: "文本颜色与透明度",
+      textColor: "文本颜色（#RRGGBBAA）",
+      lineHeight: "行高",
+      fontFamily: "字体",
+      bookSettings: "阅读设置",
+      letterSpacing: "字间距（em）",
+      paragraphSpacing: "段间距（em）",
+      firstLineIndent: "首行缩进（em）",
+      defaultFont: "默认字体",
+      textStroke: "文字描边",
+      textShadow: "文字阴影",
+      backgroundPlate: "背景板",
+      enabled: "启用",
+      widthPx: "宽度（px）",
+      offsetXPx: "水平偏移（px）",
+      offsetYPx: "垂直偏移（px）",
+      blurPx: "模糊（px）",
+      cornerRadiusPx: "圆角半径（px）",
+      colorAndTransparency: "颜色与透明度",
+      camouflage: "伪装（替换字体、颜色与装饰）",
+      camouflageNone: "无",
+      camouflageTerminal: "终端",
+      camouflageCodeEditor: "代码编辑器",
+      camouflageSpreadsheet: "电子表格",
+      camouflageChat: "聊天",
+      readerPosition: "阅读器位置",
+      anchor: "阅读器固定于",
+      anchorNone: "放置的位置",
+      anchorTopLeft: "左上",
+      anchorTop: "上方",
+      anchorTopRight: "右上",
+      anchorLeft: "左侧",
+      anchorCenter: "中央",
+      anchorRight: "右侧",
+      anchorBottomLeft: "左下",
+      anchorBottom: "下方",
+      anchorBottomRight: "右下",
+      anchorAboveTaskbar: "任务栏旁",
+      marginXPx: "水平边距（px）",
+      marginYPx: "垂直边距（px）",
+      logStream: "日志流",
+      logStreamExplain: "将文本显示为应用程序日志",
+      maxLogLineLength: "日志行最大长度（字符）",
+      useGlobalSetting: "与全局设置相同",
+      profiles: "配置方案",
+      activeProfile: "当前",
+      profileName: "方案名称",
+      saveAsProfile: "保存为方案",
+      applyProfile: "应用",
+      overwriteProfile: "用当前外观覆盖",
+      applyForTheseMonitors: "显示器排布与现在相同时自动应用",
+      currentAppearance: "当前外观",
+      nextProfile: "下一个配置方案",
+      notBound: "未设置",
       font: "字体",
       appearance: "外观",
       control: "控制",
       library: "图书馆",
+      statistics: "统计",
+      today: "今天",
+      thisWeek: "本周",
+      readingSpeed: "阅读速度",
+      readingSpeedUnknown: "阅读量尚不足",
+      charactersPerMinute: "{{count}} 字/分钟",
+      readingTime: "{{minutes}} 分钟",
+      charactersRead: "{{count}} 字",
+      last7Days: "最近 7 天",
+      goals: "目标",
+      dailyGoal: "每日目标",
+      weeklyGoal: "每周目标",
+      noGoal: "无",
+      goalUnitMinutes: "分钟",
+      goalUnitCharacters: "字数",
+      goalProgressMinutes: "{{done}} / {{amount}} 分钟",
+      goalProgressCharacters: "{{done}} / {{amount}} 字",
+      goalMet: "已达成",
+      streak: "连续天数",
+      streakDays: "{{count}} 天",
       about: "关于",
       verySafeOption: "非常安全",
       verySafeExplain: "双击，但是第二次不要松开按键以显示。松开按键以隐藏。",
@@ -84,7 +323,6 @@ const resources: Record<string, { translation: Translation }> = {
       title: "标题",
       content: "内容",
       titleAndContentRequired: "标题和内容不能为空",
-      bookWithSameTitleExists: "已存在同名图书",
       importedAllBooks: "成功导入 {{countSuccess}} 本图书",
       importedSomeBooks:
         "成功导入 {{countSuccess}} 本图书，失败导入 {{countFail}} 本图书",
@@ -98,11 +336,87 @@ const resources: Record<string, { translation: Translation }> = {
       plainTextFiles: "纯文本文件",
       read: "阅读",
       rename: "重命名",
+      bookmarks: "书签",
+      addBookmark: "添加书签",
+      addBookmarkHere: "在当前位置添加书签",
+      bookmarkNote: "备注",
+      noBookmarks: "还没有书签",
+      jumpToBookmark: "从这里开始阅读",
+      commandError_BookmarkNotFound: "书签已不存在。请重新打开书签。",
+      highlights: "划线",
+      highlightModeExplain: "在阅读器中选中文本以划线（期间无法移动阅读器）",
+      highlightNote: "备注",
+      saveHighlight: "划线",
+      noHighlights: "还没有划线。可在外观页面添加。",
+      exportAnnotations: "导出书签和划线",
+      exportMarkdown: "导出为 Markdown",
+      exportJson: "导出为 JSON",
+      commandError_HighlightNotFound: "划线已不存在。请重新打开划线。",
+      commandError_EmptyHighlight: "请选中要划线的文本。",
+      searchInBook: "书内搜索",
+      search: "搜索",
+      searchIgnoreCase: "忽略大小写",
+      searchIgnoreWidth: "忽略全角/半角",
+      searchRegex: "正则表达式",
+      searchMatches: "{{count}} 处匹配",
+      searchMatchesTruncated: "仅显示前 {{count}} 处匹配",
+      searchMatchesAtLeast: "{{count}}+ 处匹配",
+      searchLibrary: "搜索书库",
+      noSearchHits: "没有找到",
+      goBack: "回到之前的位置",
+      commandError_HistoryEmpty: "没有可以回到的之前位置。",
+      commandError_InvalidSearchPattern: "正则表达式无效：{{message}}",
+      timeLeftInChapter: "本章还需 {{time}}",
+      timeLeftInBook: "全书还需 {{time}}",
+      durationMinutes: "{{minutes}} 分钟",
+      durationHoursMinutes: "{{hours}} 小时 {{minutes}} 分钟",
       remove: "删除",
       cannotRemoveLastBook: "无法删除最后一本图书",
       version: "版本",
       author: "作者",
       email: "电邮",
+      normalization: "导入时规范化文本",
+      normalization_full_width_to_half_width: "全角字母和数字转为半角",
+      normalization_unify_quotes: "统一引号",
+      normalization_unify_ellipses: "统一省略号",
+      normalization_collapse_repeated_punctuation: "合并重复的标点",
+      normalization_collapse_spaces: "合并连续的空格",
+      normalization_nfkc: "Unicode NFKC 规范化",
+      cleanupRules: "清理规则",
+      cleanupRulesExplain: "规则会按顺序应用到导入图书的每一行。",
+      deleteLine: "删除行",
+      replace: "替换",
+      pattern: "模式（正则表达式）",
+      replacement: "替换为",
+      addRule: "添加规则",
+      save: "保存",
+      preview: "预览",
+      previewFile: "选择用于预览规则的文本文件",
+      cannotReadPreviewFile: "无法读取该文本文件。",
+      before: "之前",
+      after: "之后",
+      nothingChanged: "没有变化",
+      chineseConversionOnImport: "导入时转换中文",
+      chineseConversion: "简繁转换",
+      chineseConversionNone: "不转换",
+      chineseConversionSimplifiedToTraditional: "简体转繁体",
+      chineseConversionTraditionalToSimplified: "繁体转简体",
+      configCorrupt: "无法读取配置文件。损坏的文件已保存为 {{badFile}}。",
+      configMissing: "配置文件丢失。",
+      configRestoredFromBackup: "已从备份恢复设置。",
+      configResetToDefaults: "无法恢复任何设置，所有设置已恢复为默认值。",
+      configFieldsReset: "以下设置已恢复为默认值：{{fields}}。",
+      configNotSaved: "无法保存恢复的设置，仅在退出前有效：{{message}}",
+      configFromNewerVersion:
+        "配置文件来自更新版本的应用，因此不会保存对设置的更改，仅在退出前有效。",
+      libraryCorrupt: "无法读取书库。损坏的元数据文件已保存为 {{badFile}}。",
+      libraryBooksRecovered:
+        "已找回 {{count}} 本书，书名取自其第一行，并从头开始阅读。",
+      libraryNotSaved: "无法保存恢复的书库：{{message}}",
+      commandError: "出错了：{{message}}",
+      commandError_BookNotFound: "书库中已没有这本书。请重新打开设置窗口。",
+      commandError_Io: "无法读写文件：{{message}}",
+      commandError_InvalidCleanupRule: "请修正清理规则。{{message}}",
       trialVersionHint:
         "您正在使用试用版，仅支持左 CTRL，左 ALT，左 SHIFT，鼠标左键和鼠标滚轮向下。若想使用任何键或按钮作为快捷键，请购买完整版。",
     },
@@ -1,13 +1,112 @@
 import { invoke, InvokeArgs, InvokeOptions } from "@tauri-apps/api/core";
+import { readFile } from "@tauri-apps/plugin-fs";
+import * as jschardet from "jschardet";
 
 export interface Config {
+  schema_version: number;
   appearance: Appearance;
+  profiles: AppearanceProfile[];
+  active_profile: string | null;
   control: Control;
+  import: Import;
+  goals: ReadingGoals;
 }
 
 export interface Appearance {
+  reader_anchor: ReaderAnchor;
   text_size: number;
   text_color: string;
+  font_family: string;
+  line_height: number;
+  letter_spacing: number;
+  paragraph_spacing: number;
+  first_line_indent: number;
+  text_stroke: TextStroke;
+  text_shadow: TextShadow;
+  background_plate: BackgroundPlate;
+  camouflage: Camouflage;
+  log_stream: LogStreamOptions;
+}
+
+export type Anchor =
+  | "None"
+  | "TopLeft"
+  | "Top"
+  | "TopRight"
+  | "Left"
+  | "Center"
+  | "Right"
+  | "BottomLeft"
+  | "Bottom"
+  | "BottomRight"
+  | "AboveTaskbar";
+
+export const anchors: Anchor[] = [
+  "None",
+  "TopLeft",
+  "Top",
+  "TopRight",
+  "Left",
+  "Center",
+  "Right",
+  "BottomLeft",
+  "Bottom",
+  "BottomRight",
+  "AboveTaskbar",
+];
+
+/** Margins are in logical pixels, see `config::ReaderAnchor`. */
+export interface ReaderAnchor {
+  anchor: Anchor;
+  margin_x: number;
+  margin_y: number;
+}
+
+export interface AppearanceProfile {
+  name: string;
+  /** The monitor layout it is applied for automatically, if any. */
+  monitor_layout: string | null;
+  appearance: Appearance;
+}
+
+export type Camouflage =
+  | "None"
+  | "Terminal"
+  | "CodeEditor"
+  | "Spreadsheet"
+  | "Chat";
+
+export const camouflages: Camouflage[] = [
+  "None",
+  "Terminal",
+  "CodeEditor",
+  "Spreadsheet",
+  "Chat",
+];
+
+export interface LogStreamOptions {
+  enabled: boolean;
+  max_line_length: number;
+}
+
+export interface TextStroke {
+  enabled: boolean;
+  width: number;
+  color: string;
+}
+
+export interface TextShadow {
+  enabled: boolean;
+  offset_x: number;
+  offset_y: number;
+  blur: number;
+  color: string;
+}
+
+export interface BackgroundPlate {
+  enabled: boolean;
+  color: string;
+  corner_radius: number;
 }
 
 export interface Control {
@@ -20,20 +119,229 @@ export interface ControlBasic {
   show_hide: string;
   next_page: string;
   prev_page: string;
+  next_profile: KeyButton | null;
+  add_bookmark: KeyButton | null;
+  go_back: KeyButton | null;
+}
+
+export interface ConfigRecovery {
+  bad_file: string | null;
+  is_from_backup: boolean;
+  reset_fields: string[];
+  is_reset_to_defaults: boolean;
+  write_error: string | null;
+  is_from_newer_version: boolean;
+}
+
+export interface LibraryRecovery {
+  bad_file: string;
+  recovered_count: number;
+  write_error: string | null;
+}
+
+export interface Import {
+  normalization: NormalizeOptions;
+  cleanup_rules: CleanupRule[];
+  chinese_conversion: ChineseConversion;
+}
+
+export interface NormalizeOptions {
+  nfkc: boolean;
+  full_width_to_half_width: boolean;
+  unify_quotes: boolean;
+  unify_ellipses: boolean;
+  collapse_repeated_punctuation: boolean;
+  collapse_spaces: boolean;
+}
+
+export type ChineseConversion =
+  | "None"
+  | "SimplifiedToTraditional"
+  | "TraditionalToSimplified";
+
+export const chineseConversions: ChineseConversion[] = [
+  "None",
+  "SimplifiedToTraditional",
+  "TraditionalToSimplified",
+];
+
+export type CleanupRule =
+  | { kind: "Replace"; pattern: string; replacement: string }
+  | { kind: "DeleteLine"; pattern: string };
+
+export interface CleanupPreviewLine {
+  line_number: number;
+  before: string;
+  after: string | null;
 }
 
 export interface Book {
+  id: string;
   title: string;
   summary: string;
   total_character_count: number;
   progress: number;
   last_read_time: number;
+  chinese_conversion: ChineseConversion;
+  settings: BookSettings;
+  /** In the same units as `progress`. */
+  chapter_starts: number[] | null;
+  /** Sorted by offset. */
+  bookmarks: Bookmark[];
+  /** Sorted by start. */
+  highlights: Highlight[];
+  /** Where the book was read before it moved far away, oldest first. */
+  history: HistoryEntry[];
+}
+
+export interface Bookmark {
+  id: number;
+  /** In the same units as `Book.progress`. */
+  offset: number;
+  created_time: number;
+  note: string | null;
+  snippet: string;
+}
+
+export interface TimeLeft {
+  /** `null` if the progress is not in a chapter. */
+  chapter_seconds: number | null;
+  book_seconds: number;
+}
+
+export interface LibraryBook extends Book {
+  /** `null` until the reading speed is known. */
+  time_left: TimeLeft | null;
+}
+
+/** Reading settings of a single book. `null` falls back to the global one. */
+export interface BookSettings {
+  text_size: number | null;
+  text_color: string | null;
+  line_height: number | null;
+  font_family: string | null;
 }
 
 export interface ReaderBookInfo {
+  id: string;
   title: string;
   content: string;
   progress: number;
+  settings: BookSettings;
+  /** Whether `content` was turned into fake log lines. */
+  is_log_stream: boolean;
+}
+
+export interface ReadingTotals {
+  seconds: number;
+  characters: number;
+}
+
+export interface DailyTotals extends ReadingTotals {
+  /** `YYYY-MM-DD`, in local time. */
+  date: string;
+}
+
+export interface ReadingStats {
+  today: ReadingTotals;
+  this_week: ReadingTotals;
+  /** The last 7 days, oldest first. */
+  days: DailyTotals[];
+  /** In characters per minute. */
+  speed: number | null;
+}
+
+export type GoalUnit = "Minutes" | "Characters";
+
+export const goalUnits: GoalUnit[] = ["Minutes", "Characters"];
+
+export interface Goal {
+  unit: GoalUnit;
+  amount: number;
+}
+
+export interface ReadingGoals {
+  daily: Goal | null;
+  /** From Monday to Sunday. */
+  weekly: Goal | null;
+}
+
+export interface GoalProgress {
+  goal: Goal;
+  /** In the unit of the goal. */
+  done: number;
+  is_met: boolean;
+}
+
+export interface GoalsSummary {
+  daily: GoalProgress | null;
+  weekly: GoalProgress | null;
+  /** Days in a row the daily goal was met, or anything was read. */
+  streak: number;
+}
+
+export interface HistoryEntry {
+  /** In the same units as `Book.progress`. */
+  progress: number;
+  /** When the book was moved away from it. */
+  time: number;
+}
+
+export interface Highlight {
+  id: number;
+  /** In the same units as `Book.progress`, `end` excluded. */
+  start: number;
+  end: number;
+  created_time: number;
+  note: string | null;
+  text: string;
+}
+
+/** Text selected in the reader, in offsets into its content. */
+export interface ReaderSelection {
+  bookId: string;
+  start: number;
+  end: number;
+  text: string;
+}
+
+export type ExportFormat = "Markdown" | "Json";
+
+export interface SearchOptions {
+  ignore_case: boolean;
+  /** Fullwidth letters, digits and punctuation match their halfwidth forms. */
+  ignore_width: boolean;
+  regex: boolean;
+}
+
+/** In the same units as `Book.progress`, with the text around it. */
+export interface SearchMatch {
+  start: number;
+  end: number;
+  before: string;
+  text: string;
+  after: string;
+}
+
+export interface SearchResult {
+  matches: SearchMatch[];
+  is_truncated: boolean;
+}
+
+/** A book found by a search of the library. */
+export interface LibrarySearchHit {
+  book_id: string;
+  title: string;
+  score: number;
+  /** The first few places the query is in the book. */
+  matches: SearchMatch[];
+  match_count: number;
+  is_truncated: boolean;
+}
+
+export interface NewBookInfo {
+  title: string;
+  content: string;
 }
 
 export interface NewBooksResult {
@@ -65,11 +373,40 @@ export interface BackendKeyButtonDownInfo {
 
 const promises: Promise<void>[] = [new Promise((resolve) => resolve())];
 
-export async function invokeCommand<T = unknown>(
+/** An error returned by a command, as serialized by `error::Error`. */
+export interface CommandError {
+  kind: string;
+  message: string;
+}
+
+export type CommandResult<T> =
+  | { ok: true; value: T }
+  | { ok: false; error: CommandError };
+
+/** Dispatched on `window` when a command invoked by `invokeCommand` fails. */
+export const COMMAND_ERROR_EVENT = "command-error";
+
+function toCommandError(error: unknown): CommandError {
+  if (
+    typeof error === "object" &&
+    error !== null &&
+    "kind" in error &&
+    "message" in error
+  ) {
+    return { kind: String(error.kind), message: String(error.message) };
+  }
+  return { kind: "Unknown", message: String(error) };
+}
+
+/**
+ * Invoke a command after all previously invoked commands finish, and return
+ * either its value or the error it returned.
+ */
+export async function invokeCommandResult<T = unknown>(
   cmd: string,
   args?: InvokeArgs,
   options?: InvokeOptions
-): Promise<T | undefined> {
+): Promise<CommandResult<T>> {
   const prevPromise = promises[promises.length - 1];
   let resolveCommand;
 
@@ -82,11 +419,48 @@ export async function invokeCommand<T = unknown>(
   await prevPromise;
 
   try {
-    const ret = (await invoke(cmd, args, options)) as T;
+    const value = (await invoke(cmd, args, options)) as T;
+    return { ok: true, value };
+  } catch (error) {
+    return { ok: false, error: toCommandError(error) };
+  } finally {
     resolveCommand!();
-    return ret;
-  } catch (e) {
-    console.error(e);
+  }
+}
+
+export async function invokeCommand<T = unknown>(
+  cmd: string,
+  args?: InvokeArgs,
+  options?: InvokeOptions
+): Promise<T | undefined> {
+  const result = await invokeCommandResult<T>(cmd, args, options);
+  if (!result.ok) {
+    console.error(`Error calling '${cmd}'`, result.error);
+    window.dispatchEvent(
+      new CustomEvent<CommandError>(COMMAND_ERROR_EVENT, {
+        detail: result.error,
+      })
+    );
+    return undefined;
+  }
+  return result.value;
+}
+
+/**
+ * Read a text file in whatever encoding it is detected to be in, or `null` if
+ * it can't be decoded.
+ */
+export async function readTextFile(path: string): Promise<string | null> {
+  const rawContentBytes = await readFile(path);
+  const binaryString = Array.from(rawContentBytes)
+    .map((b) => String.fromCharCode(b))
+    .join("");
+  const detectResult = jschardet.detect(binaryString);
+  const decoder = new TextDecoder(detectResult.encoding);
+  try {
+    return decoder.decode(rawContentBytes);
+  } catch {
+    return null;
   }
 }
 